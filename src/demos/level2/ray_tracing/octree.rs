@@ -205,22 +205,22 @@ impl Octree {
 
     fn print_node(&self, node: &OctreeNode, depth: usize) {
         let indent = "  ".repeat(depth);
-        println!("{}Node at depth {}", indent, depth);
-        println!("{}Bounds: min({:.1}, {:.1}, {:.1}), max({:.1}, {:.1}, {:.1})",
+        log::debug!("{}Node at depth {}", indent, depth);
+        log::debug!("{}Bounds: min({:.1}, {:.1}, {:.1}), max({:.1}, {:.1}, {:.1})",
             indent, 
             node.bounds.min.x, node.bounds.min.y, node.bounds.min.z,
             node.bounds.max.x, node.bounds.max.y, node.bounds.max.z
         );
         
-        println!("{}Points: {}", indent, node.points.len());
+        log::debug!("{}Points: {}", indent, node.points.len());
         for (i, point) in node.points.iter().enumerate() {
-            println!("{}  Point {}: ({:.1}, {:.1}, {:.1})", 
+            log::debug!("{}  Point {}: ({:.1}, {:.1}, {:.1})", 
                 indent, i, point.x, point.y, point.z);
         }
         
         for (i, child) in node.children.iter().enumerate() {
             if let Some(child_node) = child {
-                println!("{}Child {} (octant {:?}):", indent, i, match i {
+                log::debug!("{}Child {} (octant {:?}):", indent, i, match i {
                     0 => Octant::NW,
                     1 => Octant::SW,
                     2 => Octant::NE,
@@ -1,17 +1,37 @@
 // shadow scene
 
-use depth_texture::{DepthTexture, LightStorage};
+use depth_texture::DepthTexture;
+use imported::ImportedScene;
 use plane::ShadowPlane;
 use wgpu::{util::DeviceExt, PipelineCompilationOptions};
 use world::World;
 
 use crate::{
-    console_log,
-    shared::ready_paint::{
-        get_res, get_res_mut, refs_muts, return_res, Mut, Pass, Ready, Ref, Update,
+    mesh::primitives,
+    shaders::LightUniform,
+    shared::{
+        hot_reload::{self, Reload},
+        params::{self, ParamDesc},
+        ready_paint::{get_res, get_res_mut, refs_muts, return_res, Mut, Pass, Ready, Ref, Update},
+    },
+    utils::{
+        pipeline_cache::CreatePipeline,
+        reflect::{Bindings, ShaderLayout},
+        shader_debug,
     },
-    utils::CubeMesh,
 };
+
+const SHADOW_PARAM_SYSTEM: &str = "shadow";
+const SHADOW_PARAMS: &[ParamDesc] = &[
+    ParamDesc::float("light_radius", 4.0, 0.5, 10.0),
+    ParamDesc::float("light_height", 5.0, 0.5, 10.0),
+    ParamDesc::float("light_speed", 0.8, 0.0, 5.0), // 每秒旋转的弧度
+    ParamDesc::float("bias", 0.02, 0.0, 0.1),       // getShadow 的深度偏移
+    ParamDesc::int("pcf_radius", 2, 0, 4),          // PCF 采样半径，2 即 5x5
+    ParamDesc::bool("pcf", true),                   // 关掉后只采样一次（硬阴影），切换时重建管线
+    ParamDesc::bool("wireframe", false),            // 模型上叠加线框
+    ParamDesc::int("instances", 0, 0, 20000),       // 外圈实例化立方体的数量，压力测试
+];
 #[derive(Default)]
 pub struct ShadowScene {
     pub cube_buffer: Option<wgpu::Buffer>,
@@ -24,7 +44,11 @@ pub struct ShadowScene {
     pub cube_indices_count: Option<u32>,
     pub main_depth_view: Option<wgpu::TextureView>,
     pub depth_bind_group: Option<wgpu::BindGroup>,
+    pub depth_bind_group_layout: Option<wgpu::BindGroupLayout>, // 模型等其它管线共用 group 0
     pub angle: f32,
+    pub main_layout: Option<wgpu::PipelineLayout>,
+    cube_reload: Option<Reload<wgpu::RenderPipeline>>,
+    pcf: bool, // 当前管线编译时的 SHADOW_PCF 开关
 }
 impl Ready for ShadowScene {
     fn ready(
@@ -32,76 +56,55 @@ impl Ready for ShadowScene {
         data: &mut crate::shared::ready_paint::HashTypeId2Data,
         gfx: &crate::shared::ready_paint::Gfx,
     ) {
-        println!("shadow scene ready");
+        log::debug!("shadow scene ready");
+        params::declare(SHADOW_PARAM_SYSTEM, SHADOW_PARAMS);
         let shadow_scene = get_res::<DepthTexture>(data);
         let shadow_view = shadow_scene.shadow_view.as_ref().unwrap();
         let shadow_sampler = shadow_scene.shadow_sampler.as_ref().unwrap();
 
-        let cube = CubeMesh::default();
-        let cube_indices_count = cube.indices.len() as u32;
+        // 顶点少，索引统一用 u16，和地面合并后也放得下
+        let cube = primitives::cube(1.);
+        let cube_indices: Vec<u16> = cube.indices.iter().map(|&i| i as u16).collect();
+        let cube_indices_count = cube_indices.len() as u32;
         let cube_buffer = gfx
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("cube buffer vertex"),
-                contents: bytemuck::cast_slice(&cube.vertices),
+                contents: bytemuck::cast_slice(&cube.positions),
                 usage: wgpu::BufferUsages::VERTEX,
             });
         let cube_index_buffer = gfx
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("cube buffer index"),
-                contents: bytemuck::cast_slice(&cube.indices),
+                contents: bytemuck::cast_slice(&cube_indices),
                 usage: wgpu::BufferUsages::INDEX,
             });
-        let cube_shader = gfx
-            .device
-            .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("depth_shader"),
-                source: wgpu::ShaderSource::Wgsl(CUBE_SHADER.into()),
-            });
-        let depth_bind_group_layout =
-            gfx.device
-                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: Some("depth bind group layout"),
-                    entries: &[
-                        // depth texture
-                        wgpu::BindGroupLayoutEntry {
-                            binding: 0,
-                            visibility: wgpu::ShaderStages::FRAGMENT,
-                            ty: wgpu::BindingType::Texture {
-                                sample_type: wgpu::TextureSampleType::Depth,
-                                view_dimension: wgpu::TextureViewDimension::D2,
-                                multisampled: false,
-                            },
-                            count: None,
-                        },
-                        // sampler
-                        wgpu::BindGroupLayoutEntry {
-                            binding: 1,
-                            visibility: wgpu::ShaderStages::FRAGMENT,
-                            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
-                            count: None,
-                        },
-                        // light uniform
-                        wgpu::BindGroupLayoutEntry {
-                            binding: 2,
-                            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
-                            ty: wgpu::BindingType::Buffer {
-                                ty: wgpu::BufferBindingType::Storage { read_only: true },
-                                has_dynamic_offset: false,
-                                min_binding_size: wgpu::BufferSize::new(
-                                    size_of::<LightStorage>() as wgpu::BufferAddress
-                                ),
-                            },
-                            count: None,
-                        },
-                    ],
-                });
+        let pcf = params::get(SHADOW_PARAM_SYSTEM, "pcf") != 0.;
+        let cube_source = hot_reload::register(CUBE_SHADER_NAME, CUBE_SHADER);
+        let (cube_shader, cube_reflect) = gfx
+            .pipelines
+            .validated_shader(CUBE_SHADER_NAME, &cube_source, cube_defines(pcf))
+            .unwrap();
+        // 切换 pcf 时复用同一个 layout，所以两种开关下用到的绑定都要算进来
+        let other_reflect =
+            shader_debug::preprocess_and_validate(&cube_source, cube_defines(!pcf)).map(|(_, s)| s);
+        let mut reflects = vec![&*cube_reflect];
+        reflects.extend(other_reflect.as_ref().ok());
+        // group 0（深度图、比较采样器、light）由 cube.wgsl 反射得到，group 1 复用 World 的 uniforms
+        let world = get_res::<World>(data);
+        let main_layout = ShaderLayout::new(
+            &gfx.pipelines,
+            "main pipeline layout",
+            Bindings::new(&reflects).unwrap(),
+            &[(1, world.uniforms_bind_group_layout.as_ref().unwrap())],
+        );
         let light_storage_buffer = shadow_scene.light_storage_buffer.as_ref().unwrap();
-        let depth_bind_group = gfx.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("depth bind group"),
-            layout: &depth_bind_group_layout,
-            entries: &[
+        let depth_bind_group = main_layout.create_bind_group(
+            &gfx.device,
+            "depth bind group",
+            0,
+            &[
                 wgpu::BindGroupEntry {
                     binding: 0,
                     resource: wgpu::BindingResource::TextureView(&shadow_view),
@@ -117,83 +120,17 @@ impl Ready for ShadowScene {
                     ),
                 },
             ],
-        });
-        let world = get_res::<World>(data);
-        let world_uniforms_bind_group_layout = world.uniforms_bind_group_layout.as_ref().unwrap();
-        let main_layout = gfx
-            .device
-            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("main pipeline layout"),
-                bind_group_layouts: &[&depth_bind_group_layout, &world_uniforms_bind_group_layout],
-                push_constant_ranges: &[],
-            });
+        );
+        let depth_bind_group_layout = main_layout.bind_group_layouts[0].clone();
+        let main_layout = main_layout.pipeline_layout;
 
-        let cube_pipeline = gfx
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("cube pipeline"),
-                layout: Some(&main_layout),
-                vertex: wgpu::VertexState {
-                    module: &cube_shader,
-                    entry_point: Some("vs_main"),
-                    buffers: &[wgpu::VertexBufferLayout {
-                        array_stride: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                        step_mode: wgpu::VertexStepMode::Vertex,
-                        attributes: &[wgpu::VertexAttribute {
-                            offset: 0,
-                            shader_location: 0,
-                            format: wgpu::VertexFormat::Float32x3,
-                        }],
-                    }],
-                    compilation_options: PipelineCompilationOptions::default(),
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &cube_shader,
-                    entry_point: Some("fs_main"),
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: wgpu::TextureFormat::Bgra8Unorm,
-                        blend: Some(wgpu::BlendState::REPLACE),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                    compilation_options: PipelineCompilationOptions::default(),
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Cw,
-                    // cull_mode: Some(wgpu::Face::Back),
-                    cull_mode: None,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    unclipped_depth: false,
-                    conservative: false,
-                },
-                depth_stencil: Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth32Float,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Less,
-                    stencil: wgpu::StencilState::default(),
-                    bias: wgpu::DepthBiasState::default(),
-                }),
-                multisample: wgpu::MultisampleState::default(),
-                multiview: None,
-                cache: None,
-            });
+        let cube_pipeline = create_cube_pipeline(&gfx.pipelines, &main_layout, &cube_shader);
         let plane = get_res::<ShadowPlane>(data);
-        let plane_vertex = plane
-            .vertices
-            .iter()
-            .map(|v| [v[0], v[1], v[2]])
-            .collect::<Vec<[f32; 3]>>();
-        let for_depth_verticies = cube
-            .vertices
-            .iter()
-            .map(|v| v.position)
-            .collect::<Vec<[f32; 3]>>();
-
-        let for_common_vertex = for_depth_verticies
+        let for_common_vertex = cube
+            .positions
             .iter()
-            .cloned()
-            .chain(plane_vertex.iter().cloned())
+            .chain(&plane.mesh.positions)
+            .copied()
             .collect::<Vec<[f32; 3]>>();
 
         // 给阴影场景的顶点数据创建缓冲区
@@ -205,18 +142,18 @@ impl Ready for ShadowScene {
                     usage: wgpu::BufferUsages::VERTEX,
                 });
         // 1. 获取立方体顶点数量
-        let cube_vertex_count = cube.vertices.len() as u16;
+        let cube_vertex_count = cube.vertex_count() as u16;
 
         // 2. 应用偏移到平面索引
         let plane_indices_with_offset = plane
+            .mesh
             .indices
             .iter()
-            .map(|idx| idx + cube_vertex_count) // 加上立方体顶点数作为偏移
+            .map(|&idx| idx as u16 + cube_vertex_count) // 加上立方体顶点数作为偏移
             .collect::<Vec<u16>>();
 
         // 3. 合并索引
-        let for_common_index = cube
-            .indices
+        let for_common_index = cube_indices
             .iter()
             .cloned()
             .chain(plane_indices_with_offset.iter().cloned())
@@ -229,8 +166,8 @@ impl Ready for ShadowScene {
                     usage: wgpu::BufferUsages::INDEX,
                 });
 
-        console_log!("for_common_vertex: {:?}", for_common_vertex);
-        console_log!("for_common_index: {:?}", for_common_index);
+        log::debug!("for_common_vertex: {:?}", for_common_vertex);
+        log::debug!("for_common_index: {:?}", for_common_index);
         let main_depth_texture = gfx.device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Main Scene Depth Texture"),
             size: wgpu::Extent3d {
@@ -247,6 +184,10 @@ impl Ready for ShadowScene {
         });
         let main_depth_view =
             main_depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        // 光源绕 y 轴转，从导入场景里太阳所在的方位开始，高度和半径仍由参数控制
+        let angle = get_res::<ImportedScene>(data)
+            .sun
+            .map_or(0., |sun| f32::atan2(-sun.z, -sun.x));
 
         return_res(
             data,
@@ -261,12 +202,71 @@ impl Ready for ShadowScene {
                 cube_indices_count: Some(cube_indices_count),
                 main_depth_view: Some(main_depth_view),
                 depth_bind_group: Some(depth_bind_group),
-                angle: 0.0,
+                depth_bind_group_layout: Some(depth_bind_group_layout),
+                angle,
+                main_layout: Some(main_layout),
+                cube_reload: None,
+                pcf,
             },
         );
     }
 }
 
+fn create_cube_pipeline(
+    pipelines: &impl CreatePipeline,
+    layout: &wgpu::PipelineLayout,
+    module: &wgpu::ShaderModule,
+) -> wgpu::RenderPipeline {
+    pipelines.render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("cube pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module,
+            entry_point: Some("vs_main"),
+            buffers: &[wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &[wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x3,
+                }],
+            }],
+            compilation_options: PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: wgpu::TextureFormat::Bgra8Unorm,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Cw,
+            // cull_mode: Some(wgpu::Face::Back),
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: wgpu::TextureFormat::Depth32Float,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
 impl<'a> Pass<'a> for ShadowScene {
     fn pass(
         data: &mut crate::shared::ready_paint::HashTypeId2Data,
@@ -303,16 +303,43 @@ impl Update for ShadowScene {
     ) {
         // 1. 获取当前光源和深度纹理资源
         let (depth_texture, scene) = refs_muts::<(Ref<DepthTexture>, Mut<ShadowScene>)>(data);
+        if let Some(request) = hot_reload::take_request(CUBE_SHADER_NAME) {
+            let layout = scene.main_layout.as_ref().unwrap();
+            scene.cube_reload = Some(hot_reload::rebuild(
+                &gfx.device,
+                CUBE_SHADER_NAME,
+                request,
+                cube_defines(scene.pcf),
+                |module| create_cube_pipeline(gfx.device.as_ref(), layout, module),
+            ));
+        }
+        // 阴影过滤方式是编译期开关，切换时用当前源码重建
+        let pcf = params::get(SHADOW_PARAM_SYSTEM, "pcf") != 0.;
+        if pcf != scene.pcf {
+            let source = hot_reload::active_source(CUBE_SHADER_NAME).unwrap();
+            // 两种开关的 shader 和管线都在缓存里，来回切换不会重新编译
+            if let Ok(module) = gfx
+                .pipelines
+                .shader(CUBE_SHADER_NAME, &source, cube_defines(pcf))
+            {
+                let layout = scene.main_layout.as_ref().unwrap();
+                scene.cube_pipeline = Some(create_cube_pipeline(&gfx.pipelines, layout, &module));
+            }
+            scene.pcf = pcf;
+        }
+        if let Some(pipeline) = scene.cube_reload.as_ref().and_then(Reload::take) {
+            scene.cube_pipeline = Some(pipeline);
+        }
         let light_storage_buffer = depth_texture.light_storage_buffer.as_ref().unwrap();
 
         // 2. 计算新的光源位置（围绕Y轴旋转）
-        let rotation_speed = 0.8; // 每秒旋转的速度
+        let rotation_speed = params::get(SHADOW_PARAM_SYSTEM, "light_speed"); // 每秒旋转的速度
         let angle = dt / 1000. * rotation_speed;
 
         scene.angle += angle;
 
-        let radius = 4.0;
-        let height = 5.0;
+        let radius = params::get(SHADOW_PARAM_SYSTEM, "light_radius");
+        let height = params::get(SHADOW_PARAM_SYSTEM, "light_height");
 
         let light_position = glam::Vec3::new(
             radius * f32::cos(scene.angle),
@@ -331,9 +358,12 @@ impl Update for ShadowScene {
         // 5. 计算新的视图投影矩阵
         let light_view_proj = light_proj * light_view;
 
-        // 6. 创建新的LightStorage
-        let light_storage = LightStorage {
+        // 6. 创建新的LightUniform
+        let light_storage = LightUniform {
             view_proj: light_view_proj.to_cols_array_2d(),
+            bias: params::get(SHADOW_PARAM_SYSTEM, "bias"),
+            pcf_radius: params::get(SHADOW_PARAM_SYSTEM, "pcf_radius"),
+            _padding: [0.; 2],
         };
 
         // 7. 将新数据写入buffer
@@ -344,149 +374,20 @@ impl Update for ShadowScene {
         );
     }
 }
-const CUBE_SHADER: &str = r#"
-struct VertexInput {
-    @location(0) position: vec3<f32>,
-}
-@group(0) @binding(0) var depth_texture: texture_depth_2d;
-@group(0) @binding(1) var shadow_sampler: sampler_comparison;
-@group(0) @binding(2) var<storage, read> light: LightUniform;
-
-struct LightUniform {
-    view_proj: mat4x4<f32>,
-}
-
-@group(1) @binding(0) var<uniform> uniforms: Uniforms;
-struct Uniforms {
-    matrix: mat4x4<f32>,
-    resolution: vec2<f32>,
-    delta_time: f32,
-    _padding: f32,
-}
-struct VertexOutput {
-    @builtin(position) position: vec4<f32>,
-    @location(0) world_pos: vec3<f32>,
-    @location(1) light_space_position: vec4<f32>,
-}
-@vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
-    var out: VertexOutput;
-    // 应用世界变换
-    out.world_pos = in.position;
-    // 摄像机下变换
-    out.position = uniforms.matrix * vec4<f32>(in.position, 1.0);
-    // 当前片元到光源的变换
-    out.light_space_position = light.view_proj * vec4<f32>(in.position, 1.0);
-    
-    return out;
-}
-fn getShadow(coords: vec2<f32>, compare: f32) -> f32 {
-    let texelSize = 1.0 / 1024.0;
-    let bias = 0.02; 
-    var shadow = 0.0;
-    for (var y = -2; y <= 2; y += 1) {
-        for (var x = -2; x <= 2; x += 1) {
-            let offset = vec2<f32>(f32(x), f32(y)) * texelSize;
-            shadow += textureSampleCompare(
-                depth_texture, 
-                shadow_sampler, 
-                coords + offset, 
-                compare - bias
-            );
-        }
-    }
-    return shadow / 25.0;
-}
-
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-         // 计算光空间深度坐标
-    let proj_coords = in.light_space_position.xyz / in.light_space_position.w;
-    
-    // 转换到[0,1]范围
-    let shadow_coords = vec2<f32>(
-        proj_coords.x * 0.5 + 0.5,
-        -proj_coords.y * 0.5 + 0.5
-    );
-    
-    // 当前深度值
-    let current_depth = proj_coords.z * 0.5 + 0.5;
-    
-    // 获取阴影值
-    let shadow = getShadow(shadow_coords, current_depth);
-    
-    // 处理超出深度图边界的情况
-    var final_shadow = shadow;
-    if (shadow_coords.x < 0.0 || shadow_coords.x > 1.0 || 
-        shadow_coords.y < 0.0 || shadow_coords.y > 1.0 ||
-        current_depth < 0.0 || current_depth > 1.0) {
-        final_shadow = 1.0; // 超出边界，不应用阴影
-    }
-    
-    // 通过片元位置计算法线 (几何方法)
-    var normal = vec3<f32>(0.0, 1.0, 0.0); // 默认为上方向
-    
-    // 立方体法线计算 - 基于位置判断当前面
-    if (in.world_pos.y < -0.6) {
-        // 平面法线为上方向
-        normal = vec3<f32>(0.0, 1.0, 0.0);
-    } else {
-        let threshold = 0.45; // 接近0.5，但稍微小一点避免边缘问题
-        if (abs(in.world_pos.x) > threshold) {
-            normal = vec3<f32>(sign(in.world_pos.x), 0.0, 0.0);
-        } else if (abs(in.world_pos.y) > threshold) {
-            normal = vec3<f32>(0.0, sign(in.world_pos.y), 0.0);
-        } else if (abs(in.world_pos.z) > threshold) {
-            normal = vec3<f32>(0.0, 0.0, sign(in.world_pos.z));
-        }
-    }
-    let radius = 4.0;
-    let height = 5.0;
-    let light_pos = vec3<f32>(
-        radius * cos(uniforms.delta_time * 0.001),
-        height,
-        radius * sin(uniforms.delta_time * 0.001)
-    );
-    let light_dir = normalize(light_pos - in.world_pos);
-    let diffuse_strength = max(dot(normal, light_dir), 0.0);
-    var object_color: vec3<f32>;
-    if (in.world_pos.y < -0.6) {
-        // 平面的颜色
-        object_color = vec3<f32>(0.2, 0.7, 0.3);
-        let pattern = sin(in.world_pos.x * 3.0) * sin(in.world_pos.z * 3.0);
-        if (pattern > 0.0) {
-            object_color = mix(object_color, vec3<f32>(0.3, 0.8, 0.4), 0.2);
-        }
-
-        // 简单的光照计算
-        let ambient_strength = 0.3;
-        let ambient = ambient_strength * vec3<f32>(1.0);
-        
-        // 计算漫反射光照
-        let light_color = vec3<f32>(1.0, 0.9, 0.8);
-        let diffuse = diffuse_strength * light_color * final_shadow;
-        
-        // 组合光照
-        let result = (ambient + diffuse) * object_color;
-        
-        return vec4<f32>(result, 1.0);
+fn cube_defines(pcf: bool) -> &'static [(&'static str, &'static str)] {
+    if pcf {
+        &[("SHADOW_PCF", "")]
     } else {
-        // 立方体的颜色 - 简单版本，基于法线方向
-        if (abs(normal.x) > 0.5) {
-            // 左右面
-            object_color = vec3<f32>(0.7, 0.5, 0.3);
-        } else if (abs(normal.y) > 0.5) {
-            // 上下面
-            object_color = vec3<f32>(0.3, 0.6, 0.8);
-        } else {
-            // 前后面
-            object_color = vec3<f32>(0.6, 0.4, 0.7);
-        }
-        return vec4<f32>(object_color, 1.0);
+        &[]
     }
 }
-"#;
 
+const CUBE_SHADER_NAME: &str = "shadow.cube";
+const CUBE_SHADER: &str = include_str!("shadow/cube.wgsl");
+
+pub mod cubes;
 pub mod depth_texture;
+pub mod imported;
+pub mod model;
 pub mod plane;
 pub mod world;
@@ -0,0 +1,91 @@
+#include "uniforms.wgsl"
+#include "shadow.wgsl"
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+}
+@group(1) @binding(0) var<uniform> uniforms: Uniforms;
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_pos: vec3<f32>,
+    @location(1) light_space_position: vec4<f32>,
+}
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    // 应用世界变换
+    out.world_pos = in.position;
+    // 摄像机下变换
+    out.position = uniforms.matrix * vec4<f32>(in.position, 1.0);
+    // 当前片元到光源的变换
+    out.light_space_position = light.view_proj * vec4<f32>(in.position, 1.0);
+    
+    return out;
+}
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // 阴影（深度图采样见 shadow.wgsl）
+    let final_shadow = shadow_factor(in.light_space_position);
+    
+    // 通过片元位置计算法线 (几何方法)
+    var normal = vec3<f32>(0.0, 1.0, 0.0); // 默认为上方向
+    
+    // 立方体法线计算 - 基于位置判断当前面
+    if (in.world_pos.y < -0.6) {
+        // 平面法线为上方向
+        normal = vec3<f32>(0.0, 1.0, 0.0);
+    } else {
+        let threshold = 0.45; // 接近0.5，但稍微小一点避免边缘问题
+        if (abs(in.world_pos.x) > threshold) {
+            normal = vec3<f32>(sign(in.world_pos.x), 0.0, 0.0);
+        } else if (abs(in.world_pos.y) > threshold) {
+            normal = vec3<f32>(0.0, sign(in.world_pos.y), 0.0);
+        } else if (abs(in.world_pos.z) > threshold) {
+            normal = vec3<f32>(0.0, 0.0, sign(in.world_pos.z));
+        }
+    }
+    let radius = 4.0;
+    let height = 5.0;
+    let light_pos = vec3<f32>(
+        radius * cos(uniforms.delta_time * 0.001),
+        height,
+        radius * sin(uniforms.delta_time * 0.001)
+    );
+    let light_dir = normalize(light_pos - in.world_pos);
+    let diffuse_strength = max(dot(normal, light_dir), 0.0);
+    var object_color: vec3<f32>;
+    if (in.world_pos.y < -0.6) {
+        // 平面的颜色
+        object_color = vec3<f32>(0.2, 0.7, 0.3);
+        let pattern = sin(in.world_pos.x * 3.0) * sin(in.world_pos.z * 3.0);
+        if (pattern > 0.0) {
+            object_color = mix(object_color, vec3<f32>(0.3, 0.8, 0.4), 0.2);
+        }
+
+        // 简单的光照计算
+        let ambient_strength = 0.3;
+        let ambient = ambient_strength * vec3<f32>(1.0);
+        
+        // 计算漫反射光照
+        let light_color = vec3<f32>(1.0, 0.9, 0.8);
+        let diffuse = diffuse_strength * light_color * final_shadow;
+        
+        // 组合光照
+        let result = (ambient + diffuse) * object_color;
+        
+        return vec4<f32>(result, 1.0);
+    } else {
+        // 立方体的颜色 - 简单版本，基于法线方向
+        if (abs(normal.x) > 0.5) {
+            // 左右面
+            object_color = vec3<f32>(0.7, 0.5, 0.3);
+        } else if (abs(normal.y) > 0.5) {
+            // 上下面
+            object_color = vec3<f32>(0.3, 0.6, 0.8);
+        } else {
+            // 前后面
+            object_color = vec3<f32>(0.6, 0.4, 0.7);
+        }
+        return vec4<f32>(object_color, 1.0);
+    }
+}
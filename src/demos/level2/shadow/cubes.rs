@@ -0,0 +1,118 @@
+// 阴影场景外圈的实例化立方体，压力测试用：数量由参数 `instances` 控制
+// 按向日葵螺旋往外排，下标越大离中心越远，加减数量时已有的实例不动，只上传新增的
+// 每帧只有一段连续下标（从里往外扫的一圈环）在自转，只上传这一段，其它实例停在原来的角度
+use std::f32::consts::{PI, TAU};
+
+use glam::{Mat4, Quat, Vec3};
+
+use crate::{
+    mesh::{
+        instanced::{Instance, InstancedMesh, InstancedRenderer},
+        primitives,
+    },
+    shared::{
+        params,
+        ready_paint::{get_res, get_res_mut, return_res, Gfx, HashTypeId2Data, Pass, Ready},
+    },
+};
+
+use super::{world::World, SHADOW_PARAM_SYSTEM};
+
+const CUBE_SIZE: f32 = 0.12;
+const INNER_RADIUS: f32 = 3.0; // 让出中间的立方体和模型
+const SPACING: f32 = 0.3;
+const GROUND: f32 = -1.0;
+const WAVE_WIDTH: usize = 256; // 同时在转的实例数
+const WAVE_SPEED: f32 = 400.; // 环每秒往外扫过的实例数
+
+#[derive(Default)]
+pub struct InstancedCubes {
+    cubes: Option<InstancedMesh>,
+    renderer: Option<InstancedRenderer>,
+    time: f32,
+}
+
+impl Ready for InstancedCubes {
+    fn ready(&mut self, data: &mut HashTypeId2Data, gfx: &Gfx) {
+        let world = get_res::<World>(data);
+        let renderer = InstancedRenderer::new(
+            &gfx.pipelines,
+            world.uniforms_bind_group_layout.as_ref().unwrap(),
+            wgpu::TextureFormat::Bgra8Unorm,
+            wgpu::TextureFormat::Depth32Float,
+        );
+        let cubes = InstancedMesh::new(
+            &gfx.device,
+            "instanced cubes",
+            &primitives::cube(CUBE_SIZE),
+            1024,
+        );
+        return_res(
+            data,
+            Self {
+                cubes: Some(cubes),
+                renderer: Some(renderer),
+                time: 0.,
+            },
+        );
+    }
+}
+
+// 第 i 个立方体：螺旋上的位置、随下标变化的颜色和转速
+fn cube_instance(i: usize, time: f32) -> Instance {
+    // 黄金角，相邻的点不会排成直线
+    let golden = PI * (3. - 5f32.sqrt());
+    let angle = i as f32 * golden;
+    // 每个立方体占 SPACING² 的面积
+    let radius = (INNER_RADIUS * INNER_RADIUS + i as f32 * SPACING * SPACING / PI).sqrt();
+    let position = Vec3::new(
+        radius * angle.cos(),
+        GROUND + CUBE_SIZE / 2.,
+        radius * angle.sin(),
+    );
+    let speed = 0.5 + (i % 7) as f32 * 0.25;
+    let rotation = Quat::from_rotation_y(time * speed + angle);
+    let hue = (angle / TAU).fract();
+    let color = [0., 1. / 3., 2. / 3.].map(|offset| 0.5 + 0.5 * (TAU * (hue + offset)).cos());
+    Instance::new(
+        Mat4::from_rotation_translation(rotation, position),
+        [color[0], color[1], color[2], 1.],
+    )
+}
+
+impl InstancedCubes {
+    /// 按参数增减实例，再把环扫到的那一段转到当前时间
+    pub fn update(data: &mut HashTypeId2Data, gfx: &Gfx, dt: f32) {
+        let count = params::get(SHADOW_PARAM_SYSTEM, "instances") as usize;
+        let this = get_res_mut::<Self>(data);
+        this.time += dt / 1000.;
+        let time = this.time;
+        let cubes = this.cubes.as_mut().unwrap();
+        cubes.instances.truncate(count);
+        for i in cubes.instances.len()..count {
+            cubes.instances.push(cube_instance(i, 0.));
+        }
+        // 扫到最外圈后再走一个宽度，最后一段也转完了才从头开始
+        let end = (time * WAVE_SPEED) as usize % (count + WAVE_WIDTH);
+        for i in end.saturating_sub(WAVE_WIDTH)..end.min(count) {
+            cubes.instances.set(i, cube_instance(i, time));
+        }
+        cubes.sync(&gfx.device, &gfx.queue);
+    }
+}
+
+impl<'a> Pass<'a> for InstancedCubes {
+    fn pass(
+        data: &mut HashTypeId2Data,
+        mut render_pass: wgpu::RenderPass<'a>,
+    ) -> wgpu::RenderPass<'a> {
+        let this = get_res::<Self>(data);
+        let world = get_res::<World>(data);
+        this.renderer.as_ref().unwrap().draw(
+            &mut render_pass,
+            world.uniforms_bind_group.as_ref().unwrap(),
+            this.cubes.as_ref(),
+        );
+        render_pass
+    }
+}
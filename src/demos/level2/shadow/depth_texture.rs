@@ -1,11 +1,13 @@
-use bytemuck::{Pod, Zeroable};
 use glam::{Mat4, Vec3};
 use wgpu::{util::DeviceExt, CompareFunction, PipelineCompilationOptions};
 
 use crate::{
-    console_log,
+    shaders::LightUniform,
     shared::ready_paint::{get_res, return_res, Pass, Ready},
-    utils::Vertex,
+    utils::{
+        reflect::{Bindings, ShaderLayout},
+        Vertex,
+    },
 };
 
 use super::{plane::ShadowPlane, ShadowScene};
@@ -75,8 +77,11 @@ impl Ready for DepthTexture {
         let light_view = Mat4::look_at_rh(light_position, light_target, light_up);
         let light_proj = Mat4::orthographic_rh(-3.0, 3.0, -3.0, 3.0, 0.1, 10.0);
         let light_view_proj = light_proj * light_view;
-        let light_storage = LightStorage {
+        let light_storage = LightUniform {
             view_proj: light_view_proj.to_cols_array_2d(),
+            bias: 0.02,
+            pcf_radius: 2.,
+            _padding: [0.; 2],
         };
         let light_storage_buffer =
             gfx.device
@@ -85,92 +90,76 @@ impl Ready for DepthTexture {
                     contents: bytemuck::cast_slice(&[light_storage]),
                     usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
                 });
-        let light_bind_group_layout =
-            gfx.device
-                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: Some("light_bind_group_layout"),
-                    entries: &[wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::VERTEX, // 注意：修改为顶点着色器可见
-                        ty: wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Storage { read_only: true },
-                            has_dynamic_offset: false,
-                            min_binding_size: wgpu::BufferSize::new(
-                                std::mem::size_of::<LightStorage>() as _,
-                            ),
-                        },
-                        count: None,
-                    }],
-                });
-        let light_bind_group = gfx.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &light_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
+        // wgsl
+        let (depth_shader, depth_reflect) = gfx
+            .pipelines
+            .validated_shader("depth shader", DEPTH_SHADER, &[])
+            .unwrap();
+        // light buffer 的 layout 由 depth.wgsl 反射得到
+        let depth_layout = ShaderLayout::new(
+            &gfx.pipelines,
+            "depth_pipeline_layout",
+            Bindings::new(&[&depth_reflect]).unwrap(),
+            &[],
+        );
+        let light_bind_group = depth_layout.create_bind_group(
+            &gfx.device,
+            "light_bind_group",
+            0,
+            &[wgpu::BindGroupEntry {
                 binding: 0,
                 resource: light_storage_buffer.as_entire_binding(),
             }],
-            label: Some("light_bind_group"),
-        });
-        // wgsl
-        let depth_shader = gfx
-            .device
-            .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("depth shader"),
-                source: wgpu::ShaderSource::Wgsl(DEPTH_SHADER.into()),
-            });
+        );
 
-        let light_uniform_size = size_of::<LightStorage>() as wgpu::BufferAddress;
+        let light_uniform_size = size_of::<LightUniform>() as wgpu::BufferAddress;
         // 深度管线
-        let depth_pipeline =
-            gfx.device
-                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                    label: Some("depth pipeline"),
-                    layout: Some(&gfx.device.create_pipeline_layout(
-                        &wgpu::PipelineLayoutDescriptor {
-                            label: Some("depth_pipeline_layout"),
-                            bind_group_layouts: &[&light_bind_group_layout],
-                            push_constant_ranges: &[],
-                        },
-                    )),
-                    vertex: wgpu::VertexState {
-                        module: &depth_shader,
-                        entry_point: Some("vs_main"),
-                        buffers: &[wgpu::VertexBufferLayout {
-                            array_stride: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                            step_mode: wgpu::VertexStepMode::Vertex,
-                            attributes: &[wgpu::VertexAttribute {
-                                offset: 0,
-                                shader_location: 0,
-                                format: wgpu::VertexFormat::Float32x3,
-                            }],
+        let depth_pipeline = gfx
+            .pipelines
+            .render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("depth pipeline"),
+                layout: Some(&depth_layout.pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &depth_shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &[wgpu::VertexAttribute {
+                            offset: 0,
+                            shader_location: 0,
+                            format: wgpu::VertexFormat::Float32x3,
                         }],
-                        compilation_options: PipelineCompilationOptions::default(),
-                    },
-                    fragment: None,
-                    primitive: wgpu::PrimitiveState {
-                        topology: wgpu::PrimitiveTopology::TriangleList,
-                        strip_index_format: None,
-                        front_face: wgpu::FrontFace::Cw,
-                        cull_mode: Some(wgpu::Face::Back),
-                        // cull_mode: None,
-                        polygon_mode: wgpu::PolygonMode::Fill,
-                        conservative: false,
-                        ..Default::default()
+                    }],
+                    compilation_options: PipelineCompilationOptions::default(),
+                },
+                fragment: None,
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    // 网格都是逆时针为正面，这里剔掉的是朝光源的一面，深度图里存的是背面，自阴影不容易出条纹
+                    front_face: wgpu::FrontFace::Cw,
+                    cull_mode: Some(wgpu::Face::Back),
+                    // cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    conservative: false,
+                    ..Default::default()
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth32Float,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::LessEqual,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState {
+                        constant: 2, // corresponds to bilinear filtering
+                        slope_scale: 2.0,
+                        clamp: 0.0,
                     },
-                    depth_stencil: Some(wgpu::DepthStencilState {
-                        format: wgpu::TextureFormat::Depth32Float,
-                        depth_write_enabled: true,
-                        depth_compare: wgpu::CompareFunction::LessEqual,
-                        stencil: wgpu::StencilState::default(),
-                        bias: wgpu::DepthBiasState {
-                            constant: 2, // corresponds to bilinear filtering
-                            slope_scale: 2.0,
-                            clamp: 0.0,
-                        },
-                    }),
-                    multisample: wgpu::MultisampleState::default(),
-                    multiview: None,
-                    cache: None,
-                });
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
 
         return_res(
             data,
@@ -230,29 +219,4 @@ impl<'a> Pass<'a> for DepthTexture {
     }
 }
 
-const DEPTH_SHADER: &str = r#"
-struct LightStorage {
-    view_proj: mat4x4<f32>,
-}
-@group(0) @binding(0)
-var<storage, read> light: LightStorage;
-
-struct VertexInput {
-    @location(0) position: vec3<f32>,
-}
-struct VertexOutput {
-    @builtin(position) position: vec4<f32>,
-}
-@vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
-    var out: VertexOutput;
-    out.position = light.view_proj * vec4<f32>(in.position, 1.0);
-    return out;
-}
-"#;
-
-#[repr(C)]
-#[derive(Debug, Pod, Zeroable, Copy, Clone)]
-pub struct LightStorage {
-    pub view_proj: [[f32; 4]; 4],
-}
+const DEPTH_SHADER: &str = include_str!("depth.wgsl");
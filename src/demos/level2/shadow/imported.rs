@@ -0,0 +1,69 @@
+// 导入的 glTF 场景（static/models/display.gltf，编进程序）
+// 网格、材质和贴图由 `ShadowModel` 上传、绘制；相机给 `World` 做初始视角，太阳给 `ShadowScene` 定光源的初始方位
+// 要在 World、ShadowScene 之前 ready
+use glam::{Mat4, Vec3};
+
+use crate::{
+    mesh::gltf::{self, Camera, GltfScene, LightKind},
+    shared::ready_paint::{return_res, Gfx, HashTypeId2Data, Ready},
+};
+
+const SCENE_GLTF: &[u8] = include_bytes!("../../../../static/models/display.gltf");
+
+#[derive(Default)]
+pub struct ImportedScene {
+    pub scene: Option<GltfScene>,
+    pub textures: Vec<(String, wgpu::TextureView)>, // 名字和材质里的贴图名一致
+    pub camera: Option<(Camera, Mat4)>,             // 第一个相机和它的世界矩阵
+    pub sun: Option<Vec3>,                          // 第一个平行光的照射方向
+}
+
+impl ImportedScene {
+    pub fn texture(&self, name: &str) -> Option<&wgpu::TextureView> {
+        self.textures
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, t)| t)
+    }
+}
+
+impl Ready for ImportedScene {
+    fn ready(&mut self, data: &mut HashTypeId2Data, gfx: &Gfx) {
+        // 导入失败时只是少画这部分，不影响其它物体
+        let scene = gltf::load(SCENE_GLTF, &[])
+            .inspect(|scene| {
+                log::debug!(
+                    "imported gltf: {} nodes, {} meshes, {} cameras, {} lights",
+                    scene.nodes.len(),
+                    scene.meshes.len(),
+                    scene.cameras.len(),
+                    scene.lights.len()
+                )
+            })
+            .map_err(|e| log::error!("{}", e))
+            .ok();
+        let textures = scene
+            .as_ref()
+            .map(|scene| scene.upload_images(&gfx.device, &gfx.queue))
+            .unwrap_or_default();
+        let camera = scene
+            .as_ref()
+            .and_then(|scene| scene.camera_instances().next())
+            .map(|(camera, world)| (camera.clone(), world));
+        let sun = scene.as_ref().and_then(|scene| {
+            scene
+                .light_instances()
+                .find(|(light, _)| light.kind == LightKind::Directional)
+                .map(|(_, world)| world.transform_vector3(Vec3::NEG_Z).normalize())
+        });
+        return_res(
+            data,
+            Self {
+                scene,
+                textures,
+                camera,
+                sun,
+            },
+        );
+    }
+}
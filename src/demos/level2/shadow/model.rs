@@ -0,0 +1,342 @@
+// 阴影场景里的模型：OBJ（static/models/knot.obj，编进程序）和导入的 glTF 场景，和立方体一起投射、接收阴影
+// 材质颜色乘上基础色贴图（glTF 的 baseColorTexture），其余贴图暂不使用
+use glam::{Mat4, Vec3};
+use wgpu::{util::DeviceExt, PipelineCompilationOptions};
+
+use crate::{
+    mesh::{
+        obj, process,
+        wireframe::{Wireframe, WireframeOverlay},
+        GpuMesh, Material, Mesh,
+    },
+    shaders::MaterialUniform,
+    shared::{
+        params,
+        ready_paint::{get_res, get_res_mut, return_res, Gfx, HashTypeId2Data, Pass, Ready},
+    },
+    utils::{
+        pipeline_cache::PipelineCache,
+        reflect::{Bindings, ShaderLayout},
+    },
+};
+
+use super::{
+    cube_defines, imported::ImportedScene, world::World, ShadowScene, SHADOW_PARAM_SYSTEM,
+};
+
+const MODEL_OBJ: &[u8] = include_bytes!("../../../../static/models/knot.obj");
+const MODEL_MTL: &[u8] = include_bytes!("../../../../static/models/knot.mtl");
+const MODEL_SHADER_NAME: &str = "shadow.model";
+const MODEL_SHADER: &str = include_str!("model.wgsl");
+
+#[derive(Default)]
+pub struct ShadowModel {
+    models: Vec<Model>,
+    layout: Option<wgpu::PipelineLayout>,
+    pipeline: Option<wgpu::RenderPipeline>,
+    wireframe: Option<WireframeOverlay>,
+    pcf: bool,
+}
+
+struct Model {
+    mesh: GpuMesh,
+    material_bind_groups: Vec<wgpu::BindGroup>, // 和 submesh 一一对应
+    wireframe: Wireframe,
+}
+
+impl Ready for ShadowModel {
+    fn ready(&mut self, data: &mut HashTypeId2Data, gfx: &Gfx) {
+        let mut mesh = obj::load(MODEL_OBJ, &[("knot.mtl", MODEL_MTL)]).unwrap();
+        // 放在立方体旁边的地面（y = -1）上
+        place_on_ground(&mut mesh, Vec3::new(-1.5, -1.0, 1.0), 1.4);
+
+        let pcf = params::get(SHADOW_PARAM_SYSTEM, "pcf") != 0.;
+        let (module, reflect) = gfx
+            .pipelines
+            .validated_shader(MODEL_SHADER_NAME, MODEL_SHADER, cube_defines(pcf))
+            .unwrap();
+        // group 0 和立方体共用（深度图、光源），group 1 是 World 的 uniforms，group 2 是材质
+        let scene = get_res::<ShadowScene>(data);
+        let world = get_res::<World>(data);
+        let layout = ShaderLayout::new(
+            &gfx.pipelines,
+            "model pipeline layout",
+            Bindings::new(&[&reflect]).unwrap(),
+            &[
+                (0, scene.depth_bind_group_layout.as_ref().unwrap()),
+                (1, world.uniforms_bind_group_layout.as_ref().unwrap()),
+            ],
+        );
+        // glTF 的节点变换直接烘焙进顶点，和 OBJ 模型共用同一条管线
+        let imported = get_res::<ImportedScene>(data);
+        let white = white_texture(gfx);
+        let sampler = gfx.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("model diffuse sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let textures = Textures {
+            imported,
+            white: &white,
+            sampler: &sampler,
+        };
+        let instances = imported
+            .scene
+            .iter()
+            .flat_map(|scene| scene.mesh_instances());
+        let models = std::iter::once(upload(gfx, &layout, &textures, "shadow model", &mesh))
+            .chain(instances.map(|(mesh, world)| {
+                let mut mesh = mesh.clone();
+                mesh.transform(world);
+                upload(gfx, &layout, &textures, "imported model", &mesh)
+            }))
+            .collect();
+        let pipeline = create_model_pipeline(&gfx.pipelines, &layout.pipeline_layout, &module);
+        let wireframe = WireframeOverlay::new(
+            &gfx.device,
+            &gfx.pipelines,
+            world.uniforms_bind_group_layout.as_ref().unwrap(),
+            wgpu::TextureFormat::Bgra8Unorm,
+            wgpu::TextureFormat::Depth32Float,
+            [0.1, 0.1, 0.1, 0.6],
+        );
+
+        return_res(
+            data,
+            Self {
+                models,
+                layout: Some(layout.pipeline_layout),
+                pipeline: Some(pipeline),
+                wireframe: Some(wireframe),
+                pcf,
+            },
+        );
+    }
+}
+
+// 材质 bind group 用到的贴图：导入场景里的贴图按名字找，找不到（包括 OBJ 的）用白色
+struct Textures<'a> {
+    imported: &'a ImportedScene,
+    white: &'a wgpu::TextureView,
+    sampler: &'a wgpu::Sampler,
+}
+
+impl Textures<'_> {
+    fn diffuse(&self, material: &Material) -> &wgpu::TextureView {
+        material
+            .diffuse_texture
+            .as_deref()
+            .and_then(|name| self.imported.texture(name))
+            .unwrap_or(self.white)
+    }
+}
+
+// 1x1 白色，乘上去不改变材质颜色
+fn white_texture(gfx: &Gfx) -> wgpu::TextureView {
+    gfx.device
+        .create_texture_with_data(
+            &gfx.queue,
+            &wgpu::TextureDescriptor {
+                label: Some("white texture"),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &[255; 4],
+        )
+        .create_view(&Default::default())
+}
+
+// 上传网格，每个 submesh 的材质各建一个 bind group（group 2）
+// 上传前合并重复顶点、按顶点缓存重排，顶点少的模型顺便用上 u16 索引
+fn upload(
+    gfx: &Gfx,
+    layout: &ShaderLayout,
+    textures: &Textures,
+    label: &str,
+    mesh: &Mesh,
+) -> Model {
+    let mut mesh = mesh.clone();
+    process::dedup(&mut mesh);
+    process::optimize(&mut mesh);
+    let material_bind_groups = mesh
+        .submeshes
+        .iter()
+        .map(|submesh| {
+            let material = submesh
+                .material
+                .map(|i| mesh.materials[i].clone())
+                .unwrap_or_default();
+            let uniform = MaterialUniform {
+                diffuse: material.diffuse,
+                opacity: material.opacity,
+            };
+            let buffer = gfx
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("{} material", submesh.name)),
+                    contents: bytemuck::bytes_of(&uniform),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+            layout.create_bind_group(
+                &gfx.device,
+                "model material bind group",
+                2,
+                &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(textures.diffuse(&material)),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(textures.sampler),
+                    },
+                ],
+            )
+        })
+        .collect();
+    Model {
+        mesh: mesh.upload(&gfx.device, label),
+        material_bind_groups,
+        wireframe: Wireframe::new(&gfx.device, label, &mesh, None),
+    }
+}
+
+// 缩放到最长边为 size，底面中心放到 position
+fn place_on_ground(mesh: &mut Mesh, position: Vec3, size: f32) {
+    let (min, max) = mesh.bounds().unwrap();
+    let scale = size / (max - min).max_element();
+    let bottom = Vec3::new((min.x + max.x) / 2., min.y, (min.z + max.z) / 2.);
+    mesh.transform(
+        Mat4::from_translation(position)
+            * Mat4::from_scale(Vec3::splat(scale))
+            * Mat4::from_translation(-bottom),
+    );
+}
+
+fn create_model_pipeline(
+    cache: &PipelineCache,
+    layout: &wgpu::PipelineLayout,
+    module: &wgpu::ShaderModule,
+) -> wgpu::RenderPipeline {
+    cache.render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("model pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module,
+            entry_point: Some("vs_main"),
+            buffers: &[
+                GpuMesh::POSITION_LAYOUT,
+                GpuMesh::NORMAL_LAYOUT,
+                GpuMesh::UV_LAYOUT,
+            ],
+            compilation_options: PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: wgpu::TextureFormat::Bgra8Unorm,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: PipelineCompilationOptions::default(),
+        }),
+        // OBJ 是逆时针为正面
+        primitive: wgpu::PrimitiveState {
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            ..Default::default()
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: wgpu::TextureFormat::Depth32Float,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+impl ShadowModel {
+    /// 阴影开关和立方体一起切换，两种管线都在缓存里
+    pub fn update(data: &mut HashTypeId2Data, gfx: &Gfx) {
+        let pcf = params::get(SHADOW_PARAM_SYSTEM, "pcf") != 0.;
+        let model = get_res_mut::<Self>(data);
+        if pcf == model.pcf {
+            return;
+        }
+        if let Ok(module) = gfx
+            .pipelines
+            .shader(MODEL_SHADER_NAME, MODEL_SHADER, cube_defines(pcf))
+        {
+            let layout = model.layout.as_ref().unwrap();
+            model.pipeline = Some(create_model_pipeline(&gfx.pipelines, layout, &module));
+        }
+        model.pcf = pcf;
+    }
+
+    /// 画进阴影深度图，管线和光源的 bind group 由 `DepthTexture::pass` 设置
+    pub fn pass_for_shadow_map<'a>(
+        data: &mut HashTypeId2Data,
+        mut render_pass: wgpu::RenderPass<'a>,
+    ) -> wgpu::RenderPass<'a> {
+        for model in &get_res::<Self>(data).models {
+            model.mesh.draw_positions(&mut render_pass);
+        }
+        render_pass
+    }
+}
+
+impl<'a> Pass<'a> for ShadowModel {
+    fn pass(
+        data: &mut HashTypeId2Data,
+        mut render_pass: wgpu::RenderPass<'a>,
+    ) -> wgpu::RenderPass<'a> {
+        let shadow_model = get_res::<Self>(data);
+        let scene = get_res::<ShadowScene>(data);
+        let world = get_res::<World>(data);
+        render_pass.set_pipeline(shadow_model.pipeline.as_ref().unwrap());
+        render_pass.set_bind_group(0, scene.depth_bind_group.as_ref().unwrap(), &[]);
+        render_pass.set_bind_group(1, world.uniforms_bind_group.as_ref().unwrap(), &[]);
+        for model in &shadow_model.models {
+            let mesh = &model.mesh;
+            render_pass.set_vertex_buffer(0, mesh.positions.slice(..));
+            render_pass.set_vertex_buffer(1, mesh.normals.slice(..));
+            render_pass.set_vertex_buffer(2, mesh.uvs.slice(..));
+            render_pass.set_index_buffer(mesh.indices.slice(..), mesh.index_format);
+            for (submesh, material) in mesh.submeshes.iter().zip(&model.material_bind_groups) {
+                render_pass.set_bind_group(2, material, &[]);
+                render_pass.draw_indexed(submesh.indices.clone(), 0, 0..1);
+            }
+        }
+        if params::get(SHADOW_PARAM_SYSTEM, "wireframe") != 0. {
+            let models = shadow_model.models.iter();
+            shadow_model.wireframe.as_ref().unwrap().draw(
+                &mut render_pass,
+                world.uniforms_bind_group.as_ref().unwrap(),
+                models.map(|model| (&model.mesh, &model.wireframe)),
+            );
+        }
+        render_pass
+    }
+}
@@ -0,0 +1,43 @@
+#include "uniforms.wgsl"
+#include "material.wgsl"
+#include "shadow.wgsl"
+
+@group(1) @binding(0) var<uniform> uniforms: Uniforms;
+@group(2) @binding(0) var<uniform> material: MaterialUniform;
+@group(2) @binding(1) var diffuse_texture: texture_2d<f32>; // 没有贴图时是 1x1 的白色
+@group(2) @binding(2) var diffuse_sampler: sampler;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+}
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) light_space_position: vec4<f32>,
+    @location(2) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    // 模型在加载时已经变换到世界坐标
+    out.position = uniforms.matrix * vec4<f32>(in.position, 1.0);
+    out.normal = in.normal;
+    out.light_space_position = light.view_proj * vec4<f32>(in.position, 1.0);
+    out.uv = in.uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // 正交投影下 view_proj 的第三行正比于光的朝向，反过来就是指向光源的方向
+    let forward = vec3<f32>(light.view_proj[0][2], light.view_proj[1][2], light.view_proj[2][2]);
+    let light_dir = -normalize(forward);
+    let diffuse = max(dot(normalize(in.normal), light_dir), 0.0) * shadow_factor(in.light_space_position);
+    let ambient = 0.3;
+    let light_color = vec3<f32>(1.0, 0.9, 0.8);
+    let base = vec4<f32>(material.diffuse, material.opacity) * textureSample(diffuse_texture, diffuse_sampler, in.uv);
+    return vec4<f32>((ambient + diffuse * light_color) * base.rgb, base.a);
+}
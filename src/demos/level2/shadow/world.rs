@@ -1,29 +1,53 @@
 use std::f32::consts;
 
+use glam::Vec3;
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
-    BindGroupLayoutEntry, ShaderStages,
+    ShaderStages,
 };
 
 use crate::{
-    demos::update_scene::world::generate_matrix,
-    shared::ready_paint::{return_res, Ready},
+    demos::{level2::SHADOW_CANVAS, update_scene::world::generate_matrix},
+    shaders::Uniforms,
+    shared::{
+        camera::CameraController,
+        ready_paint::{get_res, get_res_mut, return_res, Ready, Update},
+    },
+    utils::{layout::write_field, reflect::ShaderLayout},
+    web::input::Input,
 };
 
+use super::imported::ImportedScene;
+
+// cube、model、wireframe、instanced 都按这个声明使用 uniforms，group 号由各自的管线决定
+const UNIFORMS_DECLARATION: &str = "#include \"uniforms.wgsl\"
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;";
+
+const CAMERA_EYE: Vec3 = Vec3::new(-3.0, 3.0, -3.0);
+
 #[derive(Default)]
 pub struct World {
     pub uniforms_bind_group_layout: Option<wgpu::BindGroupLayout>,
     pub uniforms_bind_group: Option<wgpu::BindGroup>,
     pub uniform_buffer: Option<wgpu::Buffer>,
+    camera: Option<CameraController>,
+    projection: glam::Mat4,
 }
-pub fn camera(aspect_ratio: f32) -> glam::Mat4 {
-    let projection = glam::Mat4::perspective_rh(consts::FRAC_PI_4, aspect_ratio, 0.1, 100.0);
-    let view = glam::Mat4::look_at_rh(
-        glam::Vec3::new(-3.0, 3.0, -3.0),
-        glam::Vec3::ZERO,
-        glam::Vec3::Y,
-    );
-    projection * view
+fn projection(aspect_ratio: f32) -> glam::Mat4 {
+    glam::Mat4::perspective_rh(consts::FRAC_PI_4, aspect_ratio, 0.1, 100.0)
+}
+// 导入场景里有相机时用它的位置、朝向和投影，否则用默认视角：(eye, target, projection)
+fn initial_camera(imported: &ImportedScene, aspect_ratio: f32) -> (Vec3, Vec3, glam::Mat4) {
+    match &imported.camera {
+        Some((camera, world)) => {
+            let eye = world.transform_point3(Vec3::ZERO);
+            let forward = world.transform_vector3(Vec3::NEG_Z).normalize();
+            // 绕视线上离原点最近的点转
+            let target = eye + forward * forward.dot(-eye).max(1.);
+            (eye, target, camera.projection_matrix(aspect_ratio))
+        }
+        None => (CAMERA_EYE, Vec3::ZERO, projection(aspect_ratio)),
+    }
 }
 impl Ready for World {
     fn ready(
@@ -31,10 +55,11 @@ impl Ready for World {
         data: &mut crate::shared::ready_paint::HashTypeId2Data,
         gfx: &crate::shared::ready_paint::Gfx,
     ) {
-        let world = camera(800. / 600.);
+        let (eye, target, projection) = initial_camera(get_res::<ImportedScene>(data), 800. / 600.);
+        let world = projection * glam::Mat4::look_at_rh(eye, target, Vec3::Y);
         let uniforms = Uniforms {
             resolution: [800., 600.],
-            matrix: world.to_cols_array(),
+            matrix: world.to_cols_array_2d(),
             delta_time: 0.,
             _padding: 0.,
         };
@@ -45,31 +70,22 @@ impl Ready for World {
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
-        let uniforms_bind_group_layout =
-            gfx.device
-                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: Some("uniforms_bind_group_layout"),
-                    entries: &[BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: wgpu::BufferSize::new(
-                                std::mem::size_of::<Uniforms>() as u64,
-                            ),
-                        },
-                        count: None,
-                    }],
-                });
-        let uniforms_bind_group = gfx.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("uniforms_bind_group"),
-            layout: &uniforms_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
+        let layout = ShaderLayout::shared_group(
+            &gfx.pipelines,
+            "uniforms_bind_group_layout",
+            UNIFORMS_DECLARATION,
+            ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+        );
+        let uniforms_bind_group = layout.create_bind_group(
+            &gfx.device,
+            "uniforms_bind_group",
+            0,
+            &[wgpu::BindGroupEntry {
                 binding: 0,
                 resource: uniform_buffer.as_entire_binding(),
             }],
-        });
+        );
+        let uniforms_bind_group_layout = layout.bind_group_layouts[0].clone();
 
         return_res(
             data,
@@ -77,16 +93,38 @@ impl Ready for World {
                 uniforms_bind_group_layout: Some(uniforms_bind_group_layout),
                 uniforms_bind_group: Some(uniforms_bind_group),
                 uniform_buffer: Some(uniform_buffer),
+                camera: Some(CameraController::new(
+                    SHADOW_CANVAS,
+                    eye,
+                    target,
+                    glam::Vec3::Y,
+                )),
+                projection,
             },
         );
     }
 }
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-struct Uniforms {
-    matrix: [f32; 16],
-    resolution: [f32; 2],
-    delta_time: f32,
-    _padding: f32,
+impl Update for World {
+    fn update(
+        data: &mut crate::shared::ready_paint::HashTypeId2Data,
+        gfx: &crate::shared::ready_paint::Gfx,
+        dt: f32,
+        _surface: &std::sync::Arc<wgpu::Surface<'static>>,
+    ) {
+        let input = get_res::<Input>(data).clone();
+        let world = get_res_mut::<Self>(data);
+        let camera = world.camera.as_mut().unwrap();
+        // dt 是毫秒
+        if camera.update(&input, dt / 1000.) {
+            let matrix = (world.projection * camera.view()).to_cols_array_2d();
+            write_field!(
+                &gfx.queue,
+                world.uniform_buffer.as_ref().unwrap(),
+                Uniforms,
+                matrix,
+                matrix
+            );
+        }
+    }
 }
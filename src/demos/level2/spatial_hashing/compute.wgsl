@@ -3,15 +3,7 @@ struct Particle {
   vel : vec2<f32>,
 };
 
-struct SimParams {
-  deltaT : f32,        // 时间步长
-  rule1Distance : f32,  // 凝聚规则的距离阈值
-  rule2Distance : f32,  // 分离规则的距离阈值
-  rule3Distance : f32,  // 对齐规则的距离阈值
-  rule1Scale : f32,     // 凝聚力的强度系数
-  rule2Scale : f32,     // 分离力的强度系数
-  rule3Scale : f32,     // 对齐力的强度系数
-};
+#include "sim_params.wgsl"
 
 @group(0) @binding(0) var<uniform> params : SimParams;
 @group(0) @binding(1) var<storage, read> particlesSrc : array<Particle>;
@@ -49,14 +41,14 @@ fn main(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
     let pos = particlesSrc[i].pos;
     let vel = particlesSrc[i].vel;
 
-    if (distance(pos, vPos) < params.rule1Distance) {
+    if (distance(pos, vPos) < params.rule1_distance) {
       cMass += pos;
       cMassCount += 1;
     }
-    if (distance(pos, vPos) < params.rule2Distance) {
+    if (distance(pos, vPos) < params.rule2_distance) {
       colVel += vPos - pos;  
     }
-    if (distance(pos, vPos) < params.rule3Distance) {
+    if (distance(pos, vPos) < params.rule3_distance) {
       cVel += vel;
       cVelCount += 1;
     }
@@ -72,15 +64,15 @@ fn main(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
     cVel *= 1.0 / f32(cVelCount);
   }
 
-  vVel = vVel + (cMass * params.rule1Scale) +
-      (colVel * params.rule2Scale) +
-      (cVel * params.rule3Scale);
+  vVel = vVel + (cMass * params.rule1_scale) +
+      (colVel * params.rule2_scale) +
+      (cVel * params.rule3_scale);
 
   // clamp velocity for a more pleasing simulation
   vVel = normalize(vVel) * clamp(length(vVel), 0.0, 0.1);
 
   // kinematic update
-  vPos += vVel * params.deltaT;
+  vPos += vVel * params.delta_t;
 
   // Wrap around boundary
   if (vPos.x < -1.0) {
@@ -1,21 +1,44 @@
+#include "audio.wgsl"
+
+@group(0) @binding(0) var<uniform> audio: AudioUniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+}
+
+// 每个 boid 跟着一个频段：低频偏红，高频偏蓝
+fn band_color(band: u32) -> vec3<f32> {
+    let t = f32(band) / 7.0;
+    return vec3<f32>(1.0 - t, 0.4 + 0.6 * sin(t * 3.14159), t);
+}
+
 @vertex
 fn vs_main(
+    @builtin(instance_index) instance: u32,
     @location(0) particle_pos: vec2<f32>,
     @location(1) particle_vel: vec2<f32>,
     @location(2) position: vec2<f32>,
-) -> @builtin(position) vec4<f32> {
+) -> VertexOutput {
+    let band = instance % 8u;
+    let level = audio_band(audio, band);
     // Add a scale factor to make the triangle smaller
-    let scale = 0.1;
+    // 有声音时随所属频段和整体响度放大
+    let scale = 0.1 * (1.0 + level * 1.5 + audio.loudness * 2.0);
     
     let angle = -atan2(particle_vel.x, particle_vel.y);
     let pos = vec2<f32>(
         (position.x * scale) * cos(angle) - (position.y * scale) * sin(angle),
         (position.x * scale) * sin(angle) + (position.y * scale) * cos(angle)
     );
-    return vec4<f32>(pos + particle_pos, 0.0, 1.0);
+    var out: VertexOutput;
+    out.position = vec4<f32>(pos + particle_pos, 0.0, 1.0);
+    // 没有音源时 level 为 0，保持原来的白色
+    out.color = mix(vec3<f32>(1.0), band_color(band), level);
+    return out;
 }
 
 @fragment
-fn fs_main() -> @location(0) vec4<f32> {
-    return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.color, 1.0);
 }
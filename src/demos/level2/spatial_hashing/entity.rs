@@ -2,7 +2,21 @@ use bytemuck::{Pod, Zeroable};
 use std::mem::size_of;
 use wgpu::{util::DeviceExt, PipelineCompilationOptions}; // Add this import for size_of
 
-use crate::shared::ready_paint::{get_res, get_res_mut, return_res, Pass, Ready, Update};
+use crate::{
+    shaders::SimParams,
+    shared::{
+        audio::AudioReactive,
+        hot_reload::{self, Reload},
+        params::{self, ParamDesc},
+        ready_paint::{get_res, get_res_mut, return_res, Pass, Ready, Update},
+    },
+    utils::{
+        pipeline_cache::CreatePipeline,
+        reflect::{Bindings, ShaderLayout},
+        rng::Rng,
+    },
+    web::{input::Input, record},
+};
 #[repr(C)]
 #[derive(Debug, Clone, Pod, Copy, Zeroable)]
 pub struct Entity {
@@ -30,28 +44,72 @@ pub struct EntityRender {
     pub render_pipeline: Option<wgpu::RenderPipeline>,
     pub buffer_index: usize,
     pub entity_shape_vertex_buffer: Option<wgpu::Buffer>, // New field to store the entity shape vertices
+    pub compute_pipeline_layout: Option<wgpu::PipelineLayout>,
+    pub render_pipeline_layout: Option<wgpu::PipelineLayout>,
+    // 正在异步重建的管线，编译通过后在 update 里换上
+    compute_reload: Option<Reload<wgpu::ComputePipeline>>,
+    render_reload: Option<Reload<wgpu::RenderPipeline>>,
 }
 
 pub const MAX_ENTITY: usize = 5000;
+
+const COMPUTE_SHADER: &str = "boids.compute";
+const DRAW_SHADER: &str = "boids.draw";
+
+const BOID_PARAM_SYSTEM: &str = "boids";
+// 除 paused 外和 SimParams（crate::shaders）的字段一一对应
+const BOID_PARAMS: &[ParamDesc] = &[
+    ParamDesc::float("delta_t", 0.04, 0.0, 0.2), // 较小的时间步长更稳定
+    ParamDesc::float("rule1_distance", 0.1, 0.0, 0.5), // 凝聚距离
+    ParamDesc::float("rule2_distance", 0.02, 0.0, 0.5), // 分离距离(较小)
+    ParamDesc::float("rule3_distance", 0.08, 0.0, 0.5), // 对齐距离
+    ParamDesc::float("rule1_scale", 0.01, 0.0, 0.2), // 凝聚力强度(保持较弱)
+    ParamDesc::float("rule2_scale", 0.03, 0.0, 0.2), // 分离力强度(较强以避免聚集)
+    ParamDesc::float("rule3_scale", 0.02, 0.0, 0.2), // 对齐力强度(适中)
+    ParamDesc::bool("paused", false),            // 不写入 SimParams，只控制是否派发 compute
+];
+
+pub fn boids_paused() -> bool {
+    params::get(BOID_PARAM_SYSTEM, "paused") != 0.
+}
+
+fn boid_config() -> SimParams {
+    let get = |name| params::get(BOID_PARAM_SYSTEM, name);
+    SimParams {
+        delta_t: get("delta_t"),
+        rule1_distance: get("rule1_distance"),
+        rule2_distance: get("rule2_distance"),
+        rule3_distance: get("rule3_distance"),
+        rule1_scale: get("rule1_scale"),
+        rule2_scale: get("rule2_scale"),
+        rule3_scale: get("rule3_scale"),
+    }
+}
 impl Ready for EntityRender {
     fn ready(
         &mut self,
         data: &mut crate::shared::ready_paint::HashTypeId2Data,
         gfx: &crate::shared::ready_paint::Gfx,
     ) {
-        let compute_shader = gfx
-            .device
-            .create_shader_module(wgpu::include_wgsl!("compute.wgsl"));
-        let draw_shader = gfx
-            .device
-            .create_shader_module(wgpu::include_wgsl!("draw.wgsl"));
-        // 实体随机位置和动量
+        // 源码可以被 JS 热替换，重进页面时用最后一次替换成功的
+        let compute_source = hot_reload::register(COMPUTE_SHADER, include_str!("compute.wgsl"));
+        let draw_source = hot_reload::register(DRAW_SHADER, include_str!("draw.wgsl"));
+        let (compute_shader, compute_reflect) = gfx
+            .pipelines
+            .validated_shader(COMPUTE_SHADER, &compute_source, &[])
+            .unwrap();
+        let (draw_shader, draw_reflect) = gfx
+            .pipelines
+            .validated_shader(DRAW_SHADER, &draw_source, &[])
+            .unwrap();
+        // 实体随机位置和动量，录制时用固定种子
+        let mut rng = Rng::new(record::seed());
         let mut entities = Vec::new();
         for i in 0..MAX_ENTITY {
             let id = i as u32;
-            let position = random_vec2(-1.0, 1.0);
+            let position = random_vec2(&mut rng, -1.0, 1.0);
             // 确保初始速度更均衡，增加一些随机性
-            let velocity = random_vec2(-0.05, 0.05);
+            let velocity = random_vec2(&mut rng, -0.05, 0.05);
             entities.push(Entity { position, velocity });
         }
         // instance 变化的数据
@@ -73,70 +131,23 @@ impl Ready for EntityRender {
                     usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
                 });
 
-        let boid_config = [
-            0.04f32, // deltaT - 较小的时间步长更稳定
-            0.1,     // rule1Distance - 凝聚距离
-            0.02,    // rule2Distance - 分离距离(较小)
-            0.08,     // rule3Distance - 对齐距离
-            0.01,    // rule1Scale - 凝聚力强度(保持较弱)
-            0.03,    // rule2Scale - 分离力强度(较强以避免聚集)
-            0.02,    // rule3Scale - 对齐力强度(适中)
-        ]
-        .to_vec();
+        params::declare(BOID_PARAM_SYSTEM, BOID_PARAMS);
+        let boid_config = boid_config();
         let boid_config_buffer = gfx
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("Simulation Parameter Buffer"),
-                contents: bytemuck::cast_slice(&boid_config),
+                contents: bytemuck::bytes_of(&boid_config),
                 usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             });
 
-        let entity_size: usize = size_of::<Entity>();
-        let bind_group_layout =
-            gfx.device
-                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: Some("Entity Bind Group Layout"),
-                    entries: &[
-                        wgpu::BindGroupLayoutEntry {
-                            binding: 0,
-                            visibility: wgpu::ShaderStages::COMPUTE,
-                            ty: wgpu::BindingType::Buffer {
-                                ty: wgpu::BufferBindingType::Uniform,
-                                has_dynamic_offset: false,
-                                min_binding_size: wgpu::BufferSize::new(
-                                    (boid_config.len() * size_of::<f32>()) as _,
-                                ),
-                            },
-                            count: None,
-                        },
-                        // 读取
-                        wgpu::BindGroupLayoutEntry {
-                            binding: 1,
-                            visibility: wgpu::ShaderStages::COMPUTE,
-                            ty: wgpu::BindingType::Buffer {
-                                ty: wgpu::BufferBindingType::Storage { read_only: true },
-                                has_dynamic_offset: false,
-                                min_binding_size: wgpu::BufferSize::new(
-                                    (entity_size * MAX_ENTITY) as u64,
-                                ),
-                            },
-                            count: None,
-                        },
-                        // 写入
-                        wgpu::BindGroupLayoutEntry {
-                            binding: 2,
-                            visibility: wgpu::ShaderStages::COMPUTE,
-                            ty: wgpu::BindingType::Buffer {
-                                ty: wgpu::BufferBindingType::Storage { read_only: false },
-                                has_dynamic_offset: false,
-                                min_binding_size: wgpu::BufferSize::new(
-                                    (entity_size * MAX_ENTITY) as u64,
-                                ),
-                            },
-                            count: None,
-                        },
-                    ],
-                });
+        // layout 由 compute.wgsl 反射得到：params + 读写两个粒子 buffer
+        let compute_layout = ShaderLayout::new(
+            &gfx.pipelines,
+            "Compute Pipeline Layout",
+            Bindings::new(&[&compute_reflect]).unwrap(),
+            &[],
+        );
 
         let mut compute_bind_groups = Vec::<wgpu::BindGroup>::new();
         let mut compute_buffers = Vec::<wgpu::Buffer>::new();
@@ -154,10 +165,11 @@ impl Ready for EntityRender {
         }
 
         for i in 0..2 {
-            compute_bind_groups.push(gfx.device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Entity Compute Bind Group"),
-                layout: &bind_group_layout,
-                entries: &[
+            compute_bind_groups.push(compute_layout.create_bind_group(
+                &gfx.device,
+                "Entity Compute Bind Group",
+                0,
+                &[
                     wgpu::BindGroupEntry {
                         binding: 0,
                         resource: boid_config_buffer.as_entire_binding(),
@@ -171,90 +183,28 @@ impl Ready for EntityRender {
                         resource: compute_buffers[(i + 1) % 2].as_entire_binding(),
                     },
                 ],
-            }));
+            ));
         }
-        let compute_pipeline_layout =
-            gfx.device
-                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: Some("Compute Pipeline Layout"),
-                    bind_group_layouts: &[&bind_group_layout],
-                    push_constant_ranges: &[],
-                });
+        let compute_pipeline_layout = compute_layout.pipeline_layout;
         let compute_pipeline =
-            gfx.device
-                .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-                    label: Some("Compute pipeline"),
-                    layout: Some(&compute_pipeline_layout),
-                    module: &compute_shader,
-                    entry_point: Some("main"),
-                    compilation_options: Default::default(),
-                    cache: None,
-                });
+            create_compute_pipeline(&gfx.pipelines, &compute_pipeline_layout, &compute_shader);
 
-        let render_pipeline_layout =
-            gfx.device
-                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: Some("Render Pipeline Layout"),
-                    bind_group_layouts: &[],
-                    push_constant_ranges: &[],
-                });
-        let render_pipeline = gfx
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("Render Pipeline"),
-                layout: Some(&render_pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &draw_shader,
-                    entry_point: Some("vs_main"),
-                    buffers: &[
-                        // 实体实例数据
-                        wgpu::VertexBufferLayout {
-                            array_stride: size_of::<Entity>() as wgpu::BufferAddress,
-                            step_mode: wgpu::VertexStepMode::Instance,
-                            attributes: &wgpu::vertex_attr_array![ 0 => Float32x2 , 1 => Float32x2],
-                        },
-                        // 三角形顶点数据 - 作为第二个顶点缓冲区
-                        wgpu::VertexBufferLayout {
-                            array_stride: size_of::<[f32; 2]>() as wgpu::BufferAddress,
-                            step_mode: wgpu::VertexStepMode::Vertex,
-                            attributes: &wgpu::vertex_attr_array![ 2 => Float32x2 ],
-                        },
-                    ],
-                    compilation_options: PipelineCompilationOptions::default(),
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &draw_shader,
-                    entry_point: Some("fs_main"),
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: wgpu::TextureFormat::Bgra8Unorm,
-                        blend: Some(wgpu::BlendState {
-                            color: wgpu::BlendComponent {
-                                src_factor: wgpu::BlendFactor::SrcAlpha,
-                                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                                operation: wgpu::BlendOperation::Add,
-                            },
-                            alpha: wgpu::BlendComponent {
-                                src_factor: wgpu::BlendFactor::One,
-                                dst_factor: wgpu::BlendFactor::Zero,
-                                operation: wgpu::BlendOperation::Add,
-                            },
-                        }),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                    compilation_options: PipelineCompilationOptions::default(),
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: Some(wgpu::Face::Back),
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    ..Default::default()
-                },
-                depth_stencil: None,
-                multisample: Default::default(),
-                multiview: None,
-                cache: None,
-            });
+        // group 0 是 AudioReactive 的 uniform，bind group 由它自己创建
+        let render_pipeline_layout = ShaderLayout::new(
+            &gfx.pipelines,
+            "Render Pipeline Layout",
+            Bindings::new(&[&draw_reflect]).unwrap(),
+            &[(
+                0,
+                get_res::<AudioReactive>(data)
+                    .bind_group_layout
+                    .as_ref()
+                    .unwrap(),
+            )],
+        )
+        .pipeline_layout;
+        let render_pipeline =
+            create_render_pipeline(&gfx.pipelines, &render_pipeline_layout, &draw_shader);
 
         return_res(
             data,
@@ -267,23 +217,155 @@ impl Ready for EntityRender {
                 render_pipeline: Some(render_pipeline),
                 buffer_index: 0,
                 entity_shape_vertex_buffer: Some(entity_shape_buffer), // Store the entity shape buffer
+                compute_pipeline_layout: Some(compute_pipeline_layout),
+                render_pipeline_layout: Some(render_pipeline_layout),
+                compute_reload: None,
+                render_reload: None,
             },
         );
     }
 }
 
-fn random_vec2(min: f32, max: f32) -> [f32; 2] {
-    let mut bytes = [0u8; 8];
-    getrandom::getrandom(&mut bytes).expect("获取随机字节失败");
+fn create_compute_pipeline(
+    pipelines: &impl CreatePipeline,
+    layout: &wgpu::PipelineLayout,
+    module: &wgpu::ShaderModule,
+) -> wgpu::ComputePipeline {
+    pipelines.compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Compute pipeline"),
+        layout: Some(layout),
+        module,
+        entry_point: Some("main"),
+        compilation_options: Default::default(),
+        cache: None,
+    })
+}
+
+fn create_render_pipeline(
+    pipelines: &impl CreatePipeline,
+    layout: &wgpu::PipelineLayout,
+    module: &wgpu::ShaderModule,
+) -> wgpu::RenderPipeline {
+    pipelines.render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module,
+            entry_point: Some("vs_main"),
+            buffers: &[
+                // 实体实例数据
+                wgpu::VertexBufferLayout {
+                    array_stride: size_of::<Entity>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![ 0 => Float32x2 , 1 => Float32x2],
+                },
+                // 三角形顶点数据 - 作为第二个顶点缓冲区
+                wgpu::VertexBufferLayout {
+                    array_stride: size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![ 2 => Float32x2 ],
+                },
+            ],
+            compilation_options: PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: wgpu::TextureFormat::Bgra8Unorm,
+                blend: Some(wgpu::BlendState {
+                    color: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::SrcAlpha,
+                        dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                    alpha: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::One,
+                        dst_factor: wgpu::BlendFactor::Zero,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                }),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: Default::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+fn random_vec2(rng: &mut Rng, min: f32, max: f32) -> [f32; 2] {
+    [rng.range(min, max), rng.range(min, max)]
+}
 
-    // 使用4个字节构建一个u32，然后转换为0-1范围的f32
-    let rand_x =
-        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32 / u32::MAX as f32;
-    let rand_y =
-        u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as f32 / u32::MAX as f32;
+impl Update for EntityRender {
+    fn update(
+        data: &mut crate::shared::ready_paint::HashTypeId2Data,
+        gfx: &crate::shared::ready_paint::Gfx,
+        _dt: f32,
+        _surface: &std::sync::Arc<wgpu::Surface<'static>>,
+    ) {
+        // 空格键暂停/继续
+        if get_res::<Input>(data).key_pressed("Space") {
+            let _ = params::set(BOID_PARAM_SYSTEM, "paused", (!boids_paused()) as u8 as f32);
+        }
+        Self::hot_reload(data, gfx);
+        // 只在 JS 改过参数时重写 SimParams
+        if !params::take_changed(BOID_PARAM_SYSTEM) {
+            return;
+        }
+        let entity_render = get_res::<Self>(data);
+        gfx.queue.write_buffer(
+            entity_render.boid_config_buffer.as_ref().unwrap(),
+            0,
+            bytemuck::bytes_of(&boid_config()),
+        );
+    }
+}
 
-    let range = max - min;
-    [min + rand_x * range, min + rand_y * range]
+impl EntityRender {
+    // 处理 JS 提交的新源码，以及把重建好的管线换上
+    fn hot_reload(
+        data: &mut crate::shared::ready_paint::HashTypeId2Data,
+        gfx: &crate::shared::ready_paint::Gfx,
+    ) {
+        let entity_render = get_res_mut::<Self>(data);
+        if let Some(request) = hot_reload::take_request(COMPUTE_SHADER) {
+            let layout = entity_render.compute_pipeline_layout.as_ref().unwrap();
+            entity_render.compute_reload = Some(hot_reload::rebuild(
+                &gfx.device,
+                COMPUTE_SHADER,
+                request,
+                &[],
+                |module| create_compute_pipeline(gfx.device.as_ref(), layout, module),
+            ));
+        }
+        if let Some(request) = hot_reload::take_request(DRAW_SHADER) {
+            let layout = entity_render.render_pipeline_layout.as_ref().unwrap();
+            entity_render.render_reload = Some(hot_reload::rebuild(
+                &gfx.device,
+                DRAW_SHADER,
+                request,
+                &[],
+                |module| create_render_pipeline(gfx.device.as_ref(), layout, module),
+            ));
+        }
+        if let Some(pipeline) = entity_render.compute_reload.as_ref().and_then(Reload::take) {
+            entity_render.compute_pipeline = Some(pipeline);
+        }
+        if let Some(pipeline) = entity_render.render_reload.as_ref().and_then(Reload::take) {
+            entity_render.render_pipeline = Some(pipeline);
+        }
+    }
 }
 
 impl<'a> Pass<'a> for EntityRender {
@@ -311,6 +393,9 @@ impl<'a> Pass<'a> for EntityRender {
                 .slice(..),
         );
 
+        // 音频 uniform，boid 的大小和颜色跟着频段变
+        render_pass.set_bind_group(0, get_res::<AudioReactive>(data).bind_group.as_ref(), &[]);
+
         // 绘制所有实体
         render_pass.draw(0..3, 0..MAX_ENTITY as u32);
 
@@ -1,8 +1,7 @@
 use crate::{
-    console_log,
     shared::{ready_paint::Scene, Shared},
     utils::split,
-    App,
+    web::router::Page,
 };
 use std::{cell::RefCell, collections::HashMap, hash::Hash, rc::Rc, sync::Arc};
 
@@ -10,6 +9,7 @@ use update_scene::UpdateScene;
 use wgpu::SurfaceTexture;
 pub mod level2;
 pub mod one_shot_scene;
+pub mod playground;
 pub mod test;
 pub mod update_scene;
 pub struct Demo {
@@ -25,18 +25,24 @@ impl Demo {
         self.demos.insert(name.to_string(), demo);
     }
     pub fn run_all(&mut self, shared: Arc<Shared>, page_active: Rc<RefCell<bool>>) {
-        console_log!("run_all");
+        log::debug!("run_all");
         // 头两个 canvas 的 no update scene
         self.add_demo("canvas-1", Box::new(test::test));
         self.add_demo("canvas-2", Box::new(one_shot_scene::scene2));
 
         // updatable scene
         self.demos.iter_mut().for_each(|(name, demo)| {
-            console_log!("run_all: {}", name);
+            log::debug!("run_all: {}", name);
             demo(shared.clone());
         });
 
-        console_log!("run_all end");
+        log::debug!("run_all end");
         UpdateScene::run(shared.clone(), page_active);
     }
 }
+
+impl Page for Demo {
+    fn enter(&mut self, shared: Arc<Shared>, active: Rc<RefCell<bool>>) {
+        self.run_all(shared, active);
+    }
+}
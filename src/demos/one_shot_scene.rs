@@ -1,7 +1,7 @@
-use crate::{console_log, shared::Shared, utils::split};
+use crate::{shared::Shared, utils::split};
 use std::sync::Arc;
 pub fn scene2(shared: Arc<Shared>) {
-    console_log!("Demo test is ready");
+    log::debug!("Demo test is ready");
     let (queue, frame, mut encoder) = split(&"canvas-2".to_string(), shared.clone());
     let view = frame
         .texture
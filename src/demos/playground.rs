@@ -0,0 +1,286 @@
+/// Shadertoy 式的 shader playground：一块画布铺满一个 fragment shader，每帧写入时间、分辨率和鼠标状态
+/// 标准 Uniforms 和铺满画布的 vs_main 见 src/shaders/playground.wgsl，效果里 `#include` 进来即可
+/// 内置 src/shaders 下的 fluid / boids，外加一个自定义效果，JS 里：
+/// `set_shader_source("playground.custom", wgsl)` 提交源码，`set_param("playground.effect", 2)` 切过去
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use wgpu::{util::DeviceExt, PipelineCompilationOptions};
+
+use crate::{
+    shaders::PlaygroundUniforms,
+    shared::{
+        hot_reload::{self, Reload},
+        params::{self, ParamDesc},
+        ready_paint::{
+            get_res, get_res_mut, return_res, scoped, Gfx, HashTypeId2Data, Paint, Pass, Queue,
+            Ready, Scene, Update,
+        },
+        Shared,
+    },
+    utils::{
+        pipeline_cache::CreatePipeline,
+        reflect::{Bindings, ShaderLayout},
+        split_for_update,
+    },
+    web::{
+        capture, frame_loop,
+        input::{Input, BUTTON_LEFT},
+        router::Page,
+    },
+};
+
+pub const PLAYGROUND_CANVAS: &str = "canvas-9";
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 600;
+
+// (热替换用的名字, 内置源码)，下标就是 effect 参数的取值
+const EFFECTS: &[(&str, &str)] = &[
+    ("playground.fluid", include_str!("../shaders/fluid.wgsl")),
+    ("playground.boids", include_str!("../shaders/boids.wgsl")),
+    (
+        "playground.custom",
+        include_str!("../shaders/template.wgsl"),
+    ),
+];
+
+const PARAM_SYSTEM: &str = "playground";
+const PARAMS: &[ParamDesc] = &[
+    ParamDesc::int("effect", 0, 0, EFFECTS.len() as i32 - 1),
+    ParamDesc::float("speed", 1.0, 0.0, 4.0), // 时间流速
+];
+
+#[derive(Default)]
+pub struct Playground {
+    layout: Option<ShaderLayout>,
+    pipelines: Vec<wgpu::RenderPipeline>,
+    reloads: Vec<Option<Reload<wgpu::RenderPipeline>>>,
+    uniform_buffer: Option<wgpu::Buffer>,
+    bind_group: Option<wgpu::BindGroup>,
+    uniforms: PlaygroundUniforms,
+}
+
+impl Ready for Playground {
+    fn ready(&mut self, data: &mut HashTypeId2Data, gfx: &Gfx) {
+        params::declare(PARAM_SYSTEM, PARAMS);
+        let (modules, reflects): (Vec<_>, Vec<_>) = EFFECTS
+            .iter()
+            .map(|&(name, default)| {
+                let source = hot_reload::register(name, default);
+                gfx.pipelines.validated_shader(name, &source, &[]).unwrap()
+            })
+            .unzip();
+        // 所有效果共用一个 layout，只有 group 0 的 Uniforms
+        let layout = ShaderLayout::new(
+            &gfx.pipelines,
+            "playground pipeline layout",
+            Bindings::new(&reflects.iter().map(|r| r.as_ref()).collect::<Vec<_>>()).unwrap(),
+            &[],
+        );
+        let pipelines = modules
+            .iter()
+            .map(|module| create_pipeline(&gfx.pipelines, &layout.pipeline_layout, module))
+            .collect();
+
+        let uniforms = PlaygroundUniforms {
+            resolution: [WIDTH as f32, HEIGHT as f32],
+            mouse_position: [0.5, 0.5],
+            ..Default::default()
+        };
+        let uniform_buffer = gfx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("playground uniforms"),
+                contents: bytemuck::bytes_of(&uniforms),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        let bind_group = layout.create_bind_group(
+            &gfx.device,
+            "playground bind group",
+            0,
+            &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        );
+
+        return_res(
+            data,
+            Self {
+                layout: Some(layout),
+                pipelines,
+                reloads: EFFECTS.iter().map(|_| None).collect(),
+                uniform_buffer: Some(uniform_buffer),
+                bind_group: Some(bind_group),
+                uniforms,
+            },
+        );
+    }
+}
+
+fn create_pipeline(
+    pipelines: &impl CreatePipeline,
+    layout: &wgpu::PipelineLayout,
+    module: &wgpu::ShaderModule,
+) -> wgpu::RenderPipeline {
+    pipelines.render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("playground pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module,
+            entry_point: Some("vs_main"),
+            buffers: &[],
+            compilation_options: PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: wgpu::TextureFormat::Bgra8Unorm,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+impl Update for Playground {
+    fn update(
+        data: &mut HashTypeId2Data,
+        gfx: &Gfx,
+        dt: f32,
+        _surface: &Arc<wgpu::Surface<'static>>,
+    ) {
+        let input = get_res::<Input>(data).canvas(PLAYGROUND_CANVAS);
+        let playground = get_res_mut::<Self>(data);
+        let layout = &playground.layout.as_ref().unwrap().pipeline_layout;
+        for (i, &(name, _)) in EFFECTS.iter().enumerate() {
+            if let Some(request) = hot_reload::take_request(name) {
+                playground.reloads[i] = Some(hot_reload::rebuild(
+                    &gfx.device,
+                    name,
+                    request,
+                    &[],
+                    |module| create_pipeline(gfx.device.as_ref(), layout, module),
+                ));
+            }
+            if let Some(pipeline) = playground.reloads[i].as_ref().and_then(Reload::take) {
+                playground.pipelines[i] = pipeline;
+            }
+        }
+
+        // dt 是毫秒，shader 里用秒
+        let dt = dt / 1000. * params::get(PARAM_SYSTEM, "speed");
+        let uniforms = &mut playground.uniforms;
+        uniforms.time += dt;
+        uniforms.delta_time = dt;
+        uniforms.frame = uniforms.frame.wrapping_add(1);
+        // 移出画布后停在最后的位置
+        if input.pointer.hovered {
+            uniforms.mouse_position = input.pointer.uv();
+        }
+        uniforms.mouse_down = (input.pointer.held[BUTTON_LEFT] || !input.touches.is_empty()) as u32;
+        gfx.queue.write_buffer(
+            playground.uniform_buffer.as_ref().unwrap(),
+            0,
+            bytemuck::bytes_of(uniforms),
+        );
+    }
+}
+
+impl<'a> Pass<'a> for Playground {
+    fn pass(
+        data: &mut HashTypeId2Data,
+        mut render_pass: wgpu::RenderPass<'a>,
+    ) -> wgpu::RenderPass<'a> {
+        let playground = get_res::<Self>(data);
+        let effect = (params::get(PARAM_SYSTEM, "effect") as usize).min(EFFECTS.len() - 1);
+        render_pass.set_pipeline(&playground.pipelines[effect]);
+        render_pass.set_bind_group(0, playground.bind_group.as_ref().unwrap(), &[]);
+        render_pass.draw(0..3, 0..1);
+        render_pass
+    }
+}
+
+pub struct PaintPlayground {}
+
+impl Paint for PaintPlayground {
+    fn paint(
+        data: &mut HashTypeId2Data,
+        gfx: &Gfx,
+        dt: f32,
+        surface: &Arc<wgpu::Surface<'static>>,
+    ) {
+        scoped::<Playground, _>(gfx, || Playground::update(data, gfx, dt, surface));
+
+        let frame = surface.get_current_texture().unwrap();
+        let view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = gfx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Playground Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            let _ = scoped::<Playground, _>(gfx, || Playground::pass(data, render_pass));
+        }
+        let capture =
+            capture::copy_frame(&gfx.device, &mut encoder, PLAYGROUND_CANVAS, &frame.texture);
+        gfx.queue.submit(Some(encoder.finish()));
+        if let Some(capture) = capture {
+            capture.finish();
+        }
+        frame.present();
+    }
+}
+
+pub struct PlaygroundPage {}
+
+impl PlaygroundPage {
+    pub fn run(shared: Arc<Shared>, active: Rc<RefCell<bool>>) {
+        log::debug!("Running playground");
+        let mut scene = Scene::new("playground".to_string());
+        let (_, _, surface) = split_for_update(PLAYGROUND_CANVAS, shared.clone(), WIDTH, HEIGHT);
+        return_res(&mut scene.res, shared.input.clone());
+        PlaygroundPage::introduce(&mut scene);
+        scene.ready(&shared);
+
+        let visibility = shared.visibility.clone();
+        let visible = move || visibility.is_visible(PLAYGROUND_CANVAS);
+        frame_loop::run("playground", active, visible, move |dt| {
+            scene.paint(&shared, dt, &surface);
+            shared.input.end_frame();
+        });
+    }
+}
+
+impl Page for PlaygroundPage {
+    fn enter(&mut self, shared: Arc<Shared>, active: Rc<RefCell<bool>>) {
+        PlaygroundPage::run(shared, active);
+    }
+}
+
+impl Queue for PlaygroundPage {
+    fn introduce(scene: &mut Scene) {
+        scene.add_ready(Playground::default());
+        scene.add_paint::<PaintPlayground>();
+    }
+}
@@ -1,9 +1,9 @@
 use std::sync::Arc;
 
-use crate::{console_log, shared::Shared, utils::split};
+use crate::{shared::Shared, utils::split};
 
 pub fn test(shared: Arc<Shared>) {
-    console_log!("Demo test is ready");
+    log::debug!("Demo test is ready");
     let (queue, frame, mut encoder) = split(&"canvas-1".to_string(), shared.clone());
     let view = frame
         .texture
@@ -0,0 +1,152 @@
+/// 单画布多视口模式：把 level 1 的六个视图用 viewport / scissor 画进同一个 canvas
+/// 布局用归一化矩形描述，切换由参数 `layout.tiled` 控制
+use crate::shared::{
+    params::{self, ParamDesc},
+    ready_paint::{return_res, Gfx, HashTypeId2Data, Ready},
+};
+
+use super::MAIN_CANVAS;
+
+pub const LAYOUT_PARAM_SYSTEM: &str = "layout";
+const LAYOUT_PARAMS: [ParamDesc; 1] = [ParamDesc::bool("tiled", false)];
+
+pub fn tiled_mode() -> bool {
+    params::get(LAYOUT_PARAM_SYSTEM, "tiled") != 0.
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SceneView {
+    Main,
+    Side,
+    TriangleList,
+    TriangleListNormal,
+    VR,
+    Shadow,
+}
+
+impl SceneView {
+    /// 多画布模式下各视图对应的 canvas
+    pub fn canvas_id(&self) -> &'static str {
+        match self {
+            SceneView::Main => MAIN_CANVAS,
+            SceneView::Side => "canvas-4",
+            SceneView::TriangleList => "canvas-5",
+            SceneView::TriangleListNormal => "canvas-6",
+            SceneView::VR => "canvas-7",
+            SceneView::Shadow => "canvas-8",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Tile {
+    pub view: SceneView,
+    pub rect: [f32; 4], // 归一化 x, y, w, h，左上角为原点
+    pub aspect: f32,    // 内容宽高比，和 tile 不一致时居中留边
+}
+
+#[derive(Debug, Clone)]
+pub struct TiledLayout {
+    pub canvas_id: &'static str,
+    pub tiles: Vec<Tile>,
+}
+
+impl TiledLayout {
+    /// 画进 600x300 的 canvas-7：上排四个 150x150 方形视图，下排 VR 双眼 300x150 加阴影
+    pub fn level1() -> Self {
+        let square = |view, x, y| Tile {
+            view,
+            rect: [x, y, 0.25, 0.5],
+            aspect: 1.,
+        };
+        Self {
+            canvas_id: "canvas-7",
+            tiles: vec![
+                square(SceneView::Main, 0., 0.),
+                square(SceneView::Side, 0.25, 0.),
+                square(SceneView::TriangleList, 0.5, 0.),
+                square(SceneView::TriangleListNormal, 0.75, 0.),
+                Tile {
+                    view: SceneView::VR,
+                    rect: [0., 0.5, 0.5, 0.5],
+                    aspect: 2.,
+                },
+                square(SceneView::Shadow, 0.5, 0.5),
+            ],
+        }
+    }
+
+    /// tile 在目标纹理上的像素视口 (x, y, w, h)，按整数像素对齐
+    pub fn viewport(tile: &Tile, size: [u32; 2]) -> [u32; 4] {
+        let [x, y, w, h] = tile.rect;
+        let (w, h) = (w * size[0] as f32, h * size[1] as f32);
+        let (fit_w, fit_h) = if w / h > tile.aspect {
+            (h * tile.aspect, h)
+        } else {
+            (w, w / tile.aspect)
+        };
+        let left = x * size[0] as f32 + (w - fit_w) * 0.5;
+        let top = y * size[1] as f32 + (h - fit_h) * 0.5;
+        let left = (left.round() as u32).min(size[0]);
+        let top = (top.round() as u32).min(size[1]);
+        [
+            left,
+            top,
+            (fit_w.round() as u32).min(size[0] - left).max(1),
+            (fit_h.round() as u32).min(size[1] - top).max(1),
+        ]
+    }
+}
+
+/// 多视口模式下共享的深度纹理，尺寸跟随目标 canvas
+/// 两种格式分别给三角形列表（Depth24Plus）和阴影主通道（Depth32Float）的管线
+#[derive(Default)]
+pub struct TiledTarget {
+    pub layout: Option<TiledLayout>,
+    size: [u32; 2],
+    pub depth24: Option<wgpu::TextureView>,
+    pub depth32: Option<wgpu::TextureView>,
+}
+
+impl TiledTarget {
+    pub fn ensure_size(&mut self, device: &wgpu::Device, size: [u32; 2]) {
+        if self.size == size && self.depth24.is_some() {
+            return;
+        }
+        let create = |format, label| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width: size[0],
+                        height: size[1],
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+        self.depth24 = Some(create(wgpu::TextureFormat::Depth24Plus, "tiled depth24"));
+        self.depth32 = Some(create(wgpu::TextureFormat::Depth32Float, "tiled depth32"));
+        self.size = size;
+    }
+}
+
+impl Ready for TiledTarget {
+    fn ready(&mut self, data: &mut HashTypeId2Data, _gfx: &Gfx) {
+        params::declare(LAYOUT_PARAM_SYSTEM, &LAYOUT_PARAMS);
+        // 深度纹理等第一次进入多视口模式时再按实际尺寸创建
+        return_res(
+            data,
+            TiledTarget {
+                layout: Some(TiledLayout::level1()),
+                ..Default::default()
+            },
+        );
+    }
+}
@@ -1,10 +1,8 @@
 use std::{num::NonZero, sync::Arc};
 
-use crate::{
-    console_log,
-    shared::ready_paint::{
-        get_res, get_res_mut, return_res, Gfx, HashTypeId2Data, Pass, Ready, Update,
-    },
+use crate::mesh::{edges, process};
+use crate::shared::ready_paint::{
+    get_res, get_res_mut, return_res, Gfx, HashTypeId2Data, Pass, Ready, Update,
 };
 use glam::Mat4;
 use wgpu::{util::DeviceExt, PipelineCompilationOptions, Surface};
@@ -15,7 +13,8 @@ use super::world::World;
 pub struct Tetrahedron {
     pub vertices: Option<[[f32; 3]; 4]>,
     pub object_buffer: Option<wgpu::Buffer>,
-    pub object_line_index_buffer: Option<wgpu::Buffer>,
+    pub object_line_index_buffer: Option<wgpu::Buffer>, // u16
+    pub object_line_index_count: u32,
     pub pipeline: Option<wgpu::RenderPipeline>,
     pub triangle_list_pipeline: Option<wgpu::RenderPipeline>,
     pub object_triangle_list_index_buffer: Option<wgpu::Buffer>,
@@ -48,11 +47,13 @@ impl Ready for Tetrahedron {
                 shader_location: 0,
             }],
         };
+        // 线框从三角形索引里提取，每条棱一次
+        let lines = edges::line_list(&vertices, &TRIANGLES.map(u32::from), None);
         let object_line_index_buffer =
             gfx.device
                 .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                     label: Some("Object Line Index Buffer"),
-                    contents: bytemuck::cast_slice(&[0u16, 1, 1, 2, 2, 0, 0, 3, 1, 3, 2, 3]),
+                    contents: &process::index_bytes(&lines, wgpu::IndexFormat::Uint16),
                     usage: wgpu::BufferUsages::INDEX,
                 });
 
@@ -60,21 +61,16 @@ impl Ready for Tetrahedron {
             gfx.device
                 .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                     label: Some("Object Triangle List Index Buffer"),
-                    contents: bytemuck::cast_slice(&[0u16, 1, 2, 0, 2, 3, 0, 3, 1, 1, 3, 2]),
+                    contents: bytemuck::cast_slice(&TRIANGLES),
                     usage: wgpu::BufferUsages::INDEX,
                 });
 
-        let shader = gfx
-            .device
-            .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("screen shader"),
-                source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(SHADER)),
-            });
+        let shader = gfx.pipelines.shader("screen shader", SHADER, &[]).unwrap();
         let world = get_res::<World>(data);
 
         let world_pipeline_layout =
-            gfx.device
-                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            gfx.pipelines
+                .pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                     label: Some("World Pipeline Layout"),
                     bind_group_layouts: &[world.uniforms_bind_group_layout.as_ref().unwrap()],
                     push_constant_ranges: &[],
@@ -100,8 +96,8 @@ impl Ready for Tetrahedron {
             triangle_list_depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
         let triangle_list_pipeline =
-            gfx.device
-                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            gfx.pipelines
+                .render_pipeline(&wgpu::RenderPipelineDescriptor {
                     label: Some("Triangle List Pipeline"),
                     layout: Some(&world_pipeline_layout),
                     vertex: wgpu::VertexState {
@@ -139,8 +135,8 @@ impl Ready for Tetrahedron {
                 });
 
         let object_pipeline = gfx
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            .pipelines
+            .render_pipeline(&wgpu::RenderPipelineDescriptor {
                 label: Some("Object Pipeline"),
                 layout: Some(&world_pipeline_layout),
                 vertex: wgpu::VertexState {
@@ -172,6 +168,7 @@ impl Ready for Tetrahedron {
                 vertices: Some(vertices),
                 object_buffer: Some(object_buffer),
                 object_line_index_buffer: Some(object_line_index_buffer),
+                object_line_index_count: lines.len() as u32,
                 pipeline: Some(object_pipeline),
                 triangle_list_pipeline: Some(triangle_list_pipeline),
                 object_triangle_list_index_buffer: Some(object_triangle_list_index_buffer),
@@ -193,7 +190,7 @@ impl<'a> Pass<'a> for Tetrahedron {
             tetra.object_line_index_buffer.as_ref().unwrap().slice(..),
             wgpu::IndexFormat::Uint16,
         );
-        render_pass.draw_indexed(0..12, 0, 0..1);
+        render_pass.draw_indexed(0..tetra.object_line_index_count, 0, 0..1);
         render_pass
     }
 }
@@ -216,24 +213,6 @@ impl Update for Tetrahedron {
         );
     }
 }
-const SHADER: &str = r#"
-struct Uniforms {
-    matrix: mat4x4<f32>,
-    resolution: vec2<f32>,
-    delta_time: f32,
-    _padding: f32,
-}
-
-@group(0) @binding(0)
-var<uniform> uniforms: Uniforms;
-
-@vertex
-fn vs_main(@location(0) pos: vec3<f32>) -> @builtin(position) vec4<f32> {
-    return uniforms.matrix * vec4<f32>(pos, 1.0);
-}
-
-@fragment
-fn fs_main() -> @location(0) vec4<f32> {
-    return vec4<f32>(1.0, 1.0, 1.0, 1.0);
-}
-"#;
+const SHADER: &str = include_str!("object.wgsl");
+// 四个面，每个面三个顶点
+const TRIANGLES: [u16; 12] = [0, 1, 2, 0, 2, 3, 0, 3, 1, 1, 3, 2];
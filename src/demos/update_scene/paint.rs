@@ -1,12 +1,16 @@
 use super::{
+    layout::{tiled_mode, SceneView, TiledLayout, TiledTarget},
     object::Tetrahedron,
     shadow::ShadowScene,
     triangle_list_render::{NormalTriangleListRender, TriangleListRender},
     vr::VRScene,
     world::World,
 };
-use crate::shared::ready_paint::{
-    get_res, get_res_mut, return_res, Gfx, HashTypeId2Data, Paint, Pass, Update,
+use crate::{
+    shared::ready_paint::{
+        get_res, get_res_mut, return_res, scoped, Gfx, HashTypeId2Data, Paint, Pass, Update,
+    },
+    web::{capture, visibility::Visibility},
 };
 use std::sync::Arc;
 use wgpu::Surface;
@@ -37,277 +41,263 @@ impl PaintScene {
             },
         );
     }
-}
-impl Paint for PaintScene {
-    fn paint(data: &mut HashTypeId2Data, gfx: &Gfx, dt: f32, surface: &Arc<Surface<'static>>) {
-        let mut encoder = gfx
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
 
-        // surface
-        let side_surface: Arc<Surface<'_>> = get_res::<Self>(data).side_surface.clone();
-        let triangle_list_surface = get_res::<Self>(data).triangle_list_surface.clone();
-        let triangle_list_normal_surface =
-            get_res::<Self>(data).triangle_list_normal_surface.clone();
-        let vrscene_surface = get_res::<Self>(data).vrscene_surface.clone();
-        let shadow_surface = get_res::<Self>(data).shadow_surface.clone();
-        // surface ----
+    // main 视图的 surface 由 Scene::paint 传入
+    fn surface(&self, view: SceneView, main: &Arc<Surface<'static>>) -> Arc<Surface<'static>> {
+        match view {
+            SceneView::Main => main.clone(),
+            SceneView::Side => self.side_surface.clone(),
+            SceneView::TriangleList => self.triangle_list_surface.clone(),
+            SceneView::TriangleListNormal => self.triangle_list_normal_surface.clone(),
+            SceneView::VR => self.vrscene_surface.clone(),
+            SceneView::Shadow => self.shadow_surface.clone(),
+        }
+    }
 
-        // frame
-        let frame = surface.get_current_texture().unwrap();
-        let side_frame = side_surface.get_current_texture().unwrap();
-        let triangle_list_frame = triangle_list_surface.get_current_texture().unwrap();
-        let triangle_list_normal_frame =
-            triangle_list_normal_surface.get_current_texture().unwrap();
-        let vrscene_frame = vrscene_surface.get_current_texture().unwrap();
-        let shadow_frame = shadow_surface.get_current_texture().unwrap();
-        // frame ----
+    fn surface_by_id(&self, id: &str, main: &Arc<Surface<'static>>) -> Arc<Surface<'static>> {
+        let view = ALL_VIEWS
+            .into_iter()
+            .find(|view| view.canvas_id() == id)
+            .unwrap_or(SceneView::Main);
+        self.surface(view, main)
+    }
+}
 
-        // view
-        let main_view = frame
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-        let side_view = side_frame
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-        let triangle_list_view = triangle_list_frame
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-        let triangle_list_normal_view = triangle_list_normal_frame
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-        let vrscene_view = get_res_mut::<Self>(data)
-            .vrscene_surface
-            .get_current_texture()
-            .unwrap()
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-        let shadow_view = shadow_frame
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-        // view ----
+const ALL_VIEWS: [SceneView; 6] = [
+    SceneView::Main,
+    SceneView::Side,
+    SceneView::TriangleList,
+    SceneView::TriangleListNormal,
+    SceneView::VR,
+    SceneView::Shadow,
+];
 
-        let _ = World::update(data, gfx, dt, surface);
-        let _ = Tetrahedron::update(data, gfx, dt, surface);
-        let _ = NormalTriangleListRender::update(data, gfx, dt, surface);
-        let _ = VRScene::update(data, gfx, dt, surface);
+// 一个视图画到哪里：多画布模式下是自己的 canvas，多视口模式下是共享纹理上的一块
+struct Target {
+    color: wgpu::TextureView,
+    depth: Option<wgpu::TextureView>,
+    viewport: Option<[u32; 4]>,
+    clear: bool,
+}
 
-        // 主视角
-        {
-            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &main_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.0,
-                            g: 0.0,
-                            b: 0.0,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-            let mut rpass = World::pass(data, rpass);
-            let _ = Tetrahedron::pass(data, rpass);
-        }
-        // 侧视角
-        {
-            World::update_side_view_uniforms(data, dt, gfx);
-            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &side_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.0,
-                            g: 0.0,
-                            b: 0.0,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-            let mut rpass = World::pass_for_side_view(data, rpass);
-            let _ = Tetrahedron::pass(data, rpass);
-        }
-        // 三角形列表渲染
-        // depth
-        let triangle_list_depth_texture_view = get_res::<Tetrahedron>(data)
+fn clear_color(view: SceneView) -> wgpu::Color {
+    match view {
+        SceneView::TriangleListNormal => wgpu::Color {
+            r: 0.1,
+            g: 0.2,
+            b: 0.3,
+            a: 1.0,
+        },
+        SceneView::Shadow => wgpu::Color {
+            r: 0.1,
+            g: 0.1,
+            b: 0.1,
+            a: 1.0,
+        },
+        _ => wgpu::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        },
+    }
+}
+
+// 多画布模式下各视图自己的深度纹理
+fn own_depth(view: SceneView, data: &HashTypeId2Data) -> Option<wgpu::TextureView> {
+    match view {
+        SceneView::TriangleList => get_res::<Tetrahedron>(data)
             .triangle_list_depth_texture_view
-            .as_ref()
-            .unwrap();
-        // depth ----
-        {
-            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &triangle_list_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.0,
-                            g: 0.0,
-                            b: 0.0,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: triangle_list_depth_texture_view,
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store,
-                    }),
-                    stencil_ops: None,
-                }),
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-            let mut rpass = World::pass(data, rpass);
-            let mut rpass = TriangleListRender::pass(data, rpass);
-        }
-        // 三角形列表+normal渲染
-        let triangle_list_normal_texture_view = get_res::<NormalTriangleListRender>(data)
+            .clone(),
+        SceneView::TriangleListNormal => get_res::<NormalTriangleListRender>(data)
             .triangle_list_normal_texture_view
-            .as_ref()
+            .clone(),
+        SceneView::Shadow => get_res::<ShadowScene>(data).depth_view.clone(),
+        _ => None,
+    }
+}
+
+fn tiled_depth(view: SceneView, tiled: &TiledTarget) -> Option<wgpu::TextureView> {
+    match view {
+        SceneView::TriangleList | SceneView::TriangleListNormal => tiled.depth24.clone(),
+        SceneView::Shadow => tiled.depth32.clone(),
+        _ => None,
+    }
+}
+
+fn draw(
+    view: SceneView,
+    target: &Target,
+    data: &mut HashTypeId2Data,
+    gfx: &Gfx,
+    encoder: &mut wgpu::CommandEncoder,
+) {
+    // 阴影先画深度贴图
+    if view == SceneView::Shadow {
+        let shadow_map_view = get_res::<ShadowScene>(data)
+            .first_shadow_view
+            .clone()
             .unwrap();
-        {
-            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &triangle_list_normal_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: triangle_list_normal_texture_view,
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store,
-                    }),
-                    stencil_ops: None,
+        let render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Shadow Map Pass"),
+            color_attachments: &[], // 不需要颜色输出
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &shadow_map_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
                 }),
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-            let mut rpass = World::pass(data, rpass);
-            let mut rpass = NormalTriangleListRender::pass(data, rpass);
-        }
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        let _render_pass = scoped::<ShadowScene, _>(gfx, || {
+            ShadowScene::first_catch_depth_pass(data, render_pass)
+        });
+    }
 
-        // shadow
-        // 第一个通道：渲染阴影深度贴图
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Shadow Map Pass"),
-                color_attachments: &[], // 不需要颜色输出
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: get_res::<ShadowScene>(data)
-                        .first_shadow_view
-                        .as_ref()
-                        .unwrap(),
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store,
-                    }),
-                    stencil_ops: None,
+    let load = if target.clear {
+        wgpu::LoadOp::Clear(clear_color(view))
+    } else {
+        wgpu::LoadOp::Load
+    };
+    let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: None,
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: &target.color,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load,
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: target.depth.as_ref().map(|depth| {
+            wgpu::RenderPassDepthStencilAttachment {
+                view: depth,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
                 }),
-                timestamp_writes: None,
-                occlusion_query_set: None,
+                stencil_ops: None,
+            }
+        }),
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    if let Some([x, y, w, h]) = target.viewport {
+        rpass.set_viewport(x as f32, y as f32, w as f32, h as f32, 0., 1.);
+        rpass.set_scissor_rect(x, y, w, h);
+    }
+    match view {
+        SceneView::Main => {
+            let rpass = scoped::<World, _>(gfx, || World::pass(data, rpass));
+            let _ = scoped::<Tetrahedron, _>(gfx, || Tetrahedron::pass(data, rpass));
+        }
+        SceneView::Side => {
+            let rpass = scoped::<World, _>(gfx, || World::pass_for_side_view(data, rpass));
+            let _ = scoped::<Tetrahedron, _>(gfx, || Tetrahedron::pass(data, rpass));
+        }
+        SceneView::TriangleList => {
+            let rpass = scoped::<World, _>(gfx, || World::pass(data, rpass));
+            let _ = scoped::<TriangleListRender, _>(gfx, || TriangleListRender::pass(data, rpass));
+        }
+        SceneView::TriangleListNormal => {
+            let rpass = scoped::<World, _>(gfx, || World::pass(data, rpass));
+            let _ = scoped::<NormalTriangleListRender, _>(gfx, || {
+                NormalTriangleListRender::pass(data, rpass)
             });
-
-            let _render_pass = ShadowScene::first_catch_depth_pass(data, render_pass);
         }
+        SceneView::VR => {
+            let _ = scoped::<VRScene, _>(gfx, || VRScene::pass(data, rpass));
+        }
+        SceneView::Shadow => {
+            let _ = scoped::<ShadowScene, _>(gfx, || ShadowScene::pass(data, rpass));
+        }
+    }
+}
 
-        // 第二个通道：渲染场景和阴影
-        let shadow_scene = get_res::<ShadowScene>(data);
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Main Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &shadow_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.1,
-                            b: 0.1,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: shadow_scene.depth_view.as_ref().unwrap(),
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store,
-                    }),
-                    stencil_ops: None,
-                }),
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
+impl Paint for PaintScene {
+    fn paint(data: &mut HashTypeId2Data, gfx: &Gfx, dt: f32, surface: &Arc<Surface<'static>>) {
+        let mut encoder = gfx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
 
-            let _render_pass = ShadowScene::pass(data, render_pass);
+        scoped::<World, _>(gfx, || World::update(data, gfx, dt, surface));
+        scoped::<Tetrahedron, _>(gfx, || Tetrahedron::update(data, gfx, dt, surface));
+        scoped::<NormalTriangleListRender, _>(gfx, || {
+            NormalTriangleListRender::update(data, gfx, dt, surface)
+        });
+        scoped::<VRScene, _>(gfx, || VRScene::update(data, gfx, dt, surface));
+        scoped::<World, _>(gfx, || World::update_side_view_uniforms(data, dt, gfx));
+
+        let visibility = get_res::<Visibility>(data).clone();
+        let mut frames = Vec::new();
+        let mut draws = Vec::new();
+        if tiled_mode() {
+            // 所有视图画进一个 canvas，只取一次 swapchain 纹理
+            let layout = get_res::<TiledTarget>(data).layout.clone().unwrap();
+            if visibility.is_visible(layout.canvas_id) {
+                let frame = get_res::<Self>(data)
+                    .surface_by_id(layout.canvas_id, surface)
+                    .get_current_texture()
+                    .unwrap();
+                let size = [frame.texture.width(), frame.texture.height()];
+                let tiled = get_res_mut::<TiledTarget>(data);
+                tiled.ensure_size(&gfx.device, size);
+                let color = frame
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                for (i, tile) in layout.tiles.iter().enumerate() {
+                    let target = Target {
+                        color: color.clone(),
+                        depth: tiled_depth(tile.view, tiled),
+                        viewport: Some(TiledLayout::viewport(tile, size)),
+                        clear: i == 0, // 第一块清整张纹理，后面的叠加
+                    };
+                    draws.push((tile.view, target));
+                }
+                frames.push((layout.canvas_id, frame));
+            }
+        } else {
+            // 只给可见的 canvas 取 swapchain 纹理，不可见的整段跳过
+            for view in ALL_VIEWS {
+                if !visibility.is_visible(view.canvas_id()) {
+                    continue;
+                }
+                let frame = get_res::<Self>(data)
+                    .surface(view, surface)
+                    .get_current_texture()
+                    .unwrap();
+                let target = Target {
+                    color: frame
+                        .texture
+                        .create_view(&wgpu::TextureViewDescriptor::default()),
+                    depth: own_depth(view, data),
+                    viewport: None,
+                    clear: true,
+                };
+                draws.push((view, target));
+                frames.push((view.canvas_id(), frame));
+            }
         }
-        // vr
-        let vr_scene = get_res::<VRScene>(data);
-        {
-            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &vrscene_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.0,
-                            g: 0.0,
-                            b: 0.0,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                //  Some(wgpu::RenderPassDepthStencilAttachment {
-                //     view: vr_scene.depth_texture_view.as_ref().unwrap(), // 使用深度纹理视图
-                //     depth_ops: Some(wgpu::Operations {
-                //         load: wgpu::LoadOp::Clear(1.0),
-                //         store: wgpu::StoreOp::Store,
-                //     }),
-                //     stencil_ops: None,
-                // }),
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-            let mut rpass = VRScene::pass(data, rpass);
+
+        for (view, target) in draws.iter() {
+            draw(*view, target, data, gfx, &mut encoder);
         }
+
+        let captures: Vec<_> = frames
+            .iter()
+            .filter_map(|(id, frame)| {
+                capture::copy_frame(&gfx.device, &mut encoder, id, &frame.texture)
+            })
+            .collect();
         gfx.queue.submit(Some(encoder.finish()));
-        frame.present();
-        side_frame.present();
-        triangle_list_frame.present();
-        triangle_list_normal_frame.present();
-        shadow_frame.present();
-        vrscene_frame.present();
+        for capture in captures {
+            capture.finish();
+        }
+        for (_, frame) in frames {
+            frame.present();
+        }
     }
 }
@@ -0,0 +1,29 @@
+@group(0) @binding(0)
+var shadow_texture: texture_depth_2d;
+@group(0) @binding(1)
+// var shadow_sampler: sampler_comparison;
+var shadow_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_idx: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let x = f32(vertex_idx == 1u) * 4.0 - 1.0;
+    let y = f32(vertex_idx == 2u) * 4.0 - 1.0;
+    out.position = vec4<f32>(x, y, 0.0, 1.0);
+    out.tex_coords = vec2<f32>(x, -y) * 0.5 + 0.5;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+   let depth = textureSample(shadow_texture, shadow_sampler, in.tex_coords);
+    
+
+// 直接显示深度值，添加一些缩放以便观察
+    let adjusted_depth = 1.0 - depth; // 反转深度值，使近处物体更亮
+    return vec4<f32>(vec3<f32>(adjusted_depth), 1.0);
+}
@@ -1,6 +1,7 @@
 use super::object::Tetrahedron;
 use crate::shared::ready_paint::{get_res, Ready};
 use crate::shared::ready_paint::{return_res, Gfx, HashTypeId2Data, Pass};
+use crate::utils::reflect::{Bindings, ShaderLayout};
 use bytemuck::{Pod, Zeroable};
 use glam::{Mat4, Vec3};
 use wgpu::{util::DeviceExt, PipelineCompilationOptions};
@@ -43,29 +44,26 @@ impl Ready for ShadowScene {
                     contents: bytemuck::cast_slice(&[light_uniform]),
                     usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
                 });
-        let light_camera_bind_group_layout =
-            gfx.device
-                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: Some("Shadow Camera Bind Group Layout"),
-                    entries: &[wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
-                        },
-                        count: None,
-                    }],
-                });
-        let light_camera_bind_group = gfx.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Light Camera Bind Group"),
-            layout: &light_camera_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
+        // 创建深度着色器，布局按 WGSL 声明生成
+        let (depth_shader, depth_reflect) = gfx
+            .pipelines
+            .validated_shader("depth_shader", SHADOW_DEPTH_SHADER, &[])
+            .unwrap();
+        let first_depth_layout = ShaderLayout::new(
+            &gfx.pipelines,
+            "first depth pipeline layout",
+            Bindings::new(&[&depth_reflect]).unwrap(),
+            &[],
+        );
+        let light_camera_bind_group = first_depth_layout.create_bind_group(
+            &gfx.device,
+            "Light Camera Bind Group",
+            0,
+            &[wgpu::BindGroupEntry {
                 binding: 0,
                 resource: light_uniform_buffer.as_entire_binding(),
             }],
-        });
+        );
         let ready_store_depth_texture = gfx.device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Shadow Texture"),
             size: wgpu::Extent3d {
@@ -99,29 +97,12 @@ impl Ready for ShadowScene {
             ..Default::default()
         });
 
-        // 创建第一个管线布局
-        let first_depth_pipeline_layout =
-            gfx.device
-                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: Some("first depth pipeline layout"),
-                    bind_group_layouts: &[&light_camera_bind_group_layout],
-                    push_constant_ranges: &[],
-                });
-
-        // 创建深度着色器
-        let depth_shader = gfx
-            .device
-            .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("depth_shader"),
-                source: wgpu::ShaderSource::Wgsl(SHADOW_DEPTH_SHADER.into()),
-            });
-
         // 创建深度贴图管线
         let first_detpth_texture_pipeline =
-            gfx.device
-                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            gfx.pipelines
+                .render_pipeline(&wgpu::RenderPipelineDescriptor {
                     label: Some("first detpth pipeline"),
-                    layout: Some(&first_depth_pipeline_layout),
+                    layout: Some(&first_depth_layout.pipeline_layout),
                     vertex: wgpu::VertexState {
                         module: &depth_shader,
                         entry_point: Some("vs_main"),
@@ -158,38 +139,24 @@ impl Ready for ShadowScene {
                     multiview: None,
                     cache: None,
                 });
-        let all_bindgroup_layout =
-            gfx.device
-                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: Some("light_bind_group_layout"),
-                    entries: &[
-                        // 阴影贴图纹理
-                        wgpu::BindGroupLayoutEntry {
-                            binding: 0,
-                            visibility: wgpu::ShaderStages::FRAGMENT,
-                            ty: wgpu::BindingType::Texture {
-                                sample_type: wgpu::TextureSampleType::Depth,
-                                view_dimension: wgpu::TextureViewDimension::D2,
-                                multisampled: false,
-                            },
-                            count: None,
-                        },
-                        // 阴影采样器
-                        wgpu::BindGroupLayoutEntry {
-                            binding: 1,
-                            visibility: wgpu::ShaderStages::FRAGMENT,
-                            // ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
-                            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::NonFiltering),
-                            count: None,
-                        },
-                    ],
-                });
+        // 创建第二个着色器
+        let (second_shader, second_reflect) = gfx
+            .pipelines
+            .validated_shader("second depth shader", SECOND_DEPTH_SHADER, &[])
+            .unwrap();
+        let second_depth_layout = ShaderLayout::new(
+            &gfx.pipelines,
+            "second depth pipeline layout",
+            Bindings::new(&[&second_reflect]).unwrap(),
+            &[],
+        );
 
         // 创建光照和阴影的绑定组
-        let all_bind_group = gfx.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("light_uniform_bind_group"),
-            layout: &all_bindgroup_layout,
-            entries: &[
+        let all_bind_group = second_depth_layout.create_bind_group(
+            &gfx.device,
+            "light_uniform_bind_group",
+            0,
+            &[
                 wgpu::BindGroupEntry {
                     binding: 0,
                     resource: wgpu::BindingResource::TextureView(&first_shadow_view),
@@ -199,31 +166,14 @@ impl Ready for ShadowScene {
                     resource: wgpu::BindingResource::Sampler(&shadow_sampler),
                 },
             ],
-        });
-
-        // 创建第二个管线布局
-        let second_depth_pipeline_layout =
-            gfx.device
-                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: Some("second depth pipeline layout"),
-                    bind_group_layouts: &[&all_bindgroup_layout],
-                    push_constant_ranges: &[],
-                });
-
-        // 创建第二个着色器
-        let second_shader = gfx
-            .device
-            .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("second depth shader"),
-                source: wgpu::ShaderSource::Wgsl(SECOND_DEPTH_SHADER.into()),
-            });
+        );
 
         // 创建第二个管线
         let secone_depth_pipeline =
-            gfx.device
-                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            gfx.pipelines
+                .render_pipeline(&wgpu::RenderPipelineDescriptor {
                     label: Some("Second shadow pipeline"),
-                    layout: Some(&second_depth_pipeline_layout),
+                    layout: Some(&second_depth_layout.pipeline_layout),
                     vertex: VertexState {
                         module: &second_shader,
                         entry_point: Some("vs_main"),
@@ -332,54 +282,6 @@ impl<'a> Pass<'a> for ShadowScene {
 }
 
 // 第一个着色器：渲染深度贴图
-const SHADOW_DEPTH_SHADER: &str = r#"
-struct LightUniform {
-    view_proj: mat4x4<f32>,
-}
-@group(0) @binding(0)
-var<uniform> light: LightUniform;
-struct VertexInput {
-    @location(0) position: vec3<f32>,
-}
-struct VertexOutput {
-    @builtin(position) position: vec4<f32>,
-}
-@vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
-    var out: VertexOutput;
-    out.position = light.view_proj * vec4<f32>(in.position, 1.0);
-    return out;
-}
-"#;
-
-const SECOND_DEPTH_SHADER: &str = r#"
-@group(0) @binding(0)
-var shadow_texture: texture_depth_2d;
-@group(0) @binding(1)
-// var shadow_sampler: sampler_comparison;
-var shadow_sampler: sampler;
+const SHADOW_DEPTH_SHADER: &str = include_str!("shadow_depth.wgsl");
 
-struct VertexOutput {
-    @builtin(position) position: vec4<f32>,
-    @location(0) tex_coords: vec2<f32>,
-}
-@vertex
-fn vs_main(@builtin(vertex_index) vertex_idx: u32) -> VertexOutput {
-    var out: VertexOutput;
-    let x = f32(vertex_idx == 1u) * 4.0 - 1.0;
-    let y = f32(vertex_idx == 2u) * 4.0 - 1.0;
-    out.position = vec4<f32>(x, y, 0.0, 1.0);
-    out.tex_coords = vec2<f32>(x, -y) * 0.5 + 0.5;
-    return out;
-}
-
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-   let depth = textureSample(shadow_texture, shadow_sampler, in.tex_coords);
-    
-
-// 直接显示深度值，添加一些缩放以便观察
-    let adjusted_depth = 1.0 - depth; // 反转深度值，使近处物体更亮
-    return vec4<f32>(vec3<f32>(adjusted_depth), 1.0);
-}
-"#;
+const SECOND_DEPTH_SHADER: &str = include_str!("second_depth.wgsl");
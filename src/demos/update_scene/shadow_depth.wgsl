@@ -0,0 +1,17 @@
+struct LightUniform {
+    view_proj: mat4x4<f32>,
+}
+@group(0) @binding(0)
+var<uniform> light: LightUniform;
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+}
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+}
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = light.view_proj * vec4<f32>(in.position, 1.0);
+    return out;
+}
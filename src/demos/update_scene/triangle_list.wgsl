@@ -0,0 +1,44 @@
+#include "uniforms.wgsl"
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) color: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) color: vec3<f32>,
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = uniforms.matrix * vec4<f32>(in.position, 1.0);
+    out.normal = (uniforms.matrix * vec4<f32>(in.normal, 0.0)).xyz;
+    out.color = in.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // 简单的漫反射光照模型
+    let light_dir = normalize(vec3<f32>(0.3, -1.0, 0.7));
+    let normal = normalize(in.normal);
+    
+    // 计算漫反射系数
+    let diffuse = max(dot(normal, light_dir), 0.0);
+    
+    // 环境光
+    let ambient = 0.2;
+    
+    // 最终颜色计算
+    let light = ambient + diffuse * 0.8;
+    let result = in.color * light;
+    
+    return vec4<f32>(result, 1.0);
+}
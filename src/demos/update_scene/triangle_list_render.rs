@@ -1,6 +1,8 @@
 use glam::Mat4;
 use wgpu::util::DeviceExt;
 
+use crate::mesh::normals::{self, Weighting};
+use crate::mesh::Mesh;
 use crate::shared::ready_paint::{get_res, get_res_mut, return_res, Pass, Ready, Update};
 
 use super::object::Tetrahedron;
@@ -163,19 +165,17 @@ impl Ready for NormalTriangleListRender {
             ],
         };
         let shader = gfx
-            .device
-            .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("Triangle List Shader"),
-                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
-            });
+            .pipelines
+            .shader("Triangle List Shader", SHADER, &[])
+            .unwrap();
         let world_pipeline_layout = get_res::<Tetrahedron>(data)
             .world_pipeline_layout
             .as_ref()
             .unwrap();
 
         let pipeline = gfx
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            .pipelines
+            .render_pipeline(&wgpu::RenderPipelineDescriptor {
                 label: Some("Triangle List Pipeline"),
                 layout: Some(world_pipeline_layout),
                 vertex: wgpu::VertexState {
@@ -239,11 +239,13 @@ fn generate_colored_vertices(
     indices: &[u16],
     white: bool,
 ) -> (Vec<ColoredVertex>, Vec<u16>) {
-    // 计算每个面的法线
-    let face_normals = calculate_face_normals(vertices, indices);
-
-    // 为每个顶点计算平均法线
-    let vertex_normals = calculate_vertex_normals(vertices, indices, &face_normals);
+    // 按角度加权的平滑法线，四个顶点位置都不同，不会拆顶点
+    let mut mesh = Mesh {
+        positions: vertices.to_vec(),
+        indices: indices.iter().map(|&i| i as u32).collect(),
+        ..Default::default()
+    };
+    normals::smooth(&mut mesh, Weighting::Angle);
 
     // 生成颜色 (基于位置生成有趣的颜色)
     let colors = if white {
@@ -257,7 +259,7 @@ fn generate_colored_vertices(
     for i in 0..vertices.len() {
         colored_vertices.push(ColoredVertex {
             position: vertices[i],
-            normal: vertex_normals[i],
+            normal: mesh.normals[i],
             color: colors[i],
         });
     }
@@ -266,89 +268,6 @@ fn generate_colored_vertices(
     (colored_vertices, indices.to_vec())
 }
 
-// 计算面法线
-fn calculate_face_normals(vertices: &[[f32; 3]; 4], indices: &[u16]) -> Vec<[f32; 3]> {
-    let mut face_normals = Vec::new();
-
-    // 每3个索引组成一个面
-    for i in (0..indices.len()).step_by(3) {
-        let i0 = indices[i] as usize;
-        let i1 = indices[i + 1] as usize;
-        let i2 = indices[i + 2] as usize;
-
-        let v0 = vertices[i0];
-        let v1 = vertices[i1];
-        let v2 = vertices[i2];
-
-        // 计算边向量
-        let edge1 = [v1[0] - v0[0], v1[1] - v0[1], v1[2] - v0[2]];
-
-        let edge2 = [v2[0] - v0[0], v2[1] - v0[1], v2[2] - v0[2]];
-
-        // 叉乘计算法线
-        let normal = [
-            edge1[1] * edge2[2] - edge1[2] * edge2[1],
-            edge1[2] * edge2[0] - edge1[0] * edge2[2],
-            edge1[0] * edge2[1] - edge1[1] * edge2[0],
-        ];
-
-        // 归一化
-        let length = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
-        let normalized = [normal[0] / length, normal[1] / length, normal[2] / length];
-
-        face_normals.push(normalized);
-    }
-
-    face_normals
-}
-
-// 计算顶点法线 (将面法线平均)
-fn calculate_vertex_normals(
-    vertices: &[[f32; 3]; 4],
-    indices: &[u16],
-    face_normals: &[[f32; 3]],
-) -> Vec<[f32; 3]> {
-    let mut vertex_normals = vec![[0.0, 0.0, 0.0]; vertices.len()];
-    let mut counts = vec![0; vertices.len()];
-
-    // 将面法线添加到各个顶点
-    for i in (0..indices.len()).step_by(3) {
-        let face_index = i / 3;
-        let face_normal = face_normals[face_index];
-
-        // 将面法线添加到构成该面的所有顶点
-        for j in 0..3 {
-            let vertex_index = indices[i + j] as usize;
-            vertex_normals[vertex_index][0] += face_normal[0];
-            vertex_normals[vertex_index][1] += face_normal[1];
-            vertex_normals[vertex_index][2] += face_normal[2];
-            counts[vertex_index] += 1;
-        }
-    }
-
-    // 归一化顶点法线
-    for i in 0..vertex_normals.len() {
-        if counts[i] > 0 {
-            vertex_normals[i][0] /= counts[i] as f32;
-            vertex_normals[i][1] /= counts[i] as f32;
-            vertex_normals[i][2] /= counts[i] as f32;
-
-            // 再次归一化
-            let normal = vertex_normals[i];
-            let length =
-                (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
-
-            if length > 0.0 {
-                vertex_normals[i][0] /= length;
-                vertex_normals[i][1] /= length;
-                vertex_normals[i][2] /= length;
-            }
-        }
-    }
-
-    vertex_normals
-}
-
 fn gen_white_color(vertices: &[[f32; 3]; 4]) -> Vec<[f32; 3]> {
     vec![[1.0, 1.0, 1.0]; vertices.len()]
 }
@@ -367,54 +286,4 @@ pub fn generate_colors(vertices: &[[f32; 3]; 4]) -> Vec<[f32; 3]> {
         .collect()
 }
 
-const SHADER: &str = r#"
-struct Uniforms {
-    matrix: mat4x4<f32>,
-    resolution: vec2<f32>,
-    delta_time: f32,
-    _padding: f32,
-}
-
-@group(0) @binding(0)
-var<uniform> uniforms: Uniforms;
-
-struct VertexInput {
-    @location(0) position: vec3<f32>,
-    @location(1) normal: vec3<f32>,
-    @location(2) color: vec3<f32>,
-}
-
-struct VertexOutput {
-    @builtin(position) clip_position: vec4<f32>,
-    @location(0) normal: vec3<f32>,
-    @location(1) color: vec3<f32>,
-}
-
-@vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
-    var out: VertexOutput;
-    out.clip_position = uniforms.matrix * vec4<f32>(in.position, 1.0);
-    out.normal = (uniforms.matrix * vec4<f32>(in.normal, 0.0)).xyz;
-    out.color = in.color;
-    return out;
-}
-
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    // 简单的漫反射光照模型
-    let light_dir = normalize(vec3<f32>(0.3, -1.0, 0.7));
-    let normal = normalize(in.normal);
-    
-    // 计算漫反射系数
-    let diffuse = max(dot(normal, light_dir), 0.0);
-    
-    // 环境光
-    let ambient = 0.2;
-    
-    // 最终颜色计算
-    let light = ambient + diffuse * 0.8;
-    let result = in.color * light;
-    
-    return vec4<f32>(result, 1.0);
-}
-"#;
+const SHADER: &str = include_str!("triangle_list.wgsl");
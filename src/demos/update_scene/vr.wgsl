@@ -0,0 +1,36 @@
+#include "uniforms.wgsl"
+
+struct ViewMatrices {
+    left_eye: mat4x4<f32>,
+    right_eye: mat4x4<f32>,
+}
+
+struct VertexInput {
+    @location(0) pos: vec3<f32>,
+}
+
+@group(0) @binding(1) var<uniform> view_matrices: ViewMatrices;
+
+@vertex
+fn vs_main(
+    in: VertexInput,
+    @builtin(instance_index) instance_index: u32
+) -> @builtin(position) vec4<f32> {
+    var position: vec4<f32>;
+    if (instance_index == 0u) {
+        // 左眼
+        position = view_matrices.left_eye * vec4<f32>(in.pos, 1.0);
+        position.x = position.x * 0.5 - 0.5 * position.w; // 移至左半屏
+    } else {
+        // 右眼
+        position = view_matrices.right_eye * vec4<f32>(in.pos, 1.0);
+        position.x = position.x * 0.5 + 0.5 * position.w; // 移至右半屏
+    }
+    return position;
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    // return vec4<f32>(in.color, 1.0);
+    return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+}
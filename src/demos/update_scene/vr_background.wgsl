@@ -0,0 +1,53 @@
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_idx: u32) -> @builtin(position) vec4<f32> {
+    // 创建全屏三角形
+    var pos = vec2<f32>(0.0, 0.0);
+    if (vertex_idx == 0u) {
+        pos = vec2<f32>(-1.0, -1.0);
+    } else if (vertex_idx == 1u) {
+        pos = vec2<f32>(3.0, -1.0);
+    } else {
+        pos = vec2<f32>(-1.0, 3.0);
+    }
+    return vec4<f32>(pos, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+     // 获取屏幕尺寸 (假设为600x300)
+    let screen_width = 600.0;
+    let screen_height = 300.0;
+    
+    // 计算视口中心
+    let left_center = vec2<f32>(screen_width * 0.25, screen_height * 0.5);
+    let right_center = vec2<f32>(screen_width * 0.75, screen_height * 0.5);
+    
+    // 计算当前像素到视口中心的距离
+    let pixel_pos = vec2<f32>(pos.x, pos.y);
+    let radius = min(screen_width * 0.25, screen_height * 0.5) * 0.9; // 视口半径稍小一些
+    
+    // 确定当前像素属于哪个眼睛
+    let is_left_eye = pos.x < screen_width * 0.5;
+    let center = select(right_center, left_center, is_left_eye);
+    
+    // 计算到中心的距离
+    let dist = distance(pixel_pos, center);
+    
+    // 确定是否在视口圆圈内
+    if (dist <= radius) {
+        // 在视口内 - 绘制场景
+        // 这里可以添加不同颜色的背景
+        let bg_color = select(
+            vec3<f32>(0.1, 0.2, 0.1), // 右眼背景色 - 暗绿
+            vec3<f32>(0.1, 0.1, 0.2), // 左眼背景色 - 暗蓝
+            is_left_eye
+        );
+        
+        // 如果这里是四面体线条，则绘制线条，否则绘制背景
+        // 简化处理：始终返回背景色，四面体线条会在此之上绘制
+        return vec4<f32>(bg_color, 1.0);
+    } else {
+        // 在视口外 - 绘制黑色
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+}
@@ -1,13 +1,29 @@
 use std::{f32::consts, sync::Arc};
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
-    BindGroupLayoutEntry, ShaderStages, Surface,
+    ShaderStages, Surface,
 };
 
-use crate::shared::ready_paint::{
-    get_res, get_res_mut, return_res, Gfx, HashTypeId2Data, Pass, Ready, Update,
+use crate::{
+    shaders::Uniforms,
+    shared::{
+        camera::CameraController,
+        ready_paint::{
+            get_res, get_res_mut, return_res, Gfx, HashTypeId2Data, Pass, Ready, Update,
+        },
+    },
+    utils::{layout::write_field, reflect::ShaderLayout},
+    web::input::Input,
 };
 
+use super::MAIN_CANVAS;
+
+// object、triangle_list 和 vr 都在 group 0 按这个声明使用 uniforms
+const UNIFORMS_DECLARATION: &str = "#include \"uniforms.wgsl\"
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;";
+
+const CAMERA_EYE: glam::Vec3 = glam::Vec3::new(1.5, -5.0, 3.0);
+
 #[derive(Default)]
 pub struct World {
     pub uniform_buffer: Option<wgpu::Buffer>,
@@ -17,15 +33,17 @@ pub struct World {
     side_view_uniforms: Option<Uniforms>, // 侧视角uniform数据
     side_view_uniform_buffer: Option<wgpu::Buffer>, // 侧视角uniform buffer
     side_view_uniforms_bind_group: Option<wgpu::BindGroup>,
+    camera: Option<CameraController>, // 主视角相机，由主视角画布上的输入驱动
+    projection: glam::Mat4,
 }
 
 impl Ready for World {
     fn ready(&mut self, data: &mut HashTypeId2Data, gfx: &Gfx) {
-        println!("world ready");
+        log::debug!("world ready");
         let world = generate_matrix(300. / 300.);
         let uniforms = Uniforms {
             resolution: [300., 300.],
-            matrix: world.to_cols_array(),
+            matrix: world.to_cols_array_2d(),
             delta_time: 0.,
             _padding: 0.,
         };
@@ -33,7 +51,7 @@ impl Ready for World {
         let side_view_matrix = generate_orthographic_matrix(300. / 300.);
         let side_view_uniforms = Uniforms {
             resolution: [300., 300.],
-            matrix: side_view_matrix.to_cols_array(),
+            matrix: side_view_matrix.to_cols_array_2d(),
             delta_time: 0.,
             _padding: 0.,
         };
@@ -52,42 +70,32 @@ impl Ready for World {
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
-        let uniforms_bind_group_layout =
-            gfx.device
-                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: Some("uniforms_bind_group_layout"),
-                    entries: &[BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: ShaderStages::VERTEX,
-                        ty: wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: wgpu::BufferSize::new(
-                                std::mem::size_of::<Uniforms>() as u64,
-                            ),
-                        },
-                        count: None,
-                    }],
-                });
-
-        let uniforms_bind_group = gfx.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("uniforms_bind_group"),
-            layout: &uniforms_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
+        let layout = ShaderLayout::shared_group(
+            &gfx.pipelines,
+            "uniforms_bind_group_layout",
+            UNIFORMS_DECLARATION,
+            ShaderStages::VERTEX,
+        );
+        let uniforms_bind_group = layout.create_bind_group(
+            &gfx.device,
+            "uniforms_bind_group",
+            0,
+            &[wgpu::BindGroupEntry {
                 binding: 0,
                 resource: uniform_buffer.as_entire_binding(),
             }],
-        });
+        );
         // 创建侧视角绑定组
-        let side_view_uniforms_bind_group =
-            gfx.device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("side_view_uniforms_bind_group"),
-                layout: &uniforms_bind_group_layout,
-                entries: &[wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: side_view_uniform_buffer.as_entire_binding(),
-                }],
-            });
+        let side_view_uniforms_bind_group = layout.create_bind_group(
+            &gfx.device,
+            "side_view_uniforms_bind_group",
+            0,
+            &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: side_view_uniform_buffer.as_entire_binding(),
+            }],
+        );
+        let uniforms_bind_group_layout = layout.bind_group_layouts[0].clone();
         return_res(
             data,
             World {
@@ -98,6 +106,11 @@ impl Ready for World {
                 side_view_uniforms: Some(side_view_uniforms),
                 side_view_uniform_buffer: Some(side_view_uniform_buffer),
                 side_view_uniforms_bind_group: Some(side_view_uniforms_bind_group),
+                camera: Some(
+                    CameraController::new(MAIN_CANVAS, CAMERA_EYE, glam::Vec3::ZERO, glam::Vec3::Z)
+                        .with_distance_range(2., 8.), // 远平面是 10
+                ),
+                projection: projection(1.), // 300 x 300
             },
         );
     }
@@ -105,34 +118,39 @@ impl Ready for World {
 
 impl Update for World {
     fn update(data: &mut HashTypeId2Data, gfx: &Gfx, dt: f32, surface: &Arc<Surface<'static>>) {
+        let input = get_res::<Input>(data).clone();
         let world = get_res_mut::<Self>(data);
-        const DELTA_TIME_OFFSET: wgpu::BufferAddress = 72;
+        // dt 是毫秒
+        if world.camera.as_mut().unwrap().update(&input, dt / 1000.) {
+            let matrix =
+                (world.projection * world.camera.as_ref().unwrap().view()).to_cols_array_2d();
+            world.uniforms.as_mut().unwrap().matrix = matrix;
+            write_field!(
+                &gfx.queue,
+                world.uniform_buffer.as_ref().unwrap(),
+                Uniforms,
+                matrix,
+                matrix
+            );
+        }
         world.uniforms.as_mut().unwrap().delta_time = dt;
-        gfx.queue.write_buffer(
-            &world.uniform_buffer.as_ref().unwrap(),
-            DELTA_TIME_OFFSET,
-            bytemuck::cast_slice(&[dt]),
+        write_field!(
+            &gfx.queue,
+            world.uniform_buffer.as_ref().unwrap(),
+            Uniforms,
+            delta_time,
+            dt
         );
     }
 }
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-struct Uniforms {
-    matrix: [f32; 16],
-    resolution: [f32; 2],
-    delta_time: f32,
-    _padding: f32,
+fn projection(aspect_ratio: f32) -> glam::Mat4 {
+    glam::Mat4::perspective_rh(consts::FRAC_PI_4, aspect_ratio, 1.0, 10.0)
 }
 
 pub fn generate_matrix(aspect_ratio: f32) -> glam::Mat4 {
-    let projection = glam::Mat4::perspective_rh(consts::FRAC_PI_4, aspect_ratio, 1.0, 10.0);
-    let view = glam::Mat4::look_at_rh(
-        glam::Vec3::new(1.5f32, -5.0, 3.0),
-        glam::Vec3::ZERO,
-        glam::Vec3::Z,
-    );
-    projection * view
+    let view = glam::Mat4::look_at_rh(CAMERA_EYE, glam::Vec3::ZERO, glam::Vec3::Z);
+    projection(aspect_ratio) * view
 }
 
 // create a side camera view orhtographic matrix
@@ -162,12 +180,13 @@ impl<'a> Pass<'a> for World {
 impl World {
     pub fn update_side_view_uniforms(data: &mut HashTypeId2Data, dt: f32, gfx: &Gfx) {
         let world = get_res_mut::<Self>(data);
-        const DELTA_TIME_OFFSET: wgpu::BufferAddress = 72;
         world.side_view_uniforms.as_mut().unwrap().delta_time = dt;
-        gfx.queue.write_buffer(
-            &world.side_view_uniform_buffer.as_ref().unwrap(),
-            DELTA_TIME_OFFSET,
-            bytemuck::cast_slice(&[dt]),
+        write_field!(
+            &gfx.queue,
+            world.side_view_uniform_buffer.as_ref().unwrap(),
+            Uniforms,
+            delta_time,
+            dt
         );
     }
     pub fn pass_for_side_view<'a>(
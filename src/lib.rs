@@ -1,27 +1,56 @@
-use std::{
-    cell::RefCell,
-    collections::HashMap,
-    rc::Rc,
-    sync::{Arc, Mutex},
-};
+// 本地（非 wasm）只编译和浏览器无关的部分，用来跑单元测试
+#![cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
 
-use demos::{level2::NextLevelPage, one_shot_scene, test};
-use shared::Shared;
-use web_sys::{window, HtmlCanvasElement};
+#[cfg(target_arch = "wasm32")]
+use std::sync::Arc;
 
+#[cfg(target_arch = "wasm32")]
+use demos::{level2::NextLevelPage, playground::PlaygroundPage};
+#[cfg(target_arch = "wasm32")]
+use web::router::Router;
+#[cfg(target_arch = "wasm32")]
+use web_sys::HtmlCanvasElement;
+
+#[cfg(target_arch = "wasm32")]
 mod demos;
+mod mesh;
+mod shaders;
+#[cfg(target_arch = "wasm32")]
 mod shared;
+#[cfg(not(target_arch = "wasm32"))]
+mod shared {
+    pub mod camera;
+}
+#[cfg(target_arch = "wasm32")]
 mod utils;
+#[cfg(target_arch = "wasm32")]
 mod web;
-use wasm_bindgen::prelude::{wasm_bindgen, Closure};
+#[cfg(not(target_arch = "wasm32"))]
+mod web {
+    pub mod input;
+}
+#[cfg(not(target_arch = "wasm32"))]
+mod utils {
+    pub mod layout;
+    pub mod logging;
+    pub mod pipeline_cache;
+    pub mod preprocess;
+    pub mod reflect;
+    pub mod shader_debug;
+}
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::wasm_bindgen;
+#[cfg(target_arch = "wasm32")]
 use wgpu::{DeviceDescriptor, Limits};
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 pub async fn start(canvases: Vec<HtmlCanvasElement>) {
     console_error_panic_hook::set_once();
-    console_log!("start");
+    utils::logging::init();
+    log::info!("start");
     wasm_bindgen_futures::spawn_local(async move {
-        console_log!("start async");
+        log::debug!("start async");
         let (canvas, adapter) = web::bridge_canvas::connect_canvas_arr(canvases).await;
 
         let (device, queue) = adapter
@@ -36,56 +65,14 @@ pub async fn start(canvases: Vec<HtmlCanvasElement>) {
             )
             .await
             .unwrap();
-        let shared = shared::Shared::new(device, queue, adapter, canvas);
-        let shared = Arc::new(shared);
-        let mut app = App {
-            share: shared.clone(),
-            demo: demos::Demo::new(),
-            active_pages: HashMap::new(),
-        };
-        let page_1_rc_active = Rc::new(RefCell::new(true));
-        app.set_page("page-1", page_1_rc_active.clone());
-        let app = Rc::new(RefCell::new(app));
-        APP_INSTANCE.with(|app_instance| {
-            *app_instance.borrow_mut() = Some(app.clone());
-        });
-        app.clone()
-            .borrow_mut()
-            .demo
-            .run_all(shared.clone(), page_1_rc_active.clone());
+        web::gpu_errors::install(&device);
+        let shared = Arc::new(shared::Shared::new(device, queue, adapter, canvas));
+        let router = Router::new(shared)
+            .add_page("level-1", demos::Demo::new())
+            .add_page("level-2", NextLevelPage {})
+            .add_page("playground", PlaygroundPage {});
+        web::router::install(router).unwrap();
     });
 
     web::interactions::init_interactions().unwrap();
 }
-
-struct App {
-    share: Arc<Shared>,
-    pub demo: demos::Demo,
-    active_pages: HashMap<String, Rc<RefCell<bool>>>,
-}
-
-impl App {
-    pub fn next_level(&mut self) {
-        console_log!("next_level");
-        *self.active_pages.get_mut("page-1").unwrap().borrow_mut() = false;
-        let page_2_active = Rc::new(RefCell::new(true));
-        self.set_page("page-2", page_2_active.clone());
-        NextLevelPage::run(self.share.clone(), page_2_active.clone());
-    }
-    pub fn set_page(&mut self, name: &str, active: Rc<RefCell<bool>>) {
-        console_log!("set_page: {:?} , {:?}", name, active);
-        self.active_pages.insert(name.to_string(), active);
-        console_log!("active_pages: {:?}", self.active_pages);
-    }
-    pub fn return_level1(&mut self) {
-        console_log!("return_level1");
-        *self.active_pages.get_mut("page-2").unwrap().borrow_mut() = false;
-        let page_1_active = Rc::new(RefCell::new(true));
-        self.set_page("page-1", page_1_active.clone());
-        self.demo.run_all(self.share.clone(), page_1_active.clone());
-    }
-}
-
-thread_local! {
-    static APP_INSTANCE : RefCell<Option<Rc<RefCell<App>>>> = RefCell::new(None);
-}
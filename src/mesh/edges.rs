@@ -0,0 +1,117 @@
+/// 从三角形索引提取线框（LineList 索引），每条边只出现一次
+/// 可以给一个特征角：只留两侧面法线夹角超过它的边（硬边）和边界边，共面三角形之间的对角线就不画了
+/// 边按位置判断，uv 接缝处复制出来的顶点不会多出重复的边，也不会被当成边界
+use std::collections::HashMap;
+
+use glam::Vec3;
+
+/// 线段按边第一次出现的顺序排列，用的是第一次出现时的两个顶点下标
+pub fn line_list(positions: &[[f32; 3]], indices: &[u32], feature_angle: Option<f32>) -> Vec<u32> {
+    // +0.0 把 -0.0 变成 0.0，两者位置相同但位不同
+    let point = |i: u32| positions[i as usize].map(|x| (x + 0.).to_bits());
+    let mut lookup = HashMap::new();
+    // (两个顶点, 两侧三角形的法线)
+    let mut edges: Vec<([u32; 2], Vec<Vec3>)> = Vec::new();
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|k| Vec3::from(positions[triangle[k] as usize]));
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        for k in 0..3 {
+            let (u, v) = (triangle[k], triangle[(k + 1) % 3]);
+            let (pu, pv) = (point(u), point(v));
+            if pu == pv {
+                continue;
+            }
+            let next = edges.len();
+            let edge = *lookup.entry((pu.min(pv), pu.max(pv))).or_insert(next);
+            if edge == next {
+                edges.push(([u, v], Vec::new()));
+            }
+            edges[edge].1.push(normal);
+        }
+    }
+    let cos = feature_angle.map(f32::cos);
+    edges
+        .into_iter()
+        .filter(|(_, normals)| match (cos, normals.as_slice()) {
+            (Some(cos), [a, b]) => a.dot(*b) < cos,
+            // 边界和非流形的边总是画
+            _ => true,
+        })
+        .flat_map(|(vertices, _)| vertices)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::mesh::primitives;
+
+    fn edge_count(lines: &[u32]) -> usize {
+        assert_eq!(lines.len() % 2, 0);
+        lines.len() / 2
+    }
+
+    #[test]
+    fn tetrahedron_has_six_edges() {
+        let positions = [
+            [0.0, 1., 0.0],
+            [-1., -1., -1.],
+            [1., -1., -1.],
+            [0.0, -1., 1.],
+        ];
+        let lines = line_list(&positions, &[0, 1, 2, 0, 2, 3, 0, 3, 1, 1, 3, 2], None);
+        let pairs: HashSet<_> = lines
+            .chunks_exact(2)
+            .map(|l| (l[0].min(l[1]), l[0].max(l[1])))
+            .collect();
+        assert_eq!(edge_count(&lines), 6);
+        assert_eq!(pairs.len(), 6);
+        // 尖锐的四面体，特征角再小所有边也都在
+        assert_eq!(
+            edge_count(&line_list(
+                &positions,
+                &[0, 1, 2, 0, 2, 3, 0, 3, 1, 1, 3, 2],
+                Some(0.1)
+            )),
+            6
+        );
+    }
+
+    #[test]
+    fn cube_feature_edges_skip_diagonals() {
+        // 每个面 4 个独立的顶点，按位置合并后是 12 条棱加 6 条对角线
+        let cube = primitives::cube(1.);
+        assert_eq!(
+            edge_count(&line_list(&cube.positions, &cube.indices, None)),
+            18
+        );
+        let feature = line_list(&cube.positions, &cube.indices, Some(10f32.to_radians()));
+        assert_eq!(edge_count(&feature), 12);
+        for line in feature.chunks_exact(2) {
+            let [a, b] = [line[0], line[1]].map(|i| Vec3::from(cube.positions[i as usize]));
+            // 棱只沿一个轴
+            assert_eq!((a - b).abs().max_element(), 1.);
+            assert_eq!((a - b).abs().min_element(), 0.);
+        }
+    }
+
+    #[test]
+    fn open_and_seamed_surfaces() {
+        // 平面上只剩边界
+        let plane = primitives::plane([1., 1.], [2, 2]);
+        assert_eq!(
+            edge_count(&line_list(&plane.positions, &plane.indices, None)),
+            16
+        );
+        assert_eq!(
+            edge_count(&line_list(&plane.positions, &plane.indices, Some(0.1))),
+            8
+        );
+        // 圆环两个方向都有 uv 接缝，按位置合并后 V - E + F = 0
+        let torus = primitives::torus(1., 0.3, 8, 4);
+        let lines = line_list(&torus.positions, &torus.indices, None);
+        assert_eq!(edge_count(&lines), 8 * 4 + torus.triangle_count());
+    }
+}
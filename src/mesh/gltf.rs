@@ -0,0 +1,554 @@
+/// glTF 2.0 导入：默认场景的节点层级、网格（每个 primitive 一个 submesh）、
+/// 金属度/粗糙度材质、内嵌和外部贴图、相机和 KHR_lights_punctual 光源
+/// `.gltf` 和 `.glb` 都支持；外部的 .bin 和图片由调用方按 uri 提供（`load`）或按 URL 取（`load_url`）
+use std::collections::HashMap;
+
+use base64::Engine;
+use glam::Mat4;
+use image::RgbaImage;
+
+use super::{smooth_normals, Material, Mesh, SubMesh};
+
+#[derive(Debug, Default)]
+pub struct GltfScene {
+    pub meshes: Vec<Mesh>,
+    pub nodes: Vec<Node>, // 按深度优先排好，父节点总在子节点前面
+    pub cameras: Vec<Camera>,
+    pub lights: Vec<Light>,
+    pub images: Vec<(String, RgbaImage)>, // 材质里贴图名对应的图片
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub name: String,
+    pub parent: Option<usize>, // `GltfScene::nodes` 的下标
+    pub local: Mat4,
+    pub world: Mat4,
+    pub mesh: Option<usize>,
+    pub camera: Option<usize>,
+    pub light: Option<usize>,
+}
+
+/// 相机沿自身 -z 看，位置和朝向取所在节点的 world
+#[derive(Debug, Clone, PartialEq)]
+pub struct Camera {
+    pub name: String,
+    pub projection: Projection,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Projection {
+    Perspective {
+        yfov: f32,
+        aspect_ratio: Option<f32>,
+        znear: f32,
+        zfar: Option<f32>, // None 为无限远
+    },
+    Orthographic {
+        xmag: f32,
+        ymag: f32,
+        znear: f32,
+        zfar: f32,
+    },
+}
+
+/// 光沿所在节点的 -z 方向照射
+#[derive(Debug, Clone, PartialEq)]
+pub struct Light {
+    pub name: String,
+    pub kind: LightKind,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub range: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightKind {
+    Directional,
+    Point,
+    Spot {
+        inner_cone_angle: f32,
+        outer_cone_angle: f32,
+    },
+}
+
+impl GltfScene {
+    pub fn image(&self, name: &str) -> Option<&RgbaImage> {
+        self.images.iter().find(|(n, _)| n == name).map(|(_, i)| i)
+    }
+
+    /// 带网格的节点和它的世界矩阵
+    pub fn mesh_instances(&self) -> impl Iterator<Item = (&Mesh, Mat4)> {
+        self.nodes
+            .iter()
+            .filter_map(|node| node.mesh.map(|m| (&self.meshes[m], node.world)))
+    }
+
+    /// 带相机的节点和它的世界矩阵
+    pub fn camera_instances(&self) -> impl Iterator<Item = (&Camera, Mat4)> {
+        self.nodes
+            .iter()
+            .filter_map(|node| node.camera.map(|c| (&self.cameras[c], node.world)))
+    }
+
+    /// 带光源的节点和它的世界矩阵
+    pub fn light_instances(&self) -> impl Iterator<Item = (&Light, Mat4)> {
+        self.nodes
+            .iter()
+            .filter_map(|node| node.light.map(|l| (&self.lights[l], node.world)))
+    }
+
+    /// 把图片上传成贴图，名字和 `images` 一致。按原值存储（不是 sRGB 格式），和材质颜色一样直接参与计算
+    pub fn upload_images(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Vec<(String, wgpu::TextureView)> {
+        use wgpu::util::DeviceExt;
+
+        self.images
+            .iter()
+            .map(|(name, image)| {
+                let texture = device.create_texture_with_data(
+                    queue,
+                    &wgpu::TextureDescriptor {
+                        label: Some(name),
+                        size: wgpu::Extent3d {
+                            width: image.width(),
+                            height: image.height(),
+                            depth_or_array_layers: 1,
+                        },
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: wgpu::TextureDimension::D2,
+                        format: wgpu::TextureFormat::Rgba8Unorm,
+                        usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                        view_formats: &[],
+                    },
+                    wgpu::util::TextureDataOrder::LayerMajor,
+                    image.as_raw(),
+                );
+                (name.clone(), texture.create_view(&Default::default()))
+            })
+            .collect()
+    }
+}
+
+impl Camera {
+    /// 投影矩阵（右手系，深度 0..1），文件里没给宽高比时用 `aspect_ratio`
+    pub fn projection_matrix(&self, aspect_ratio: f32) -> Mat4 {
+        match self.projection {
+            Projection::Perspective {
+                yfov,
+                aspect_ratio: aspect,
+                znear,
+                zfar: Some(zfar),
+            } => Mat4::perspective_rh(yfov, aspect.unwrap_or(aspect_ratio), znear, zfar),
+            Projection::Perspective {
+                yfov,
+                aspect_ratio: aspect,
+                znear,
+                zfar: None,
+            } => Mat4::perspective_infinite_rh(yfov, aspect.unwrap_or(aspect_ratio), znear),
+            Projection::Orthographic {
+                xmag,
+                ymag,
+                znear,
+                zfar,
+            } => Mat4::orthographic_rh(-xmag, xmag, -ymag, ymag, znear, zfar),
+        }
+    }
+}
+
+/// 从内存加载，`resources` 是外部文件的 uri 和内容（data: uri 不用给）
+pub fn load(bytes: &[u8], resources: &[(&str, &[u8])]) -> Result<GltfScene, String> {
+    let gltf = gltf::Gltf::from_slice(bytes).map_err(|e| format!("gltf: {}", e))?;
+    import(gltf, resources)
+}
+
+/// 按 URL 加载，外部文件相对 .gltf 所在目录去取
+#[cfg(target_arch = "wasm32")]
+pub async fn load_url(url: &str) -> Result<GltfScene, String> {
+    use crate::web::fetch::fetch_bytes;
+
+    let bytes = fetch_bytes(url).await?;
+    let gltf = gltf::Gltf::from_slice(&bytes).map_err(|e| format!("gltf {}: {}", url, e))?;
+    let base = url.rfind('/').map_or("", |i| &url[..=i]);
+    let mut fetched = Vec::new();
+    for uri in external_uris(&gltf) {
+        let data = fetch_bytes(&format!("{}{}", base, uri)).await?;
+        fetched.push((uri, data));
+    }
+    let resources: Vec<_> = fetched
+        .iter()
+        .map(|(uri, data)| (uri.as_str(), data.as_slice()))
+        .collect();
+    import(gltf, &resources)
+}
+
+// 需要另外去取的 buffer 和图片
+#[cfg(target_arch = "wasm32")]
+fn external_uris(gltf: &gltf::Gltf) -> Vec<String> {
+    let buffers = gltf.buffers().filter_map(|b| match b.source() {
+        gltf::buffer::Source::Uri(uri) => Some(uri),
+        gltf::buffer::Source::Bin => None,
+    });
+    let images = gltf.images().filter_map(|i| match i.source() {
+        gltf::image::Source::Uri { uri, .. } => Some(uri),
+        gltf::image::Source::View { .. } => None,
+    });
+    buffers
+        .chain(images)
+        .filter(|uri| !uri.starts_with("data:"))
+        .map(str::to_string)
+        .collect()
+}
+
+fn import(gltf: gltf::Gltf, resources: &[(&str, &[u8])]) -> Result<GltfScene, String> {
+    let resolve = |uri: &str| -> Result<Vec<u8>, String> {
+        if let Some(data) = uri.strip_prefix("data:") {
+            let (_, encoded) = data
+                .split_once(";base64,")
+                .ok_or_else(|| format!("gltf: unsupported data uri {:.32}", uri))?;
+            return base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map_err(|e| format!("gltf: bad data uri: {}", e));
+        }
+        resources
+            .iter()
+            .find(|(name, _)| *name == uri)
+            .map(|(_, data)| data.to_vec())
+            .ok_or_else(|| format!("gltf: missing resource {}", uri))
+    };
+
+    let buffers = gltf
+        .buffers()
+        .map(|buffer| match buffer.source() {
+            gltf::buffer::Source::Bin => gltf
+                .blob
+                .clone()
+                .ok_or_else(|| "gltf: missing glb BIN chunk".to_string()),
+            gltf::buffer::Source::Uri(uri) => resolve(uri),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let images = gltf
+        .images()
+        .map(|image| {
+            let encoded = match image.source() {
+                gltf::image::Source::View { view, .. } => {
+                    let buffer = &buffers[view.buffer().index()];
+                    buffer
+                        .get(view.offset()..view.offset() + view.length())
+                        .ok_or_else(|| {
+                            format!(
+                                "gltf: image {} is outside buffer {} ({} bytes)",
+                                image.index(),
+                                view.buffer().index(),
+                                buffer.len()
+                            )
+                        })?
+                        .to_vec()
+                }
+                gltf::image::Source::Uri { uri, .. } => resolve(uri)?,
+            };
+            let decoded = image::load_from_memory(&encoded)
+                .map_err(|e| format!("gltf: image {}: {}", image.index(), e))?;
+            Ok((image_name(&image), decoded.to_rgba8()))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let materials: Vec<Material> = gltf.materials().map(material).collect();
+    let meshes = gltf
+        .meshes()
+        .map(|m| mesh(&m, &buffers, &materials))
+        .collect();
+    let cameras = gltf.cameras().map(camera).collect();
+    let lights = gltf
+        .lights()
+        .map(|lights| lights.map(light).collect())
+        .unwrap_or_default();
+
+    let mut nodes = Vec::new();
+    let scene = gltf
+        .default_scene()
+        .or_else(|| gltf.scenes().next())
+        .ok_or("gltf: no scene")?;
+    for root in scene.nodes() {
+        visit(&root, None, Mat4::IDENTITY, &mut nodes);
+    }
+
+    Ok(GltfScene {
+        meshes,
+        nodes,
+        cameras,
+        lights,
+        images,
+    })
+}
+
+// 外部图片用 uri 当名字，内嵌的用 "#下标"
+fn image_name(image: &gltf::Image) -> String {
+    match image.source() {
+        gltf::image::Source::Uri { uri, .. } if !uri.starts_with("data:") => uri.to_string(),
+        _ => format!("#{}", image.index()),
+    }
+}
+
+fn texture_name(texture: gltf::Texture) -> String {
+    image_name(&texture.source())
+}
+
+fn material(m: gltf::Material) -> Material {
+    let pbr = m.pbr_metallic_roughness();
+    let [r, g, b, a] = pbr.base_color_factor();
+    Material {
+        name: m.name().unwrap_or_default().to_string(),
+        diffuse: [r, g, b],
+        opacity: a,
+        metallic: pbr.metallic_factor(),
+        roughness: pbr.roughness_factor(),
+        emissive: m.emissive_factor(),
+        diffuse_texture: pbr.base_color_texture().map(|t| texture_name(t.texture())),
+        metallic_roughness_texture: pbr
+            .metallic_roughness_texture()
+            .map(|t| texture_name(t.texture())),
+        normal_texture: m.normal_texture().map(|t| texture_name(t.texture())),
+        ..Default::default()
+    }
+}
+
+// 只导入三角形 primitive；mesh.materials 只放用到的材质
+fn mesh(m: &gltf::Mesh, buffers: &[Vec<u8>], materials: &[Material]) -> Mesh {
+    let mut mesh = Mesh::default();
+    let mut used = HashMap::new();
+    for primitive in m.primitives() {
+        if primitive.mode() != gltf::mesh::Mode::Triangles {
+            log::warn!(
+                "gltf mesh {:?}: skip {:?} primitive",
+                m.name(),
+                primitive.mode()
+            );
+            continue;
+        }
+        let reader = primitive.reader(|b| buffers.get(b.index()).map(Vec::as_slice));
+        let Some(positions) = reader.read_positions() else {
+            continue;
+        };
+        let first = mesh.positions.len();
+        mesh.positions.extend(positions);
+        let vertex_count = mesh.positions.len() - first;
+        let has_normals = match reader.read_normals() {
+            Some(normals) => {
+                mesh.normals.extend(normals);
+                true
+            }
+            None => {
+                mesh.normals.extend((0..vertex_count).map(|_| [0.; 3]));
+                false
+            }
+        };
+        match reader.read_tex_coords(0) {
+            Some(uvs) => mesh.uvs.extend(uvs.into_f32()),
+            None => mesh.uvs.extend((0..vertex_count).map(|_| [0.; 2])),
+        }
+        let start = mesh.indices.len() as u32;
+        match reader.read_indices() {
+            Some(indices) => mesh
+                .indices
+                .extend(indices.into_u32().map(|i| i + first as u32)),
+            None => mesh
+                .indices
+                .extend(first as u32..(first + vertex_count) as u32),
+        }
+        let end = mesh.indices.len() as u32;
+        if !has_normals {
+            smooth_normals(&mut mesh, first, start..end);
+        }
+        let material = primitive.material().index().map(|i| {
+            *used.entry(i).or_insert_with(|| {
+                mesh.materials.push(materials[i].clone());
+                mesh.materials.len() - 1
+            })
+        });
+        mesh.submeshes.push(SubMesh {
+            name: format!("{}.{}", m.name().unwrap_or("mesh"), primitive.index()),
+            indices: start..end,
+            material,
+        });
+    }
+    mesh
+}
+
+fn camera(c: gltf::Camera) -> Camera {
+    let projection = match c.projection() {
+        gltf::camera::Projection::Perspective(p) => Projection::Perspective {
+            yfov: p.yfov(),
+            aspect_ratio: p.aspect_ratio(),
+            znear: p.znear(),
+            zfar: p.zfar(),
+        },
+        gltf::camera::Projection::Orthographic(o) => Projection::Orthographic {
+            xmag: o.xmag(),
+            ymag: o.ymag(),
+            znear: o.znear(),
+            zfar: o.zfar(),
+        },
+    };
+    Camera {
+        name: c.name().unwrap_or_default().to_string(),
+        projection,
+    }
+}
+
+fn light(l: gltf::khr_lights_punctual::Light) -> Light {
+    let kind = match l.kind() {
+        gltf::khr_lights_punctual::Kind::Directional => LightKind::Directional,
+        gltf::khr_lights_punctual::Kind::Point => LightKind::Point,
+        gltf::khr_lights_punctual::Kind::Spot {
+            inner_cone_angle,
+            outer_cone_angle,
+        } => LightKind::Spot {
+            inner_cone_angle,
+            outer_cone_angle,
+        },
+    };
+    Light {
+        name: l.name().unwrap_or_default().to_string(),
+        kind,
+        color: l.color(),
+        intensity: l.intensity(),
+        range: l.range(),
+    }
+}
+
+fn visit(node: &gltf::Node, parent: Option<usize>, parent_world: Mat4, nodes: &mut Vec<Node>) {
+    let local = Mat4::from_cols_array_2d(&node.transform().matrix());
+    let world = parent_world * local;
+    let index = nodes.len();
+    nodes.push(Node {
+        name: node.name().unwrap_or_default().to_string(),
+        parent,
+        local,
+        world,
+        mesh: node.mesh().map(|m| m.index()),
+        camera: node.camera().map(|c| c.index()),
+        light: node.light().map(|l| l.index()),
+    });
+    for child in node.children() {
+        visit(&child, Some(index), world, nodes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::*;
+
+    const DISPLAY: &[u8] = include_bytes!("../../static/models/display.gltf");
+
+    #[test]
+    fn imports_hierarchy_and_transforms() {
+        let scene = load(DISPLAY, &[]).unwrap();
+        let names: Vec<_> = scene.nodes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["display", "pedestal", "gem", "camera", "sun"]);
+        assert_eq!(scene.nodes[2].parent, Some(0));
+        assert_eq!(scene.nodes[3].parent, None);
+
+        // gem 的世界位置 = 父节点平移 + 自身平移
+        let gem = scene.nodes[2].world.transform_point3(Vec3::ZERO);
+        assert!(gem.abs_diff_eq(Vec3::new(1.6, -0.25, -1.2), 1e-5));
+        assert_eq!(scene.mesh_instances().count(), 2);
+    }
+
+    #[test]
+    fn imports_meshes_and_materials() {
+        let scene = load(DISPLAY, &[]).unwrap();
+        let pedestal = &scene.meshes[0];
+        assert_eq!(pedestal.vertex_count(), 24);
+        assert_eq!(pedestal.triangle_count(), 12);
+        assert_eq!(pedestal.submeshes.len(), 1);
+        assert_eq!(pedestal.materials.len(), 1);
+        assert_eq!(pedestal.materials[0].name, "marble");
+        assert_eq!(pedestal.materials[0].roughness, 0.6);
+
+        let gem = &scene.meshes[1];
+        assert_eq!(gem.triangle_count(), 8);
+        let ruby = &gem.materials[gem.submeshes[0].material.unwrap()];
+        assert_eq!(ruby.metallic, 0.3);
+        assert_eq!(ruby.emissive, [0.1, 0., 0.]);
+        // 内嵌在 bufferView 里的 PNG
+        let texture = scene
+            .image(ruby.diffuse_texture.as_deref().unwrap())
+            .unwrap();
+        assert_eq!(texture.dimensions(), (2, 2));
+        assert_eq!(texture.get_pixel(1, 0).0, [200, 30, 50, 255]);
+    }
+
+    #[test]
+    fn imports_cameras_and_lights() {
+        let scene = load(DISPLAY, &[]).unwrap();
+        assert_eq!(scene.nodes[3].camera, Some(0));
+        assert!(matches!(
+            scene.cameras[0].projection,
+            Projection::Perspective { zfar: Some(z), .. } if z == 100.
+        ));
+        let sun = &scene.lights[scene.nodes[4].light.unwrap()];
+        assert_eq!(sun.kind, LightKind::Directional);
+        assert_eq!(sun.intensity, 3.);
+
+        // 相机在 (0, 1.5, 5) 沿 -z 看，太阳绕 x 轴转了 -45° 斜着往下照
+        let (camera, world) = scene.camera_instances().next().unwrap();
+        assert_eq!(camera.name, "overview");
+        assert!(world
+            .transform_point3(Vec3::ZERO)
+            .abs_diff_eq(Vec3::new(0., 1.5, 5.), 1e-5));
+        let projection = camera.projection_matrix(1.);
+        assert!((projection.x_axis.x * 1.3333 - projection.y_axis.y).abs() < 1e-4);
+        let (_, world) = scene.light_instances().next().unwrap();
+        let direction = world.transform_vector3(Vec3::NEG_Z);
+        assert!(direction.abs_diff_eq(Vec3::new(0., -1., -1.).normalize(), 1e-5));
+    }
+
+    // 外部 .bin、没有索引、没有法线的三角形
+    #[test]
+    fn external_buffer_without_indices_or_normals() {
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "scenes": [{"nodes": [0]}],
+            "nodes": [{"mesh": 0, "translation": [0, 0, 1]}],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}}]}],
+            "accessors": [{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                           "min": [0, 0, 0], "max": [1, 1, 0]}],
+            "bufferViews": [{"buffer": 0, "byteLength": 36}],
+            "buffers": [{"uri": "triangle.bin", "byteLength": 36}]
+        }"#;
+        let positions: [[f32; 3]; 3] = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]];
+        let bin = bytemuck::cast_slice(&positions);
+        assert!(load(json.as_bytes(), &[]).is_err());
+
+        let scene = load(json.as_bytes(), &[("triangle.bin", bin)]).unwrap();
+        let mesh = &scene.meshes[0];
+        assert_eq!(mesh.indices, [0, 1, 2]);
+        assert_eq!(mesh.normals, vec![[0., 0., 1.]; 3]);
+        assert_eq!(mesh.submeshes[0].material, None);
+        assert!(scene.cameras.is_empty() && scene.lights.is_empty());
+    }
+
+    // 外部 .bin 比 bufferView 短，图片读不到
+    #[test]
+    fn image_outside_buffer_is_an_error() {
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "scenes": [{"nodes": []}],
+            "images": [{"bufferView": 0, "mimeType": "image/png"}],
+            "bufferViews": [{"buffer": 0, "byteOffset": 4, "byteLength": 16}],
+            "buffers": [{"uri": "image.bin", "byteLength": 20}]
+        }"#;
+        let error = load(json.as_bytes(), &[("image.bin", &[0; 8])])
+            .err()
+            .unwrap();
+        assert!(error.contains("image 0"), "{}", error);
+    }
+}
@@ -0,0 +1,327 @@
+/// 实例化渲染：一个网格画成很多份，每份有自己的模型矩阵和颜色
+/// 实例先在 CPU 侧改，`sync` 时只上传改过的那一段；放不下时容量翻倍，重建 buffer 整个上传
+/// 着色用场景的 `Uniforms`（group 0）和固定方向的平行光，不接收阴影
+use std::ops::Range;
+
+use glam::Mat4;
+use wgpu::PipelineCompilationOptions;
+
+use crate::utils::{
+    pipeline_cache::PipelineCache,
+    reflect::{Bindings, ShaderLayout},
+};
+
+use super::{GpuMesh, Mesh};
+
+const SHADER_NAME: &str = "instanced";
+const SHADER: &str = include_str!("../shaders/instanced.wgsl");
+
+// 第一次建 buffer 时最少留这么多个实例的位置
+const MIN_CAPACITY: usize = 64;
+
+/// 一个实例，按实例步进的顶点属性
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Instance {
+    pub model: [[f32; 4]; 4], // 列主序，法线也用它变换，只支持等比缩放
+    pub color: [f32; 4],
+}
+
+impl Instance {
+    // 接在网格属性后面：模型矩阵的四列 4..=7，颜色 8
+    pub const LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+        array_stride: size_of::<Instance>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &wgpu::vertex_attr_array![
+            4 => Float32x4,
+            5 => Float32x4,
+            6 => Float32x4,
+            7 => Float32x4,
+            8 => Float32x4
+        ],
+    };
+
+    pub fn new(model: Mat4, color: [f32; 4]) -> Self {
+        Instance {
+            model: model.to_cols_array_2d(),
+            color,
+        }
+    }
+}
+
+/// CPU 侧的实例列表，记着上次 `take_dirty` 之后改过的下标范围
+#[derive(Debug, Default)]
+pub struct Instances {
+    items: Vec<Instance>,
+    dirty: Option<Range<usize>>,
+}
+
+impl Instances {
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[Instance] {
+        &self.items
+    }
+
+    /// 返回新实例的下标
+    pub fn push(&mut self, instance: Instance) -> usize {
+        let i = self.items.len();
+        self.items.push(instance);
+        self.mark(i..i + 1);
+        i
+    }
+
+    pub fn set(&mut self, i: usize, instance: Instance) {
+        if self.items[i] != instance {
+            self.items[i] = instance;
+            self.mark(i..i + 1);
+        }
+    }
+
+    /// 最后一个实例挪到 `i`，其余的下标不变
+    pub fn swap_remove(&mut self, i: usize) -> Instance {
+        let removed = self.items.swap_remove(i);
+        if i < self.items.len() {
+            self.mark(i..i + 1);
+        }
+        removed
+    }
+
+    /// 缩短不用上传，少画几个就行
+    pub fn truncate(&mut self, len: usize) {
+        self.items.truncate(len);
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.dirty = None;
+    }
+
+    // 合并成一段连续的范围，中间没改的也一起上传，换来每帧最多一次 write_buffer
+    fn mark(&mut self, range: Range<usize>) {
+        self.dirty = Some(match self.dirty.take() {
+            Some(dirty) => dirty.start.min(range.start)..dirty.end.max(range.end),
+            None => range,
+        });
+    }
+
+    // 取出要上传的范围，删掉的尾部不算
+    fn take_dirty(&mut self) -> Option<Range<usize>> {
+        let dirty = self.dirty.take()?;
+        let range = dirty.start..dirty.end.min(self.items.len());
+        (!range.is_empty()).then_some(range)
+    }
+}
+
+// 放得下 `len` 个实例的容量：不够时翻倍到够为止
+fn grown_capacity(capacity: usize, len: usize) -> usize {
+    let mut capacity = capacity.max(MIN_CAPACITY);
+    while capacity < len {
+        capacity *= 2;
+    }
+    capacity
+}
+
+/// 网格加上它的实例 buffer
+pub struct InstancedMesh {
+    pub mesh: GpuMesh,
+    pub instances: Instances,
+    label: String,
+    buffer: wgpu::Buffer,
+    capacity: usize,
+    drawn: u32, // 上次 sync 时的实例数，画的时候只用 GPU 上已经有的
+}
+
+impl InstancedMesh {
+    /// `capacity` 是预留的实例数，之后不够会自动扩
+    pub fn new(device: &wgpu::Device, label: &str, mesh: &Mesh, capacity: usize) -> Self {
+        let capacity = grown_capacity(capacity, 0);
+        InstancedMesh {
+            mesh: mesh.upload(device, label),
+            instances: Instances::default(),
+            label: label.to_string(),
+            buffer: create_buffer(device, label, capacity),
+            capacity,
+            drawn: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 把改过的实例写进 buffer，每帧画之前调一次
+    pub fn sync(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let len = self.instances.len();
+        let dirty = self.instances.take_dirty();
+        if len > self.capacity {
+            self.capacity = grown_capacity(self.capacity, len);
+            self.buffer = create_buffer(device, &self.label, self.capacity);
+            queue.write_buffer(
+                &self.buffer,
+                0,
+                bytemuck::cast_slice(self.instances.as_slice()),
+            );
+        } else if let Some(range) = dirty {
+            let offset = (range.start * size_of::<Instance>()) as wgpu::BufferAddress;
+            let items = &self.instances.as_slice()[range];
+            queue.write_buffer(&self.buffer, offset, bytemuck::cast_slice(items));
+        }
+        self.drawn = len as u32;
+    }
+}
+
+fn create_buffer(device: &wgpu::Device, label: &str, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(&format!("{} instances", label)),
+        size: (capacity * size_of::<Instance>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// 实例化管线，一个场景一份，可以画任意多个 `InstancedMesh`
+pub struct InstancedRenderer {
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl InstancedRenderer {
+    /// `camera_layout` 是场景 `Uniforms` 的 bind group layout，颜色和深度格式要和场景的 render pass 一致
+    pub fn new(
+        cache: &PipelineCache,
+        camera_layout: &wgpu::BindGroupLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+    ) -> Self {
+        let (module, reflect) = cache.validated_shader(SHADER_NAME, SHADER, &[]).unwrap();
+        let layout = ShaderLayout::new(
+            cache,
+            "instanced pipeline layout",
+            Bindings::new(&[&reflect]).unwrap(),
+            &[(0, camera_layout)],
+        );
+        let pipeline = cache.render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("instanced pipeline"),
+            layout: Some(&layout.pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: Some("vs_main"),
+                buffers: &[
+                    GpuMesh::POSITION_LAYOUT,
+                    GpuMesh::NORMAL_LAYOUT,
+                    Instance::LAYOUT,
+                ],
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        InstancedRenderer { pipeline }
+    }
+
+    /// `camera` 是 `camera_layout` 对应的 bind group
+    /// 会换掉 render pass 的管线和 group 0，之后再画别的要重新设置
+    pub fn draw<'m>(
+        &self,
+        render_pass: &mut wgpu::RenderPass,
+        camera: &wgpu::BindGroup,
+        meshes: impl IntoIterator<Item = &'m InstancedMesh>,
+    ) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, camera, &[]);
+        for instanced in meshes.into_iter().filter(|m| m.drawn > 0) {
+            let mesh = &instanced.mesh;
+            let size = instanced.drawn as wgpu::BufferAddress * size_of::<Instance>() as u64;
+            render_pass.set_vertex_buffer(0, mesh.positions.slice(..));
+            render_pass.set_vertex_buffer(1, mesh.normals.slice(..));
+            render_pass.set_vertex_buffer(2, instanced.buffer.slice(..size));
+            render_pass.set_index_buffer(mesh.indices.slice(..), mesh.index_format);
+            render_pass.draw_indexed(0..mesh.index_count, 0, 0..instanced.drawn);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(x: f32) -> Instance {
+        Instance::new(Mat4::from_translation(glam::Vec3::X * x), [1.; 4])
+    }
+
+    #[test]
+    fn dirty_range_covers_changes() {
+        let mut instances = Instances::default();
+        for i in 0..8 {
+            instances.push(instance(i as f32));
+        }
+        assert_eq!(instances.take_dirty(), Some(0..8));
+        assert_eq!(instances.take_dirty(), None);
+
+        // 没变的不算
+        instances.set(2, instance(2.));
+        assert_eq!(instances.take_dirty(), None);
+        instances.set(5, instance(0.));
+        instances.set(3, instance(0.));
+        assert_eq!(instances.take_dirty(), Some(3..6));
+
+        // 最后一个挪到 1，尾部缩短不用上传
+        instances.swap_remove(1);
+        assert_eq!(instances.as_slice()[1], instance(7.));
+        assert_eq!(instances.take_dirty(), Some(1..2));
+        instances.set(6, instance(0.));
+        instances.truncate(4);
+        assert_eq!(instances.take_dirty(), None);
+        instances.swap_remove(3);
+        assert_eq!(instances.take_dirty(), None);
+        assert_eq!(instances.len(), 3);
+    }
+
+    #[test]
+    fn capacity_doubles() {
+        assert_eq!(grown_capacity(0, 0), MIN_CAPACITY);
+        assert_eq!(grown_capacity(100, 100), 100);
+        assert_eq!(grown_capacity(100, 101), 200);
+        assert_eq!(grown_capacity(64, 1000), 1024);
+    }
+
+    #[test]
+    fn layout_matches_instance() {
+        let attributes = Instance::LAYOUT.attributes;
+        let last = attributes.last().unwrap();
+        assert_eq!(
+            last.offset + last.format.size(),
+            size_of::<Instance>() as u64
+        );
+        assert_eq!(attributes[4].offset, 64);
+    }
+}
@@ -0,0 +1,292 @@
+/// 三角网格：CPU 侧的 `Mesh` 和上传到 GPU 后的 `GpuMesh`
+/// 顶点属性分开存放（位置、法线、uv、切线各一个数组、各一个 vertex buffer），
+/// 只用位置的管线（阴影深度图等）只绑第 0 个 buffer 即可。CPU 侧索引统一用 u32，
+/// 上传时顶点数够少就换成 u16
+/// 约定：右手系 y 朝上，逆时针为正面，uv 左上角为原点
+/// 切线和 glTF / MikkTSpace 一致：xyz 沿 u 增大的方向，w 为 ±1，
+/// 副切线 = cross(normal, tangent.xyz) * w，指向贴图的上方（v 减小）
+use std::ops::Range;
+
+use glam::{Mat3, Mat4, Vec3};
+use wgpu::util::DeviceExt;
+
+pub mod edges;
+pub mod gltf;
+pub mod instanced;
+pub mod normals;
+pub mod obj;
+pub mod primitives;
+pub mod process;
+pub mod tangents;
+pub mod wireframe;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,      // 左上角为原点，和 wgpu 的纹理坐标一致
+    pub tangents: Vec<[f32; 4]>, // 为空表示没有切线
+    pub indices: Vec<u32>,
+    pub submeshes: Vec<SubMesh>,
+    pub materials: Vec<Material>,
+}
+
+/// 一段连续的索引，共用一个材质；OBJ 里的一个 object / group
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubMesh {
+    pub name: String,
+    pub indices: Range<u32>,
+    pub material: Option<usize>, // Mesh::materials 的下标
+}
+
+/// OBJ 的 Phong 参数和 glTF 的金属度/粗糙度放在一起，各自没有的字段保持默认值
+/// 贴图是名字：OBJ 里是文件路径，glTF 里是 `GltfScene::images` 的 key
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub name: String,
+    pub diffuse: [f32; 3], // glTF 的 base color
+    pub specular: [f32; 3],
+    pub shininess: f32,
+    pub opacity: f32,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: [f32; 3],
+    pub diffuse_texture: Option<String>,
+    pub metallic_roughness_texture: Option<String>,
+    pub normal_texture: Option<String>,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            name: String::new(),
+            diffuse: [0.8; 3],
+            specular: [0.0; 3],
+            shininess: 0.0,
+            opacity: 1.0,
+            metallic: 0.0,
+            roughness: 1.0,
+            emissive: [0.0; 3],
+            diffuse_texture: None,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+        }
+    }
+}
+
+impl Mesh {
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// 位置按 `matrix` 变换，法线按逆转置变换，切线按线性部分变换
+    pub fn transform(&mut self, matrix: Mat4) {
+        let linear = Mat3::from_mat4(matrix);
+        let normal_matrix = linear.inverse().transpose();
+        for p in &mut self.positions {
+            *p = matrix.transform_point3(Vec3::from(*p)).into();
+        }
+        for n in &mut self.normals {
+            *n = (normal_matrix * Vec3::from(*n)).normalize_or_zero().into();
+        }
+        // 镜像变换会让副切线反向
+        let handedness = linear.determinant().signum();
+        for t in &mut self.tangents {
+            let xyz = (linear * Vec3::new(t[0], t[1], t[2])).normalize_or_zero();
+            *t = xyz.extend(t[3] * handedness).into();
+        }
+    }
+
+    /// 位置的包围盒 (min, max)，空网格返回 None
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let mut positions = self.positions.iter().map(|&p| Vec3::from(p));
+        let first = positions.next()?;
+        Some(positions.fold((first, first), |(min, max), p| (min.min(p), max.max(p))))
+    }
+
+    /// 复制第 `i` 个顶点的全部属性，返回新顶点的下标
+    pub fn duplicate_vertex(&mut self, i: usize) -> u32 {
+        let n = self.positions.len();
+        self.positions.push(self.positions[i]);
+        if self.normals.len() == n {
+            self.normals.push(self.normals[i]);
+        }
+        if self.uvs.len() == n {
+            self.uvs.push(self.uvs[i]);
+        }
+        if self.tangents.len() == n {
+            self.tangents.push(self.tangents[i]);
+        }
+        n as u32
+    }
+
+    /// 包住所有顶点的球 (中心, 半径)，Ritter 算法，比最小包围球大一点
+    pub fn bounding_sphere(&self) -> Option<(Vec3, f32)> {
+        let positions: Vec<Vec3> = self.positions.iter().map(|&p| Vec3::from(p)).collect();
+        let farthest = |from: Vec3| {
+            positions
+                .iter()
+                .copied()
+                .max_by(|a, b| {
+                    a.distance_squared(from)
+                        .total_cmp(&b.distance_squared(from))
+                })
+                .unwrap()
+        };
+        let a = farthest(*positions.first()?);
+        let b = farthest(a);
+        let (mut center, mut radius) = ((a + b) / 2., a.distance(b) / 2.);
+        // 在球外的点把球往自己这边扩
+        for &p in &positions {
+            let distance = p.distance(center);
+            if distance > radius {
+                let grown = (radius + distance) / 2.;
+                center += (p - center) * ((grown - radius) / distance);
+                radius = grown;
+            }
+        }
+        Some((center, radius))
+    }
+
+    pub fn upload(&self, device: &wgpu::Device, label: &str) -> GpuMesh {
+        let buffer = |name: &str, contents: &[u8], usage| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{} {}", label, name)),
+                contents,
+                usage,
+            })
+        };
+        let vertex = wgpu::BufferUsages::VERTEX;
+        let index_format = process::index_format(self.vertex_count());
+        GpuMesh {
+            positions: buffer("positions", bytemuck::cast_slice(&self.positions), vertex),
+            normals: buffer("normals", bytemuck::cast_slice(&self.normals), vertex),
+            uvs: buffer("uvs", bytemuck::cast_slice(&self.uvs), vertex),
+            tangents: (!self.tangents.is_empty())
+                .then(|| buffer("tangents", bytemuck::cast_slice(&self.tangents), vertex)),
+            indices: buffer(
+                "indices",
+                &process::index_bytes(&self.indices, index_format),
+                wgpu::BufferUsages::INDEX,
+            ),
+            index_format,
+            index_count: self.indices.len() as u32,
+            submeshes: self.submeshes.clone(),
+        }
+    }
+}
+
+// 文件里没有法线时，从 `first` 开始的顶点按面积加权平均 `indices` 里相邻三角形的面法线
+fn smooth_normals(mesh: &mut Mesh, first: usize, indices: Range<u32>) {
+    let indices = &mesh.indices[indices.start as usize..indices.end as usize];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|k| Vec3::from(mesh.positions[triangle[k] as usize]));
+        // 叉积的长度是面积的两倍，不归一化就是面积加权
+        let face = (b - a).cross(c - a);
+        for &i in triangle {
+            let n = &mut mesh.normals[i as usize];
+            *n = (Vec3::from(*n) + face).into();
+        }
+    }
+    for n in &mut mesh.normals[first..] {
+        *n = Vec3::from(*n).normalize_or_zero().into();
+    }
+}
+
+// 按角（`indices` 的每一项）给一个属性赋值：同一顶点上的角值都相同就直接写，
+// 不同的话多出来的值各复制一个顶点。`attribute` 必须已经和顶点数一样长
+fn set_per_corner<T: Copy + PartialEq>(
+    mesh: &mut Mesh,
+    values: &[T],
+    attribute: fn(&mut Mesh) -> &mut Vec<T>,
+) {
+    // 每个原顶点已经用过的 (值, 顶点)
+    let mut used: Vec<Vec<(T, u32)>> = vec![Vec::new(); mesh.vertex_count()];
+    for (corner, &value) in values.iter().enumerate() {
+        let vertex = mesh.indices[corner] as usize;
+        let index = match used[vertex].iter().find(|(v, _)| *v == value) {
+            Some(&(_, index)) => index,
+            None => {
+                let index = if used[vertex].is_empty() {
+                    vertex as u32
+                } else {
+                    mesh.duplicate_vertex(vertex)
+                };
+                attribute(mesh)[index as usize] = value;
+                used[vertex].push((value, index));
+                index
+            }
+        };
+        mesh.indices[corner] = index;
+    }
+}
+
+/// 上传后的网格，材质留在 CPU 侧由各场景自己处理
+pub struct GpuMesh {
+    pub positions: wgpu::Buffer,
+    pub normals: wgpu::Buffer,
+    pub uvs: wgpu::Buffer,
+    pub tangents: Option<wgpu::Buffer>,
+    pub indices: wgpu::Buffer,
+    pub index_format: wgpu::IndexFormat,
+    pub index_count: u32,
+    pub submeshes: Vec<SubMesh>,
+}
+
+impl GpuMesh {
+    // 各属性固定的 shader location：position 0，normal 1，uv 2，tangent 3
+    pub const POSITION_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+        array_stride: size_of::<[f32; 3]>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![0 => Float32x3],
+    };
+    pub const NORMAL_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+        array_stride: size_of::<[f32; 3]>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![1 => Float32x3],
+    };
+    pub const UV_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+        array_stride: size_of::<[f32; 2]>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![2 => Float32x2],
+    };
+    pub const TANGENT_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+        array_stride: size_of::<[f32; 4]>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![3 => Float32x4],
+    };
+
+    /// 只绑位置，画全部三角形（阴影深度图用）
+    pub fn draw_positions(&self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_vertex_buffer(0, self.positions.slice(..));
+        render_pass.set_index_buffer(self.indices.slice(..), self.index_format);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::primitives;
+
+    #[test]
+    fn bounding_sphere_contains_all_vertices() {
+        let mut mesh = primitives::icosphere(1.5, 2);
+        mesh.transform(glam::Mat4::from_translation(Vec3::new(1., 2., 3.)));
+        let (center, radius) = mesh.bounding_sphere().unwrap();
+        assert!(center.distance(Vec3::new(1., 2., 3.)) < 0.1);
+        assert!((1.5..1.6).contains(&radius), "{}", radius);
+        for p in &mesh.positions {
+            assert!(Vec3::from(*p).distance(center) <= radius + 1e-5);
+        }
+
+        let (_, radius) = primitives::cube(2.).bounding_sphere().unwrap();
+        assert!(radius >= 3f32.sqrt() - 1e-5);
+        assert_eq!(super::Mesh::default().bounding_sphere(), None);
+    }
+}
@@ -0,0 +1,232 @@
+/// 任意三角网格的法线：平直（每个面自己的法线）、平滑（相邻面加权平均）和带折痕角的平滑
+/// 相邻按位置判断而不是按顶点下标，uv 接缝两侧复制出来的顶点也会一起平滑
+/// 法线在某个顶点上需要不止一个值时（硬边）会把顶点拆开，其余属性原样复制
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use glam::Vec3;
+
+use super::{set_per_corner, Mesh};
+
+/// 平滑时相邻面的权重
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weighting {
+    /// 按三角形面积，大面说了算
+    Area,
+    /// 按三角形在该顶点处的内角，结果和三角化的方式无关
+    Angle,
+}
+
+/// 每个面用自己的法线，顶点在法线不同的面之间拆开
+pub fn flat(mesh: &mut Mesh) {
+    let corners: Vec<[f32; 3]> = face_normals(mesh)
+        .iter()
+        .flat_map(|n| [n.normalize_or_zero().into(); 3])
+        .collect();
+    mesh.normals.resize(mesh.vertex_count(), [0.; 3]);
+    set_per_corner(mesh, &corners, |mesh| &mut mesh.normals);
+}
+
+/// 所有相邻面一起平滑，不拆顶点
+pub fn smooth(mesh: &mut Mesh, weighting: Weighting) {
+    creased(mesh, PI, weighting);
+}
+
+/// 只和法线夹角不超过 `crease_angle`（弧度）的相邻面一起平滑，超过的当成硬边
+/// 0 相当于 `flat`，π 相当于 `smooth`
+pub fn creased(mesh: &mut Mesh, crease_angle: f32, weighting: Weighting) {
+    let faces = face_normals(mesh);
+    let units: Vec<Vec3> = faces.iter().map(|n| n.normalize_or_zero()).collect();
+
+    // 位置完全相同的顶点算同一个点
+    let mut points = HashMap::new();
+    let point: Vec<usize> = mesh
+        .positions
+        .iter()
+        .map(|p| {
+            let next = points.len();
+            *points.entry(p.map(f32::to_bits)).or_insert(next)
+        })
+        .collect();
+
+    // 每个点周围的面和权重
+    let mut around = vec![Vec::new(); points.len()];
+    for (corner, &i) in mesh.indices.iter().enumerate() {
+        let face = corner / 3;
+        let weight = match weighting {
+            // 叉积的长度是面积的两倍，对所有面都一样不影响结果
+            Weighting::Area => faces[face].length(),
+            Weighting::Angle => corner_angle(mesh, corner),
+        };
+        around[point[i as usize]].push((face, weight));
+    }
+
+    // 同一个点上参与平滑的面相同的角，求和顺序也相同，得到的法线逐位相等，不会被拆开
+    let cos = crease_angle.cos() - 1e-6;
+    let corners: Vec<[f32; 3]> = mesh
+        .indices
+        .iter()
+        .enumerate()
+        .map(|(corner, &i)| {
+            let face = units[corner / 3];
+            around[point[i as usize]]
+                .iter()
+                .filter(|&&(other, _)| face.dot(units[other]) >= cos)
+                .map(|&(other, weight)| units[other] * weight)
+                .sum::<Vec3>()
+                .normalize_or_zero()
+                .into()
+        })
+        .collect();
+    mesh.normals.resize(mesh.vertex_count(), [0.; 3]);
+    set_per_corner(mesh, &corners, |mesh| &mut mesh.normals);
+}
+
+// 每个三角形未归一化的法线（逆时针为正面）
+fn face_normals(mesh: &Mesh) -> Vec<Vec3> {
+    mesh.indices
+        .chunks_exact(3)
+        .map(|triangle| {
+            let [a, b, c] = [0, 1, 2].map(|k| Vec3::from(mesh.positions[triangle[k] as usize]));
+            (b - a).cross(c - a)
+        })
+        .collect()
+}
+
+// 第 `corner` 个角处的内角
+fn corner_angle(mesh: &Mesh, corner: usize) -> f32 {
+    let triangle = corner - corner % 3;
+    let p = |k: usize| Vec3::from(mesh.positions[mesh.indices[triangle + k % 3] as usize]);
+    let k = corner % 3;
+    let origin = p(k);
+    (p(k + 1) - origin).angle_between(p(k + 2) - origin)
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::*;
+    use crate::mesh::primitives;
+
+    // 8 个顶点共用的立方体，没有法线
+    fn shared_cube() -> Mesh {
+        let mut mesh = primitives::cube(2.);
+        let mut unique: Vec<[f32; 3]> = Vec::new();
+        for index in &mut mesh.indices {
+            let p = mesh.positions[*index as usize];
+            *index = match unique.iter().position(|&q| q == p) {
+                Some(i) => i as u32,
+                None => {
+                    unique.push(p);
+                    unique.len() as u32 - 1
+                }
+            };
+        }
+        Mesh {
+            positions: unique,
+            indices: mesh.indices,
+            submeshes: mesh.submeshes,
+            ..Default::default()
+        }
+    }
+
+    fn corner_normals(mesh: &Mesh) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+        mesh.indices.chunks_exact(3).flat_map(move |triangle| {
+            let [a, b, c] = [0, 1, 2].map(|k| Vec3::from(mesh.positions[triangle[k] as usize]));
+            let face = (b - a).cross(c - a).normalize();
+            triangle
+                .iter()
+                .map(move |&i| (face, Vec3::from(mesh.normals[i as usize])))
+        })
+    }
+
+    #[test]
+    fn flat_splits_cube_corners() {
+        let mut mesh = shared_cube();
+        assert_eq!(mesh.vertex_count(), 8);
+        flat(&mut mesh);
+        // 每个角 3 个面，每个面一份
+        assert_eq!(mesh.vertex_count(), 24);
+        assert_eq!(mesh.normals.len(), 24);
+        assert_eq!(mesh.triangle_count(), 12);
+        for (face, normal) in corner_normals(&mesh) {
+            assert!(face.abs_diff_eq(normal, 1e-6), "{} vs {}", face, normal);
+        }
+    }
+
+    #[test]
+    fn angle_weighting_ignores_triangulation() {
+        let mut mesh = shared_cube();
+        smooth(&mut mesh, Weighting::Angle);
+        assert_eq!(mesh.vertex_count(), 8);
+        // 每个面在角上都是 90 度，不管这个角被分成一个还是两个三角形
+        for (p, n) in mesh.positions.iter().zip(&mesh.normals) {
+            let expected = Vec3::from(*p).normalize();
+            assert!(expected.abs_diff_eq(Vec3::from(*n), 1e-6), "{:?}", n);
+        }
+    }
+
+    #[test]
+    fn weights_merge_vertices_at_same_position() {
+        // 原点处两个不相连的顶点：xy 平面上面积 2 的三角形和 yz 平面上面积 0.5 的三角形
+        let mut mesh = Mesh {
+            positions: vec![
+                [0., 0., 0.],
+                [2., 0., 0.],
+                [0., 2., 0.],
+                [0., 0., 0.],
+                [0., 1., 0.],
+                [0., 0., 1.],
+            ],
+            indices: vec![0, 1, 2, 3, 4, 5],
+            ..Default::default()
+        };
+        let origin = |mesh: &Mesh| [0, 3].map(|i| Vec3::from(mesh.normals[i]));
+
+        smooth(&mut mesh, Weighting::Area);
+        let expected = Vec3::new(0.5, 0., 2.).normalize();
+        for n in origin(&mesh) {
+            assert!(n.abs_diff_eq(expected, 1e-6), "{}", n);
+        }
+        assert_eq!(mesh.normals[1], [0., 0., 1.]);
+        assert_eq!(mesh.normals[5], [1., 0., 0.]);
+
+        smooth(&mut mesh, Weighting::Angle);
+        let expected = Vec3::new(1., 0., 1.).normalize();
+        for n in origin(&mesh) {
+            assert!(n.abs_diff_eq(expected, 1e-6), "{}", n);
+        }
+        assert_eq!(mesh.vertex_count(), 6);
+    }
+
+    #[test]
+    fn crease_angle_keeps_hard_edges() {
+        let mut mesh = shared_cube();
+        creased(&mut mesh, 100f32.to_radians(), Weighting::Angle);
+        assert_eq!(mesh.vertex_count(), 8);
+
+        let mut mesh = shared_cube();
+        creased(&mut mesh, 30f32.to_radians(), Weighting::Angle);
+        assert_eq!(mesh.vertex_count(), 24);
+        for (face, normal) in corner_normals(&mesh) {
+            assert!(face.abs_diff_eq(normal, 1e-6));
+        }
+
+        // 圆柱：侧面相邻两段只差 360/32 度，盖子和侧面差 90 度
+        let mut mesh = primitives::cylinder(1., 2., 32);
+        let vertex_count = mesh.vertex_count();
+        let expected = mesh.normals.clone();
+        mesh.normals.clear();
+        creased(&mut mesh, 45f32.to_radians(), Weighting::Angle);
+        assert_eq!(mesh.vertex_count(), vertex_count);
+        for (n, e) in mesh.normals.iter().zip(&expected) {
+            assert!(
+                Vec3::from(*n).abs_diff_eq(Vec3::from(*e), 1e-5),
+                "{:?} vs {:?}",
+                n,
+                e
+            );
+        }
+    }
+}
@@ -0,0 +1,183 @@
+/// OBJ/MTL 加载：用 tobj 解析成 `Mesh`，每个 object / group 一个 submesh
+/// 数据可以直接给字节（`include_bytes!` 编进程序），也可以在浏览器里按 URL 取
+use super::{smooth_normals, Material, Mesh, SubMesh};
+
+/// 从内存加载，`mtls` 是 `mtllib` 引用到的文件名和内容，找不到的材质用默认值
+pub fn load(obj: &[u8], mtls: &[(&str, &[u8])]) -> Result<Mesh, String> {
+    let (models, materials) = tobj::load_obj_buf(&mut &obj[..], &tobj::GPU_LOAD_OPTIONS, |path| {
+        let name = path.to_string_lossy();
+        match mtls.iter().find(|(mtl, _)| *mtl == name) {
+            Some((_, bytes)) => tobj::load_mtl_buf(&mut &bytes[..]),
+            None => Err(tobj::LoadError::OpenFileFailed),
+        }
+    })
+    .map_err(|e| format!("obj: {}", e))?;
+    Ok(from_tobj(models, materials))
+}
+
+/// 按 URL 加载，`mtllib` 相对 OBJ 所在目录去取
+#[cfg(target_arch = "wasm32")]
+pub async fn load_url(url: &str) -> Result<Mesh, String> {
+    use crate::web::fetch::fetch_bytes;
+
+    let obj = fetch_bytes(url).await?;
+    let base = url.rfind('/').map_or("", |i| &url[..=i]).to_string();
+    let (models, materials) =
+        tobj::load_obj_buf_async(&mut obj.as_slice(), &tobj::GPU_LOAD_OPTIONS, |name| {
+            let url = format!("{}{}", base, name);
+            async move {
+                match fetch_bytes(&url).await {
+                    Ok(bytes) => tobj::load_mtl_buf(&mut bytes.as_slice()),
+                    Err(e) => {
+                        log::warn!("{}", e);
+                        Err(tobj::LoadError::OpenFileFailed)
+                    }
+                }
+            }
+        })
+        .await
+        .map_err(|e| format!("obj {}: {}", url, e))?;
+    Ok(from_tobj(models, materials))
+}
+
+fn from_tobj(
+    models: Vec<tobj::Model>,
+    materials: Result<Vec<tobj::Material>, tobj::LoadError>,
+) -> Mesh {
+    let materials = materials.unwrap_or_else(|e| {
+        log::warn!("obj materials not loaded: {}, use default", e);
+        Vec::new()
+    });
+    let mut mesh = Mesh {
+        materials: materials.into_iter().map(material).collect(),
+        ..Default::default()
+    };
+    for model in models {
+        let m = model.mesh;
+        let base = mesh.positions.len() as u32;
+        let vertex_count = m.positions.len() / 3;
+        let first = mesh.positions.len();
+        mesh.positions
+            .extend(m.positions.chunks_exact(3).map(|p| [p[0], p[1], p[2]]));
+        if m.normals.len() == m.positions.len() {
+            mesh.normals
+                .extend(m.normals.chunks_exact(3).map(|n| [n[0], n[1], n[2]]));
+        } else {
+            mesh.normals.extend((0..vertex_count).map(|_| [0.; 3]));
+        }
+        if m.texcoords.len() / 2 == vertex_count {
+            // OBJ 的 v 朝上
+            mesh.uvs
+                .extend(m.texcoords.chunks_exact(2).map(|t| [t[0], 1. - t[1]]));
+        } else {
+            mesh.uvs.extend((0..vertex_count).map(|_| [0.; 2]));
+        }
+        let start = mesh.indices.len() as u32;
+        mesh.indices.extend(m.indices.iter().map(|i| i + base));
+        let end = mesh.indices.len() as u32;
+        if m.normals.len() != m.positions.len() {
+            smooth_normals(&mut mesh, first, start..end);
+        }
+        mesh.submeshes.push(SubMesh {
+            name: model.name,
+            indices: start..end,
+            material: m.material_id.filter(|&i| i < mesh.materials.len()),
+        });
+    }
+    mesh
+}
+
+fn material(m: tobj::Material) -> Material {
+    Material {
+        name: m.name,
+        diffuse: m.diffuse,
+        specular: m.specular,
+        shininess: m.shininess,
+        opacity: m.dissolve,
+        diffuse_texture: Some(m.diffuse_texture).filter(|t| !t.is_empty()),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUADS: &str = "mtllib quads.mtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+o front
+usemtl red
+f 1/1 2/2 3/3 4/4
+o back
+usemtl missing
+f 1/1 4/4 3/3
+";
+    const MTL: &str = "newmtl red
+Kd 1 0 0
+d 0.5
+map_Kd red.png
+";
+
+    #[test]
+    fn loads_submeshes_and_materials() {
+        let mesh = load(QUADS.as_bytes(), &[("quads.mtl", MTL.as_bytes())]).unwrap();
+        // 四边形三角化成两个三角形
+        assert_eq!(mesh.triangle_count(), 3);
+        assert_eq!(mesh.submeshes.len(), 2);
+        assert_eq!(mesh.submeshes[0].name, "front");
+        assert_eq!(mesh.submeshes[0].indices, 0..6);
+        assert_eq!(mesh.submeshes[1].indices, 6..9);
+        assert_eq!(mesh.submeshes[0].material, Some(0));
+        assert_eq!(mesh.submeshes[1].material, None);
+
+        let red = &mesh.materials[0];
+        assert_eq!(red.diffuse, [1., 0., 0.]);
+        assert_eq!(red.opacity, 0.5);
+        assert_eq!(red.diffuse_texture.as_deref(), Some("red.png"));
+
+        assert_eq!(mesh.positions.len(), mesh.normals.len());
+        assert_eq!(mesh.positions.len(), mesh.uvs.len());
+        assert!(mesh
+            .indices
+            .iter()
+            .all(|&i| (i as usize) < mesh.vertex_count()));
+    }
+
+    #[test]
+    fn flips_v_and_fills_missing_normals() {
+        let mesh = load(QUADS.as_bytes(), &[("quads.mtl", MTL.as_bytes())]).unwrap();
+        let first = &mesh.submeshes[0];
+        let i = mesh.indices[first.indices.start as usize] as usize;
+        assert_eq!(mesh.positions[i], [0., 0., 0.]);
+        assert_eq!(mesh.uvs[i], [0., 1.]);
+        // 逆时针的 front 朝 +z
+        for &i in &mesh.indices[0..6] {
+            assert_eq!(mesh.normals[i as usize], [0., 0., 1.]);
+        }
+        for &i in &mesh.indices[6..9] {
+            assert_eq!(mesh.normals[i as usize], [0., 0., -1.]);
+        }
+    }
+
+    #[test]
+    fn missing_mtl_uses_defaults() {
+        let mesh = load(QUADS.as_bytes(), &[]).unwrap();
+        assert!(mesh.materials.is_empty());
+        assert!(mesh.submeshes.iter().all(|s| s.material.is_none()));
+    }
+
+    #[test]
+    fn reads_normals_from_file() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 1 0\nf 1//1 2//1 3//1\n";
+        let mesh = load(obj.as_bytes(), &[]).unwrap();
+        assert_eq!(mesh.normals, vec![[0., 1., 0.]; 3]);
+        assert_eq!(mesh.submeshes[0].indices, 0..3);
+    }
+}
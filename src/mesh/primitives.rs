@@ -0,0 +1,494 @@
+/// 程序生成的基本网格，都以原点为中心，属性齐全（位置、法线、uv、切线），
+/// 朝向、绕序和切线约定见 `crate::mesh`。每个网格一个 submesh，没有材质
+///
+/// 除立方体和平面外都是绕 y 轴旋转的曲面：u 沿经度（从 +z 开始往 +x 转），
+/// v 从上往下沿轮廓线，接缝处的顶点复制一份以便 uv 连续
+use std::f32::consts::{PI, TAU};
+
+use glam::{Vec2, Vec3};
+
+use super::{process, Mesh, SubMesh};
+
+/// 边长为 `size` 的立方体，每个面 4 个顶点，uv 各自铺满 0..1
+pub fn cube(size: f32) -> Mesh {
+    // (法线, 右, 上)，右 × 上 = 法线
+    const FACES: [(Vec3, Vec3, Vec3); 6] = [
+        (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+        (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+        (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+        (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+        (Vec3::Z, Vec3::X, Vec3::Y),
+        (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+    ];
+    let mut mesh = Mesh::default();
+    for (normal, right, up) in FACES {
+        face(
+            &mut mesh,
+            normal * size / 2.,
+            right * size,
+            -up * size,
+            1,
+            1,
+        );
+    }
+    finish(mesh, "cube")
+}
+
+/// xz 平面上朝 +y 的矩形，`size` 是 x、z 方向的长度，各自细分 `subdivisions` 段
+/// u 沿 +x，v 沿 +z
+pub fn plane(size: [f32; 2], subdivisions: [u32; 2]) -> Mesh {
+    let mut mesh = Mesh::default();
+    face(
+        &mut mesh,
+        Vec3::ZERO,
+        Vec3::X * size[0],
+        Vec3::Z * size[1],
+        subdivisions[0].max(1),
+        subdivisions[1].max(1),
+    );
+    finish(mesh, "plane")
+}
+
+/// 经纬球，`sectors` 条经线、`stacks` 层纬线，两极的退化三角形去掉
+pub fn uv_sphere(radius: f32, sectors: u32, stacks: u32) -> Mesh {
+    let (sectors, stacks) = (sectors.max(3), stacks.max(2));
+    let mut mesh = Mesh::default();
+    revolve(&mut mesh, sectors, &steps(stacks), [true, true], |v| {
+        let (sin, cos) = (PI * v).sin_cos();
+        (Vec2::new(radius * sin, radius * cos), Vec2::new(sin, cos))
+    });
+    finish(mesh, "uv sphere")
+}
+
+/// 从正二十面体开始细分 `subdivisions` 次的球，三角形大小均匀
+/// uv 按经纬度算，跨接缝的三角形和极点的顶点会另外复制
+/// `subdivisions` 为 0 时一个面横跨半圈经度，顶点切线和面上的 uv 方向对不上，要用法线贴图至少细分 1 次
+pub fn icosphere(radius: f32, subdivisions: u32) -> Mesh {
+    let (directions, triangles) = icosahedron(subdivisions);
+    let longitude = |d: Vec3| 0.5 + d.x.atan2(d.z) / TAU;
+    let latitude = |d: Vec3| d.y.clamp(-1., 1.).acos() / PI;
+
+    let mut mesh = Mesh::default();
+    for triangle in triangles {
+        let d = triangle.map(|i| directions[i]);
+        let pole = d.map(|d| d.x.abs() < 1e-6 && d.z.abs() < 1e-6);
+        let mut u = d.map(longitude);
+        // 跨过 u = 0/1 接缝的三角形，把小的一侧挪到 1 以上（极点的经度不算）
+        let others = || (0..3).filter(|&k| !pole[k]).map(|k| u[k]);
+        let span = others().fold(f32::MIN, f32::max) - others().fold(f32::MAX, f32::min);
+        if span > 0.5 {
+            u = u.map(|u| if u < 0.5 { u + 1. } else { u });
+        }
+        // 极点的经度没有意义，取另外两个顶点的平均
+        for k in 0..3 {
+            if pole[k] {
+                u[k] = (u[(k + 1) % 3] + u[(k + 2) % 3]) / 2.;
+            }
+        }
+        for k in 0..3 {
+            let angle = (u[k] - 0.5) * TAU;
+            let (sin, cos) = angle.sin_cos();
+            push(
+                &mut mesh,
+                d[k] * radius,
+                d[k],
+                [u[k], latitude(d[k])],
+                Vec3::new(cos, 0., -sin),
+            );
+        }
+    }
+    mesh.indices = (0..mesh.positions.len() as u32).collect();
+    process::dedup(&mut mesh);
+    finish(mesh, "icosphere")
+}
+
+/// 圆环，`radius` 是中心线半径，`tube_radius` 是管半径，绕 y 轴 `sectors` 段、管截面 `sides` 段
+pub fn torus(radius: f32, tube_radius: f32, sectors: u32, sides: u32) -> Mesh {
+    let (sectors, sides) = (sectors.max(3), sides.max(3));
+    let mut mesh = Mesh::default();
+    revolve(&mut mesh, sectors, &steps(sides), [false, false], |v| {
+        // 从外侧赤道开始，先往下绕
+        let (sin, cos) = (TAU * v.fract()).sin_cos();
+        (
+            Vec2::new(radius + tube_radius * cos, -tube_radius * sin),
+            Vec2::new(cos, -sin),
+        )
+    });
+    finish(mesh, "torus")
+}
+
+/// 圆柱，高 `height`，上下各一个盖子
+pub fn cylinder(radius: f32, height: f32, sectors: u32) -> Mesh {
+    let mut mesh = Mesh::default();
+    frustum(&mut mesh, radius, radius, height, sectors.max(3));
+    cap(&mut mesh, radius, height / 2., sectors.max(3), true);
+    cap(&mut mesh, radius, -height / 2., sectors.max(3), false);
+    finish(mesh, "cylinder")
+}
+
+/// 圆锥，底面在 y = -height/2，尖在 y = height/2
+pub fn cone(radius: f32, height: f32, sectors: u32) -> Mesh {
+    let mut mesh = Mesh::default();
+    frustum(&mut mesh, 0., radius, height, sectors.max(3));
+    cap(&mut mesh, radius, -height / 2., sectors.max(3), false);
+    finish(mesh, "cone")
+}
+
+/// 胶囊：中间圆柱段高 `height`，上下两个半球各 `rings` 层，v 按弧长分布
+pub fn capsule(radius: f32, height: f32, sectors: u32, rings: u32) -> Mesh {
+    let rings = rings.max(1);
+    let quarter = PI * radius / 2.;
+    let length = 2. * quarter + height;
+    // 上半球、下半球各 rings + 1 行，中间的圆柱段就是两行赤道之间的一段
+    let rows: Vec<f32> = steps(rings)
+        .iter()
+        .map(|t| t * quarter)
+        .chain(steps(rings).iter().map(|t| quarter + height + t * quarter))
+        .map(|s| s / length)
+        .collect();
+    let mut mesh = Mesh::default();
+    revolve(&mut mesh, sectors.max(3), &rows, [true, true], |v| {
+        let s = v * length;
+        let (phi, y) = if s <= quarter {
+            (s / radius, height / 2.)
+        } else if s < quarter + height {
+            (PI / 2., height / 2. - (s - quarter))
+        } else {
+            ((s - quarter - height) / radius + PI / 2., -height / 2.)
+        };
+        let (sin, cos) = phi.sin_cos();
+        (
+            Vec2::new(radius * sin, y + radius * cos),
+            Vec2::new(sin, cos),
+        )
+    });
+    finish(mesh, "capsule")
+}
+
+// 0, 1/n, ..., 1
+fn steps(n: u32) -> Vec<f32> {
+    (0..=n).map(|i| i as f32 / n as f32).collect()
+}
+
+fn push(mesh: &mut Mesh, position: Vec3, normal: Vec3, uv: [f32; 2], tangent: Vec3) {
+    mesh.positions.push(position.into());
+    mesh.normals.push(normal.into());
+    mesh.uvs.push(uv);
+    mesh.tangents.push(tangent.extend(1.).into());
+}
+
+/// (cols + 1) × rows.len() 的顶点网格，`vertex(u, v)` 返回位置、法线、切线
+/// 要求 u 方向是切线、v 增大的方向和副切线相反，这样按下面的顺序连出来就是逆时针
+/// `pinched` 表示第一行 / 最后一行缩成一点（极点），那一侧的退化三角形不要
+fn grid(
+    mesh: &mut Mesh,
+    cols: u32,
+    rows: &[f32],
+    pinched: [bool; 2],
+    vertex: impl Fn(f32, f32) -> (Vec3, Vec3, Vec3),
+) {
+    let base = mesh.positions.len() as u32;
+    for &v in rows {
+        for i in 0..=cols {
+            let u = i as f32 / cols as f32;
+            let (position, normal, tangent) = vertex(u, v);
+            push(mesh, position, normal, [u, v], tangent);
+        }
+    }
+    let stride = cols + 1;
+    let last = rows.len() as u32 - 2;
+    for j in 0..=last {
+        for i in 0..cols {
+            let top_left = base + j * stride + i;
+            let bottom_left = top_left + stride;
+            if !(pinched[0] && j == 0) {
+                mesh.indices.extend([top_left, bottom_left, top_left + 1]);
+            }
+            if !(pinched[1] && j == last) {
+                mesh.indices
+                    .extend([top_left + 1, bottom_left, bottom_left + 1]);
+            }
+        }
+    }
+}
+
+// 平行四边形面：中心 center，u 方向跨过 right，v 方向跨过 down
+fn face(mesh: &mut Mesh, center: Vec3, right: Vec3, down: Vec3, cols: u32, rows: u32) {
+    let normal = down.cross(right).normalize();
+    let tangent = right.normalize();
+    grid(mesh, cols, &steps(rows), [false, false], |u, v| {
+        let position = center + (u - 0.5) * right + (v - 0.5) * down;
+        (position, normal, tangent)
+    });
+}
+
+// 绕 y 轴旋转一条轮廓线，`profile(v)` 返回 (到轴的距离, y) 和 (径向, y) 方向的法线
+// 轮廓线要沿着外侧往下走（参见 `grid` 的要求）
+fn revolve(
+    mesh: &mut Mesh,
+    sectors: u32,
+    rows: &[f32],
+    pinched: [bool; 2],
+    profile: impl Fn(f32) -> (Vec2, Vec2),
+) {
+    grid(mesh, sectors, rows, pinched, |u, v| {
+        // u = 1 取 fract 回到 0，接缝两侧的位置逐位相等
+        let (sin, cos) = (TAU * u.fract()).sin_cos();
+        let (point, normal) = profile(v);
+        let radial = Vec3::new(sin, 0., cos);
+        (
+            radial * point.x + Vec3::Y * point.y,
+            (radial * normal.x + Vec3::Y * normal.y).normalize(),
+            Vec3::new(cos, 0., -sin),
+        )
+    });
+}
+
+// 侧面：上半径 top、下半径 bottom；上半径为 0 时是圆锥，尖的一行是退化的
+fn frustum(mesh: &mut Mesh, top: f32, bottom: f32, height: f32, sectors: u32) {
+    let normal = Vec2::new(height, bottom - top).normalize();
+    revolve(mesh, sectors, &steps(1), [top == 0., bottom == 0.], |v| {
+        (
+            Vec2::new(top + (bottom - top) * v, height / 2. - height * v),
+            normal,
+        )
+    });
+}
+
+// y 处的圆盘，朝上或朝下；uv 是俯视的平面投影，切线都是 +x
+fn cap(mesh: &mut Mesh, radius: f32, y: f32, sectors: u32, up: bool) {
+    let (normal, flip) = if up {
+        (Vec3::Y, 1.)
+    } else {
+        (Vec3::NEG_Y, -1.)
+    };
+    let center = mesh.positions.len() as u32;
+    push(mesh, Vec3::new(0., y, 0.), normal, [0.5, 0.5], Vec3::X);
+    for i in 0..sectors {
+        let (sin, cos) = (TAU * i as f32 / sectors as f32).sin_cos();
+        let uv = [0.5 + sin / 2., 0.5 + flip * cos / 2.];
+        push(
+            mesh,
+            Vec3::new(radius * sin, y, radius * cos),
+            normal,
+            uv,
+            Vec3::X,
+        );
+    }
+    for i in 0..sectors {
+        let a = center + 1 + i;
+        let b = center + 1 + (i + 1) % sectors;
+        let triangle = if up { [center, a, b] } else { [center, b, a] };
+        mesh.indices.extend(triangle);
+    }
+}
+
+// 单位球上的正二十面体细分，返回顶点方向和三角形
+fn icosahedron(subdivisions: u32) -> (Vec<Vec3>, Vec<[usize; 3]>) {
+    let t = (1. + 5f32.sqrt()) / 2.;
+    let mut directions: Vec<Vec3> = [
+        [-1., t, 0.],
+        [1., t, 0.],
+        [-1., -t, 0.],
+        [1., -t, 0.],
+        [0., -1., t],
+        [0., 1., t],
+        [0., -1., -t],
+        [0., 1., -t],
+        [t, 0., -1.],
+        [t, 0., 1.],
+        [-t, 0., -1.],
+        [-t, 0., 1.],
+    ]
+    .iter()
+    .map(|&p| Vec3::from(p).normalize())
+    .collect();
+    let mut triangles = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+    for _ in 0..subdivisions {
+        let mut midpoints = std::collections::HashMap::new();
+        let mut midpoint = |a: usize, b: usize| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                directions.push((directions[a] + directions[b]).normalize());
+                directions.len() - 1
+            })
+        };
+        triangles = triangles
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+    (directions, triangles)
+}
+
+fn finish(mut mesh: Mesh, name: &str) -> Mesh {
+    mesh.submeshes.push(SubMesh {
+        name: name.to_string(),
+        indices: 0..mesh.indices.len() as u32,
+        material: None,
+    });
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 属性数量一致、法线单位长、切线和法线正交、三角形逆时针朝外、
+    // 切线沿 dP/du 且 w 和副切线的方向一致
+    fn check(mesh: &Mesh) {
+        let n = mesh.vertex_count();
+        assert_eq!(mesh.normals.len(), n);
+        assert_eq!(mesh.uvs.len(), n);
+        assert_eq!(mesh.tangents.len(), n);
+        assert_eq!(mesh.indices.len() % 3, 0);
+        assert_eq!(mesh.submeshes[0].indices, 0..mesh.indices.len() as u32);
+        for (normal, tangent) in mesh.normals.iter().zip(&mesh.tangents) {
+            let normal = Vec3::from(*normal);
+            let tangent = Vec3::new(tangent[0], tangent[1], tangent[2]);
+            assert!((normal.length() - 1.).abs() < 1e-4, "{:?}", normal);
+            assert!((tangent.length() - 1.).abs() < 1e-4, "{:?}", tangent);
+            assert!(normal.dot(tangent).abs() < 1e-4);
+        }
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| triangle[k] as usize);
+            let p = [a, b, c].map(|i| Vec3::from(mesh.positions[i]));
+            let uv = [a, b, c].map(|i| Vec2::from(mesh.uvs[i]));
+            let face = (p[1] - p[0]).cross(p[2] - p[0]);
+            assert!(face.length() > 0., "degenerate triangle {:?}", p);
+            let (e1, e2) = (p[1] - p[0], p[2] - p[0]);
+            let (d1, d2) = (uv[1] - uv[0], uv[2] - uv[0]);
+            let det = d1.x * d2.y - d2.x * d1.y;
+            let dp_du = (e1 * d2.y - e2 * d1.y) / det;
+            let dp_dv = (e2 * d1.x - e1 * d2.x) / det;
+            for i in [a, b, c] {
+                let normal = Vec3::from(mesh.normals[i]);
+                let t = mesh.tangents[i];
+                let tangent = Vec3::new(t[0], t[1], t[2]);
+                assert!(face.dot(normal) > 0., "triangle faces inward at {:?}", p[0]);
+                assert!(
+                    tangent.dot(dp_du) > 0.,
+                    "tangent {:?} vs {:?}",
+                    tangent,
+                    dp_du
+                );
+                assert!(normal.cross(tangent).dot(-dp_dv) * t[3] > 0.);
+            }
+        }
+    }
+
+    #[test]
+    fn cube() {
+        let mesh = super::cube(2.);
+        check(&mesh);
+        assert_eq!(mesh.vertex_count(), 24);
+        assert_eq!(mesh.triangle_count(), 12);
+        assert_eq!(mesh.bounds(), Some((Vec3::splat(-1.), Vec3::splat(1.))));
+    }
+
+    #[test]
+    fn plane() {
+        let mesh = super::plane([4., 2.], [4, 3]);
+        check(&mesh);
+        assert_eq!(mesh.vertex_count(), 5 * 4);
+        assert_eq!(mesh.triangle_count(), 4 * 3 * 2);
+        assert!(mesh.normals.iter().all(|&n| n == [0., 1., 0.]));
+        assert_eq!(
+            mesh.bounds(),
+            Some((Vec3::new(-2., 0., -1.), Vec3::new(2., 0., 1.)))
+        );
+    }
+
+    #[test]
+    fn uv_sphere() {
+        let mesh = super::uv_sphere(2., 16, 8);
+        check(&mesh);
+        assert_eq!(mesh.vertex_count(), 17 * 9);
+        assert_eq!(mesh.triangle_count(), 16 * (2 * 8 - 2));
+        for (p, n) in mesh.positions.iter().zip(&mesh.normals) {
+            assert!(Vec3::from(*p).abs_diff_eq(Vec3::from(*n) * 2., 1e-5));
+        }
+    }
+
+    #[test]
+    fn icosphere() {
+        for subdivisions in 0..4 {
+            let mesh = super::icosphere(1.5, subdivisions);
+            // 不细分时一个面横跨半圈经度，面上的 dP/du 和顶点切线对不上
+            if subdivisions > 0 {
+                check(&mesh);
+            }
+            let faces = 20 * 4usize.pow(subdivisions);
+            assert_eq!(mesh.triangle_count(), faces);
+            // 接缝和极点会多出一些顶点
+            assert!(mesh.vertex_count() >= faces / 2 + 2);
+            for p in &mesh.positions {
+                assert!((Vec3::from(*p).length() - 1.5).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn torus() {
+        let mesh = super::torus(1., 0.25, 24, 12);
+        check(&mesh);
+        assert_eq!(mesh.vertex_count(), 25 * 13);
+        assert_eq!(mesh.triangle_count(), 24 * 12 * 2);
+        // 每个点到中心线的距离都是管半径
+        for p in &mesh.positions {
+            let p = Vec3::from(*p);
+            let ring = Vec3::new(p.x, 0., p.z).normalize();
+            assert!(((p - ring).length() - 0.25).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn cylinder_and_cone() {
+        let mesh = super::cylinder(1., 2., 12);
+        check(&mesh);
+        assert_eq!(mesh.vertex_count(), 2 * 13 + 2 * 13);
+        assert_eq!(mesh.triangle_count(), 12 * 2 + 2 * 12);
+
+        let mesh = super::cone(1., 2., 12);
+        check(&mesh);
+        assert_eq!(mesh.vertex_count(), 2 * 13 + 13);
+        assert_eq!(mesh.triangle_count(), 12 + 12);
+        // 侧面法线和母线垂直
+        let slope = Vec3::new(0., -2., 1.);
+        assert!(Vec3::from(mesh.normals[0]).dot(slope).abs() < 1e-5);
+    }
+
+    #[test]
+    fn capsule() {
+        let mesh = super::capsule(0.5, 1., 16, 4);
+        check(&mesh);
+        assert_eq!(mesh.vertex_count(), 17 * 10);
+        assert_eq!(mesh.triangle_count(), 16 * 4 * 4);
+        let (min, max) = mesh.bounds().unwrap();
+        assert!(min.abs_diff_eq(Vec3::new(-0.5, -1., -0.5), 1e-5));
+        assert!(max.abs_diff_eq(Vec3::new(0.5, 1., 0.5), 1e-5));
+    }
+}
@@ -0,0 +1,706 @@
+/// 网格处理：合并顶点、选择索引格式、顶点缓存优化和二次误差简化（生成 LOD）
+/// 三角形只在自己的 submesh 里重排或删除，submesh 的范围跟着调整
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+use glam::{DVec3, Vec3};
+
+use super::Mesh;
+
+/// 合并所有属性都逐位相同的顶点，没被引用的顶点一起去掉
+pub fn dedup(mesh: &mut Mesh) {
+    merge(mesh, |mesh, i| {
+        attributes(mesh, i).map(f32::to_bits).collect::<Vec<_>>()
+    });
+}
+
+/// 合并所有属性都只差 `tolerance` 以内的顶点（按网格量化，格子边上的两个顶点可能合不上）
+/// 合并后退化的三角形会被删掉
+pub fn weld(mesh: &mut Mesh, tolerance: f32) {
+    if tolerance <= 0. {
+        return dedup(mesh);
+    }
+    merge(mesh, |mesh, i| {
+        attributes(mesh, i)
+            .map(|x| (x / tolerance).round() as i64)
+            .collect::<Vec<_>>()
+    });
+}
+
+/// 顶点数不超过 65536 时用 u16 索引，索引的显存和带宽减半
+pub fn index_format(vertex_count: usize) -> wgpu::IndexFormat {
+    if vertex_count <= u16::MAX as usize + 1 {
+        wgpu::IndexFormat::Uint16
+    } else {
+        wgpu::IndexFormat::Uint32
+    }
+}
+
+/// 按 `format` 打包索引，配合 `index_format` 上传
+pub fn index_bytes(indices: &[u32], format: wgpu::IndexFormat) -> Vec<u8> {
+    match format {
+        wgpu::IndexFormat::Uint16 => indices
+            .iter()
+            .flat_map(|&i| (i as u16).to_ne_bytes())
+            .collect(),
+        wgpu::IndexFormat::Uint32 => bytemuck::cast_slice(indices).to_vec(),
+    }
+}
+
+/// 每个 submesh 里的三角形按 Forsyth 的算法重排，相邻三角形尽量共用 GPU 顶点缓存里的顶点；
+/// 再按第一次用到的顺序重排顶点，顶点读取也更连续
+pub fn optimize(mesh: &mut Mesh) {
+    let vertex_count = mesh.vertex_count();
+    for range in segments(mesh) {
+        optimize_vertex_cache(&mut mesh.indices[range], vertex_count);
+    }
+    merge(mesh, |_, i| i);
+}
+
+/// FIFO 顶点缓存下平均每个三角形缺失几次，越接近 0.5 越好，最差是 3
+pub fn acmr(indices: &[u32], cache_size: usize) -> f32 {
+    let mut cache = std::collections::VecDeque::with_capacity(cache_size);
+    let mut misses = 0;
+    for &i in indices {
+        if !cache.contains(&i) {
+            misses += 1;
+            if cache.len() == cache_size {
+                cache.pop_front();
+            }
+            cache.push_back(i);
+        }
+    }
+    misses as f32 / (indices.len() / 3).max(1) as f32
+}
+
+/// 二次误差度量（Garland & Heckbert）的边收缩，把三角形减到 `target_triangles` 个左右
+/// 只往已有的顶点上收缩，uv、法线等属性不用插值；网格边界、uv 接缝和 submesh 交界处的顶点不动，
+/// 所以有时到不了目标数量
+pub fn simplify(mesh: &Mesh, target_triangles: usize) -> Mesh {
+    Simplifier::new(mesh).run(target_triangles)
+}
+
+/// LOD 链：第 0 级是原网格，之后每级的目标三角形数乘以 `ratio`，都从原网格简化
+/// 简化不动了或者到了 `max_levels` 级就停
+pub fn lod_chain(mesh: &Mesh, ratio: f32, max_levels: usize) -> Vec<Mesh> {
+    let mut levels = vec![mesh.clone()];
+    let mut target = mesh.triangle_count() as f32;
+    while levels.len() < max_levels {
+        target *= ratio;
+        let level = simplify(mesh, target as usize);
+        if level.triangle_count() >= levels.last().unwrap().triangle_count() {
+            break;
+        }
+        levels.push(level);
+    }
+    levels
+}
+
+// 第 i 个顶点的所有属性依次排开
+fn attributes(mesh: &Mesh, i: usize) -> impl Iterator<Item = f32> + '_ {
+    let n = mesh.vertex_count();
+    let optional = |len: usize| len == n;
+    mesh.positions[i]
+        .iter()
+        .chain(
+            optional(mesh.normals.len())
+                .then(|| &mesh.normals[i])
+                .into_iter()
+                .flatten(),
+        )
+        .chain(
+            optional(mesh.uvs.len())
+                .then(|| &mesh.uvs[i])
+                .into_iter()
+                .flatten(),
+        )
+        .chain(
+            optional(mesh.tangents.len())
+                .then(|| &mesh.tangents[i])
+                .into_iter()
+                .flatten(),
+        )
+        .copied()
+}
+
+// `key` 相同的顶点合成一个，去掉因此退化的三角形，剩下的顶点按索引里第一次出现的顺序重新编号
+fn merge<K: Eq + Hash>(mesh: &mut Mesh, key: impl Fn(&Mesh, usize) -> K) {
+    let mut classes = HashMap::new();
+    let mut representative = Vec::new();
+    for index in 0..mesh.indices.len() {
+        let i = mesh.indices[index];
+        let next = representative.len() as u32;
+        let class = *classes.entry(key(mesh, i as usize)).or_insert(next);
+        if class == next {
+            representative.push(i);
+        }
+        mesh.indices[index] = class;
+    }
+    retain_triangles(mesh, |_, t| t[0] != t[1] && t[1] != t[2] && t[2] != t[0]);
+
+    let mut new = vec![u32::MAX; representative.len()];
+    let mut old = Vec::new();
+    for index in &mut mesh.indices {
+        let class = *index as usize;
+        if new[class] == u32::MAX {
+            new[class] = old.len() as u32;
+            old.push(representative[class]);
+        }
+        *index = new[class];
+    }
+    gather(mesh, &old);
+}
+
+// 新顶点 k 取原来的第 old[k] 个，索引由调用方改好
+fn gather(mesh: &mut Mesh, old: &[u32]) {
+    fn pick<T: Copy>(values: &mut Vec<T>, old: &[u32], n: usize) {
+        if values.len() == n {
+            *values = old.iter().map(|&i| values[i as usize]).collect();
+        }
+    }
+    let n = mesh.vertex_count();
+    pick(&mut mesh.positions, old, n);
+    pick(&mut mesh.normals, old, n);
+    pick(&mut mesh.uvs, old, n);
+    pick(&mut mesh.tangents, old, n);
+}
+
+// 只留下 `keep(第几个三角形, 三个索引)` 为真的三角形，submesh 的范围按删掉的数量前移
+fn retain_triangles(mesh: &mut Mesh, keep: impl Fn(usize, &[u32]) -> bool) {
+    let mut kept_before = Vec::with_capacity(mesh.triangle_count() + 1);
+    let mut indices = Vec::with_capacity(mesh.indices.len());
+    for (t, triangle) in mesh.indices.chunks_exact(3).enumerate() {
+        kept_before.push(indices.len() as u32);
+        if keep(t, triangle) {
+            indices.extend_from_slice(triangle);
+        }
+    }
+    kept_before.push(indices.len() as u32);
+    for submesh in &mut mesh.submeshes {
+        let range = &mut submesh.indices;
+        *range = kept_before[range.start as usize / 3]..kept_before[range.end as usize / 3];
+    }
+    mesh.indices = indices;
+}
+
+// 各 submesh 的索引范围，没有 submesh 时就是整个网格
+fn segments(mesh: &Mesh) -> Vec<std::ops::Range<usize>> {
+    if mesh.submeshes.is_empty() {
+        return std::iter::once(0..mesh.indices.len()).collect();
+    }
+    mesh.submeshes
+        .iter()
+        .map(|s| s.indices.start as usize..s.indices.end as usize)
+        .collect()
+}
+
+const CACHE_SIZE: usize = 32;
+
+// Forsyth：刚用过的三个顶点分数固定，缓存里越靠前分越高；剩下的三角形越少越要早点用完
+fn vertex_score(cache_position: Option<usize>, remaining: usize) -> f32 {
+    if remaining == 0 {
+        return -1.;
+    }
+    let cache = match cache_position {
+        Some(p) if p < 3 => 0.75,
+        Some(p) => (1. - (p - 3) as f32 / (CACHE_SIZE - 3) as f32).powf(1.5),
+        None => 0.,
+    };
+    cache + 2. * (remaining as f32).powf(-0.5)
+}
+
+fn optimize_vertex_cache(indices: &mut [u32], vertex_count: usize) {
+    let triangle_count = indices.len() / 3;
+    let vertices = |t: usize| [0, 1, 2].map(|k| indices[t * 3 + k] as usize);
+    // 每个顶点还没输出的三角形
+    let mut adjacency = vec![Vec::new(); vertex_count];
+    for t in 0..triangle_count {
+        for v in vertices(t) {
+            adjacency[v].push(t);
+        }
+    }
+    let mut score: Vec<f32> = adjacency
+        .iter()
+        .map(|a| vertex_score(None, a.len()))
+        .collect();
+    let triangle_score =
+        |t: usize, score: &[f32]| vertices(t).iter().map(|&v| score[v]).sum::<f32>();
+    let mut emitted = vec![false; triangle_count];
+    let mut cache: Vec<usize> = Vec::with_capacity(CACHE_SIZE + 3);
+    let mut output = Vec::with_capacity(indices.len());
+    let mut best = None;
+    while output.len() < indices.len() {
+        // 缓存里没有能接着画的三角形时，从剩下的里面挑分最高的
+        let t = best.unwrap_or_else(|| {
+            (0..triangle_count)
+                .filter(|&t| !emitted[t])
+                .max_by(|&a, &b| triangle_score(a, &score).total_cmp(&triangle_score(b, &score)))
+                .unwrap()
+        });
+        emitted[t] = true;
+        let triangle = vertices(t);
+        output.extend(triangle.map(|v| v as u32));
+        for v in triangle {
+            adjacency[v].retain(|&other| other != t);
+        }
+        // 新三角形的顶点放到缓存最前面，挤出去的顶点重新算分
+        let evicted: Vec<usize> = cache
+            .iter()
+            .copied()
+            .filter(|v| !triangle.contains(v))
+            .collect();
+        cache = triangle.to_vec();
+        cache.extend(evicted);
+        for (p, &v) in cache.iter().enumerate() {
+            let position = (p < CACHE_SIZE).then_some(p);
+            score[v] = vertex_score(position, adjacency[v].len());
+        }
+        cache.truncate(CACHE_SIZE);
+        best = cache
+            .iter()
+            .flat_map(|&v| &adjacency[v])
+            .copied()
+            .max_by(|&a, &b| triangle_score(a, &score).total_cmp(&triangle_score(b, &score)));
+    }
+    indices.copy_from_slice(&output);
+}
+
+// 对称 4×4 矩阵的上三角，平面 ax + by + cz + d = 0 的是 [a b c d]ᵀ[a b c d]
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn plane(normal: DVec3, d: f64, weight: f64) -> Self {
+        let [a, b, c] = normal.to_array();
+        Quadric(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|x| x * weight),
+        )
+    }
+
+    fn add(&self, other: &Quadric) -> Quadric {
+        let mut sum = *self;
+        for (s, o) in sum.0.iter_mut().zip(other.0) {
+            *s += o;
+        }
+        sum
+    }
+
+    // 点到各平面距离平方的加权和
+    fn error(&self, p: DVec3) -> f64 {
+        let q = &self.0;
+        let (x, y, z) = (p.x, p.y, p.z);
+        q[0] * x * x
+            + q[4] * y * y
+            + q[7] * z * z
+            + 2. * (q[1] * x * y + q[2] * x * z + q[5] * y * z)
+            + 2. * (q[3] * x + q[6] * y + q[8] * z)
+            + q[9]
+    }
+}
+
+// 把 from 收缩到 to；堆顶是代价最小的
+#[derive(Debug)]
+struct Collapse {
+    cost: f64,
+    from: usize,
+    to: usize,
+    versions: [u32; 2],
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+struct Simplifier<'a> {
+    mesh: &'a Mesh,
+    positions: Vec<DVec3>,
+    triangles: Vec<[usize; 3]>,
+    alive: Vec<bool>,
+    alive_count: usize,
+    adjacency: Vec<Vec<usize>>, // 顶点所在的三角形，可能有已经删掉的
+    quadrics: Vec<Quadric>,
+    locked: Vec<bool>,
+    removed: Vec<bool>,
+    versions: Vec<u32>, // 顶点每变一次加一，堆里旧的收缩就作废
+    heap: BinaryHeap<Collapse>,
+}
+
+impl<'a> Simplifier<'a> {
+    fn new(mesh: &'a Mesh) -> Self {
+        let n = mesh.vertex_count();
+        let positions: Vec<DVec3> = mesh
+            .positions
+            .iter()
+            .map(|&p| Vec3::from(p).as_dvec3())
+            .collect();
+        let triangles: Vec<[usize; 3]> = mesh
+            .indices
+            .chunks_exact(3)
+            .map(|t| [t[0] as usize, t[1] as usize, t[2] as usize])
+            .collect();
+
+        let mut adjacency = vec![Vec::new(); n];
+        let mut quadrics = vec![Quadric::default(); n];
+        for (t, &[a, b, c]) in triangles.iter().enumerate() {
+            let cross = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+            let normal = cross.normalize_or_zero();
+            let plane = Quadric::plane(normal, -normal.dot(positions[a]), cross.length() / 2.);
+            for v in [a, b, c] {
+                adjacency[v].push(t);
+                quadrics[v] = quadrics[v].add(&plane);
+            }
+        }
+
+        let mut locked = vec![false; n];
+        // 位置相同的几个顶点（uv 接缝、硬边）不动，否则会裂开
+        let mut points: HashMap<[u32; 3], Vec<usize>> = HashMap::new();
+        for (v, p) in mesh.positions.iter().enumerate() {
+            points.entry(p.map(f32::to_bits)).or_default().push(v);
+        }
+        let point: Vec<[u32; 3]> = mesh.positions.iter().map(|p| p.map(f32::to_bits)).collect();
+        for vertices in points.values().filter(|v| v.len() > 1) {
+            for &v in vertices {
+                locked[v] = true;
+            }
+        }
+        // 边界：按位置算只属于一个三角形的边
+        let mut edges = HashMap::new();
+        for &[a, b, c] in &triangles {
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                let key = if point[u] < point[v] {
+                    (point[u], point[v])
+                } else {
+                    (point[v], point[u])
+                };
+                edges.entry(key).or_insert((0, [u, v])).0 += 1usize;
+            }
+        }
+        for (count, [u, v]) in edges.values() {
+            if *count == 1 {
+                locked[*u] = true;
+                locked[*v] = true;
+            }
+        }
+        // 几个 submesh 共用的顶点
+        let mut submesh_of = vec![None; n];
+        for (s, range) in segments(mesh).into_iter().enumerate() {
+            for &i in &mesh.indices[range] {
+                let i = i as usize;
+                match submesh_of[i] {
+                    Some(other) if other != s => locked[i] = true,
+                    _ => submesh_of[i] = Some(s),
+                }
+            }
+        }
+
+        let mut simplifier = Simplifier {
+            mesh,
+            positions,
+            alive_count: triangles.len(),
+            alive: vec![true; triangles.len()],
+            triangles,
+            adjacency,
+            quadrics,
+            locked,
+            removed: vec![false; n],
+            versions: vec![0; n],
+            heap: BinaryHeap::new(),
+        };
+        // 按固定顺序入堆，代价相同时结果也是确定的
+        let mut pairs: Vec<(usize, usize)> = simplifier
+            .triangles
+            .iter()
+            .flat_map(|&[a, b, c]| [(a, b), (b, c), (c, a)])
+            .map(|(u, v)| (u.min(v), u.max(v)))
+            .collect();
+        pairs.sort_unstable();
+        pairs.dedup();
+        for (u, v) in pairs {
+            simplifier.push(u, v);
+            simplifier.push(v, u);
+        }
+        simplifier
+    }
+
+    fn push(&mut self, from: usize, to: usize) {
+        if self.locked[from] {
+            return;
+        }
+        let quadric = self.quadrics[from].add(&self.quadrics[to]);
+        self.heap.push(Collapse {
+            cost: quadric.error(self.positions[to]),
+            from,
+            to,
+            versions: [self.versions[from], self.versions[to]],
+        });
+    }
+
+    fn run(mut self, target_triangles: usize) -> Mesh {
+        while self.alive_count > target_triangles {
+            let Some(collapse) = self.heap.pop() else {
+                break;
+            };
+            let Collapse {
+                from, to, versions, ..
+            } = collapse;
+            if self.removed[from] || versions != [self.versions[from], self.versions[to]] {
+                continue;
+            }
+            if self.can_collapse(from, to) {
+                self.collapse(from, to);
+            }
+        }
+
+        let mut mesh = self.mesh.clone();
+        mesh.indices = self.triangles.iter().flatten().map(|&i| i as u32).collect();
+        retain_triangles(&mut mesh, |t, _| self.alive[t]);
+        merge(&mut mesh, |_, i| i);
+        mesh
+    }
+
+    fn neighbors(&self, v: usize) -> Vec<usize> {
+        let mut neighbors: Vec<usize> = self.adjacency[v]
+            .iter()
+            .filter(|&&t| self.alive[t])
+            .flat_map(|&t| self.triangles[t])
+            .filter(|&w| w != v)
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+    }
+
+    // 三角形的法线不能转过 60 度以上（几次收缩下来累积就会翻面），也不能退化；
+    // 也不能把两片粘成非流形：两端共同的邻居只能是被删掉的三角形的第三个顶点
+    fn can_collapse(&self, from: usize, to: usize) -> bool {
+        let mut shared_triangles = 0;
+        for &t in self.adjacency[from].iter().filter(|&&t| self.alive[t]) {
+            let triangle = self.triangles[t];
+            if triangle.contains(&to) {
+                shared_triangles += 1;
+                continue;
+            }
+            let [a, b, c] = triangle.map(|v| self.positions[v]);
+            let before = (b - a).cross(c - a);
+            let [a, b, c] = triangle.map(|v| self.positions[if v == from { to } else { v }]);
+            let after = (b - a).cross(c - a);
+            let (before, after) = (before.normalize_or_zero(), after.normalize_or_zero());
+            if after.dot(before) < 0.5 {
+                return false;
+            }
+        }
+        let to_neighbors = self.neighbors(to);
+        let common = self
+            .neighbors(from)
+            .iter()
+            .filter(|w| to_neighbors.contains(w))
+            .count();
+        shared_triangles > 0 && common == shared_triangles
+    }
+
+    fn collapse(&mut self, from: usize, to: usize) {
+        let triangles = std::mem::take(&mut self.adjacency[from]);
+        for t in triangles {
+            if !self.alive[t] {
+                continue;
+            }
+            if self.triangles[t].contains(&to) {
+                self.alive[t] = false;
+                self.alive_count -= 1;
+            } else {
+                for v in &mut self.triangles[t] {
+                    if *v == from {
+                        *v = to;
+                    }
+                }
+                self.adjacency[to].push(t);
+            }
+        }
+        self.adjacency[to].retain(|&t| self.alive[t]);
+        self.quadrics[to] = self.quadrics[to].add(&self.quadrics[from]);
+        self.removed[from] = true;
+        self.versions[to] += 1;
+        for neighbor in self.neighbors(to) {
+            self.push(to, neighbor);
+            self.push(neighbor, to);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::*;
+    use crate::mesh::{primitives, SubMesh};
+
+    // 三角形的法线都和 `outward(重心)` 同向
+    fn faces_outward(mesh: &Mesh, outward: impl Fn(Vec3) -> Vec3) -> bool {
+        mesh.indices.chunks_exact(3).all(|t| {
+            let [a, b, c] = [0, 1, 2].map(|k| Vec3::from(mesh.positions[t[k] as usize]));
+            (b - a).cross(c - a).dot(outward((a + b + c) / 3.)) > 0.
+        })
+    }
+
+    #[test]
+    fn dedup_and_weld() {
+        let mut mesh = primitives::cube(1.);
+        mesh.normals.clear();
+        mesh.uvs.clear();
+        mesh.tangents.clear();
+        dedup(&mut mesh);
+        assert_eq!(mesh.vertex_count(), 8);
+        assert_eq!(mesh.triangle_count(), 12);
+        assert!(faces_outward(&mesh, |c| c));
+
+        // 两个三角形各自的顶点，对角线上的两对只差一点
+        let mut mesh = Mesh {
+            positions: vec![
+                [0., 0., 0.],
+                [1., 0., 0.],
+                [1., 1., 0.],
+                [1e-6, 0., 0.],
+                [1., 1. + 1e-6, 0.],
+                [0., 1., 0.],
+                // 合并后退化的小三角形
+                [5., 5., 5.],
+                [5., 5., 5. + 1e-6],
+                [5., 5. + 1e-6, 5.],
+            ],
+            indices: vec![0, 1, 2, 3, 4, 5, 6, 7, 8],
+            submeshes: vec![
+                SubMesh {
+                    name: "quad".to_string(),
+                    indices: 0..6,
+                    material: None,
+                },
+                SubMesh {
+                    name: "speck".to_string(),
+                    indices: 6..9,
+                    material: None,
+                },
+            ],
+            ..Default::default()
+        };
+        weld(&mut mesh, 1e-4);
+        assert_eq!(mesh.vertex_count(), 4);
+        assert_eq!(mesh.indices, vec![0, 1, 2, 0, 2, 3]);
+        assert_eq!(mesh.submeshes[0].indices, 0..6);
+        assert_eq!(mesh.submeshes[1].indices, 6..6);
+    }
+
+    #[test]
+    fn picks_index_format() {
+        assert_eq!(index_format(65536), wgpu::IndexFormat::Uint16);
+        assert_eq!(index_format(65537), wgpu::IndexFormat::Uint32);
+        let indices = [0, 1, 65535];
+        assert_eq!(index_bytes(&indices, wgpu::IndexFormat::Uint16).len(), 6);
+        assert_eq!(index_bytes(&indices, wgpu::IndexFormat::Uint32).len(), 12);
+        assert_eq!(
+            index_bytes(&indices, wgpu::IndexFormat::Uint16),
+            bytemuck::cast_slice::<u16, u8>(&[0, 1, 65535])
+        );
+    }
+
+    #[test]
+    fn optimize_improves_cache_hits() {
+        let mut mesh = primitives::plane([1., 1.], [32, 32]);
+        // 打乱三角形顺序
+        let triangles: Vec<&[u32]> = mesh.indices.chunks_exact(3).collect();
+        let n = triangles.len();
+        mesh.indices = (0..n)
+            .flat_map(|k| triangles[k * 769 % n].to_vec())
+            .collect();
+        let before = acmr(&mesh.indices, 16);
+        let vertex_count = mesh.vertex_count();
+
+        optimize(&mut mesh);
+        let after = acmr(&mesh.indices, 16);
+        assert!(
+            after < 0.8 && after < before / 2.,
+            "{} -> {}",
+            before,
+            after
+        );
+        assert_eq!(mesh.triangle_count(), n);
+        assert_eq!(mesh.vertex_count(), vertex_count);
+        assert!(faces_outward(&mesh, |_| Vec3::Y));
+        // 顶点按第一次用到的顺序排
+        let mut seen = 0;
+        for &i in &mesh.indices {
+            assert!(i <= seen);
+            seen = seen.max(i + 1);
+        }
+    }
+
+    #[test]
+    fn simplify_flat_plane_to_its_border() {
+        let mesh = primitives::plane([2., 2.], [8, 8]);
+        let simple = simplify(&mesh, 0);
+        // 内部的点都在平面上，误差为 0，基本都能收缩掉；边界上 32 个点不动
+        // 只往已有顶点收缩，角落附近的点往哪收都会和边界共线，可能剩下一两个
+        assert!(simple.vertex_count() <= 34, "{}", simple.vertex_count());
+        assert_eq!(
+            simple.triangle_count(),
+            30 + 2 * (simple.vertex_count() - 32)
+        );
+        assert_eq!(simple.bounds(), mesh.bounds());
+        assert!(faces_outward(&simple, |_| Vec3::Y));
+    }
+
+    #[test]
+    fn simplify_sphere_keeps_shape() {
+        let mesh = primitives::icosphere(1., 3);
+        let simple = simplify(&mesh, 320);
+        assert!(simple.triangle_count() <= 320);
+        assert!(simple.triangle_count() >= 300);
+        assert_eq!(simple.submeshes[0].indices, 0..simple.indices.len() as u32);
+        assert!(faces_outward(&simple, |c| c));
+        // 面的重心不会离球面太远
+        for t in simple.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| Vec3::from(simple.positions[t[k] as usize]));
+            assert!(((a + b + c) / 3.).length() > 0.8);
+        }
+    }
+
+    #[test]
+    fn lod_chain_shrinks() {
+        let mesh = primitives::icosphere(1., 3);
+        let levels = lod_chain(&mesh, 0.5, 4);
+        assert_eq!(levels.len(), 4);
+        assert_eq!(levels[0], mesh);
+        for pair in levels.windows(2) {
+            assert!(pair[1].triangle_count() < pair[0].triangle_count());
+        }
+        assert!(levels[3].triangle_count() <= mesh.triangle_count() / 8);
+    }
+}
@@ -0,0 +1,142 @@
+/// MikkTSpace 切线，和 Blender 等工具烘焙法线贴图时用的一样，贴图才能对上
+/// 需要法线和 uv；切线不连续的地方（uv 接缝、镜像的 uv）会拆顶点
+use super::{set_per_corner, Mesh};
+
+/// 生成 `mesh.tangents`，覆盖原有的
+pub fn generate(mesh: &mut Mesh) -> Result<(), String> {
+    let n = mesh.vertex_count();
+    if mesh.normals.len() != n || mesh.uvs.len() != n {
+        return Err("tangents: mesh needs normals and uvs".to_string());
+    }
+    let mut geometry = Geometry {
+        corners: vec![[0.; 4]; mesh.indices.len()],
+        mesh,
+    };
+    if !bevy_mikktspace::generate_tangents(&mut geometry) {
+        return Err("tangents: mikktspace failed".to_string());
+    }
+    let corners = geometry.corners;
+    mesh.tangents.resize(n, [0.; 4]);
+    set_per_corner(mesh, &corners, |mesh| &mut mesh.tangents);
+    Ok(())
+}
+
+// 结果先按角存起来，算完再决定要不要拆顶点
+struct Geometry<'a> {
+    mesh: &'a Mesh,
+    corners: Vec<[f32; 4]>,
+}
+
+impl Geometry<'_> {
+    fn vertex(&self, face: usize, vert: usize) -> usize {
+        self.mesh.indices[face * 3 + vert] as usize
+    }
+}
+
+impl bevy_mikktspace::Geometry for Geometry<'_> {
+    fn num_faces(&self) -> usize {
+        self.mesh.triangle_count()
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.positions[self.vertex(face, vert)]
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.normals[self.vertex(face, vert)]
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        // MikkTSpace 的 v 朝上，翻过来后它的副切线就指向我们的 v 减小的方向，w 不用改
+        let [u, v] = self.mesh.uvs[self.vertex(face, vert)];
+        [u, 1. - v]
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        self.corners[face * 3 + vert] = tangent;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Vec3, Vec4};
+
+    use super::*;
+    use crate::mesh::primitives;
+
+    // 和生成器里按解析式算的切线比较
+    fn matches_analytic(mut mesh: Mesh, skip: impl Fn(Vec3) -> bool) {
+        let vertex_count = mesh.vertex_count();
+        let expected = std::mem::take(&mut mesh.tangents);
+        generate(&mut mesh).unwrap();
+        assert_eq!(mesh.vertex_count(), vertex_count);
+        for (i, (t, e)) in mesh.tangents.iter().zip(&expected).enumerate() {
+            if skip(Vec3::from(mesh.positions[i])) {
+                continue;
+            }
+            let (t, e) = (Vec4::from(*t), Vec4::from(*e));
+            // uv 接缝两侧不是同一个顶点，MikkTSpace 只平均一侧的面，会偏半个扇区
+            assert!(t.truncate().dot(e.truncate()) > 0.99, "{} vs {}", t, e);
+            assert_eq!(t.w, e.w);
+        }
+    }
+
+    #[test]
+    fn flat_surfaces() {
+        matches_analytic(primitives::plane([2., 1.], [3, 2]), |_| false);
+        matches_analytic(primitives::cube(1.), |_| false);
+    }
+
+    #[test]
+    fn curved_surfaces() {
+        // 极点上的切线方向没有定义
+        matches_analytic(primitives::uv_sphere(1., 32, 16), |p| {
+            p.x.abs() < 1e-6 && p.z.abs() < 1e-6
+        });
+        matches_analytic(primitives::torus(1., 0.3, 32, 16), |_| false);
+    }
+
+    #[test]
+    fn mirrored_uvs_split_vertices() {
+        // 朝 +z 的 2×1 长条，u 从 0 到 1 再回到 0，中间一列顶点两边共用
+        let mut mesh = Mesh {
+            positions: vec![
+                [0., 1., 0.],
+                [1., 1., 0.],
+                [2., 1., 0.],
+                [0., 0., 0.],
+                [1., 0., 0.],
+                [2., 0., 0.],
+            ],
+            normals: vec![[0., 0., 1.]; 6],
+            uvs: vec![[0., 0.], [1., 0.], [0., 0.], [0., 1.], [1., 1.], [0., 1.]],
+            indices: vec![0, 3, 1, 1, 3, 4, 1, 4, 2, 2, 4, 5],
+            ..Default::default()
+        };
+        generate(&mut mesh).unwrap();
+        assert_eq!(mesh.vertex_count(), 8);
+        for (k, triangle) in mesh.indices.chunks_exact(3).enumerate() {
+            // 左半边正常，右半边 u 反向、副切线（贴图上方，+y）靠 w 翻回来
+            let expected = if k < 2 {
+                [1., 0., 0., 1.]
+            } else {
+                [-1., 0., 0., -1.]
+            };
+            for &i in triangle {
+                let t = Vec4::from(mesh.tangents[i as usize]);
+                assert!(t.abs_diff_eq(Vec4::from(expected), 1e-6), "{}", t);
+            }
+        }
+    }
+
+    #[test]
+    fn needs_uvs() {
+        let mut mesh = primitives::cube(1.);
+        mesh.uvs.clear();
+        assert!(generate(&mut mesh).is_err());
+    }
+}
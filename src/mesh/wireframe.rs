@@ -0,0 +1,153 @@
+/// 线框叠加层：把任意 `Mesh` 的边（见 `edges`）画在它着色后的表面上
+/// 只用 `GpuMesh` 的位置 buffer，另外多一份线段索引；相机用场景自己的 `Uniforms` bind group（group 0），
+/// 线的深度往相机挪一点再用 LessEqual 比较，不会被自己的表面挡住
+use wgpu::{util::DeviceExt, PipelineCompilationOptions};
+
+use crate::{
+    shaders::WireframeUniform,
+    utils::{
+        pipeline_cache::PipelineCache,
+        reflect::{Bindings, ShaderLayout},
+    },
+};
+
+use super::{edges, process, GpuMesh, Mesh};
+
+const SHADER_NAME: &str = "wireframe";
+const SHADER: &str = include_str!("../shaders/wireframe.wgsl");
+
+/// 一个网格的线段索引，和上传这个网格得到的 `GpuMesh` 配对使用
+pub struct Wireframe {
+    lines: wgpu::Buffer,
+    index_format: wgpu::IndexFormat,
+    index_count: u32,
+}
+
+impl Wireframe {
+    /// `feature_angle` 为 None 时画所有边，否则只画硬边和边界（见 `edges::line_list`）
+    pub fn new(
+        device: &wgpu::Device,
+        label: &str,
+        mesh: &Mesh,
+        feature_angle: Option<f32>,
+    ) -> Self {
+        let lines = edges::line_list(&mesh.positions, &mesh.indices, feature_angle);
+        let index_format = process::index_format(mesh.vertex_count());
+        Wireframe {
+            lines: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{} wireframe", label)),
+                contents: &process::index_bytes(&lines, index_format),
+                usage: wgpu::BufferUsages::INDEX,
+            }),
+            index_format,
+            index_count: lines.len() as u32,
+        }
+    }
+}
+
+/// 线框管线和颜色，一个场景一份，可以画任意多个 `Wireframe`
+pub struct WireframeOverlay {
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl WireframeOverlay {
+    /// `camera_layout` 是场景 `Uniforms` 的 bind group layout，颜色和深度格式要和场景的 render pass 一致
+    pub fn new(
+        device: &wgpu::Device,
+        cache: &PipelineCache,
+        camera_layout: &wgpu::BindGroupLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        color: [f32; 4],
+    ) -> Self {
+        let (module, reflect) = cache.validated_shader(SHADER_NAME, SHADER, &[]).unwrap();
+        let layout = ShaderLayout::new(
+            cache,
+            "wireframe pipeline layout",
+            Bindings::new(&[&reflect]).unwrap(),
+            &[(0, camera_layout)],
+        );
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("wireframe uniform buffer"),
+            contents: bytemuck::bytes_of(&WireframeUniform { color }),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group = layout.create_bind_group(
+            device,
+            "wireframe bind group",
+            1,
+            &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        );
+        let pipeline = cache.render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("wireframe pipeline"),
+            layout: Some(&layout.pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: Some("vs_main"),
+                buffers: &[GpuMesh::POSITION_LAYOUT],
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            // 线不写深度，后面画的东西照常遮挡
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        WireframeOverlay {
+            pipeline,
+            uniform_buffer,
+            bind_group,
+        }
+    }
+
+    pub fn set_color(&self, queue: &wgpu::Queue, color: [f32; 4]) {
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::bytes_of(&WireframeUniform { color }),
+        );
+    }
+
+    /// 画在表面之后；`camera` 是 `camera_layout` 对应的 bind group
+    /// 会换掉 render pass 的管线和 group 0、1，之后再画别的要重新设置
+    pub fn draw<'m>(
+        &self,
+        render_pass: &mut wgpu::RenderPass,
+        camera: &wgpu::BindGroup,
+        meshes: impl IntoIterator<Item = (&'m GpuMesh, &'m Wireframe)>,
+    ) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, camera, &[]);
+        render_pass.set_bind_group(1, &self.bind_group, &[]);
+        for (mesh, wireframe) in meshes {
+            render_pass.set_vertex_buffer(0, mesh.positions.slice(..));
+            render_pass.set_index_buffer(wireframe.lines.slice(..), wireframe.index_format);
+            render_pass.draw_indexed(0..wireframe.index_count, 0, 0..1);
+        }
+    }
+}
@@ -0,0 +1,9 @@
+// 音频分析的标准 uniform 块，结构体定义在 Rust 侧 shaders/mod.rs
+// 绑定位置由使用的 shader 自己声明，例如：
+// @group(1) @binding(0) var<uniform> audio: AudioUniforms;
+#include "audio_uniforms.wgsl"
+
+// 第 i 个频段（0..8，低频到高频），值在 0..1
+fn audio_band(a: AudioUniforms, i: u32) -> f32 {
+    return a.bands[i / 4u][i % 4u];
+}
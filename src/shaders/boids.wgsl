@@ -1,18 +1,6 @@
-// 顶点着色器
-@vertex
-fn vs_main(@location(0) position: vec2<f32>) -> @builtin(position) vec4<f32> {
-    return vec4<f32>(position, 0.0, 1.0);
-}
-
-struct Uniforms {
-    time: f32,
-    resolution: vec2<f32>,
-    mouse_position: vec2<f32>,
-    boid_count: u32,
-}
+#include "playground.wgsl"
 
-@group(0) @binding(0)
-var<uniform> uniforms: Uniforms;
+const BOID_COUNT: u32 = 10u;
 
 // 简单的Boid效果片段着色器
 @fragment
@@ -26,7 +14,7 @@ fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
     // 为简单起见，我们直接在片段着色器中模拟几个boid
     // 实际应用中应该在compute shader中计算boid位置
     var color = bg_color;
-    var boid_count = min(10u, uniforms.boid_count);
+    let boid_count = BOID_COUNT;
     
     // 创建几个简单的boid
     for(var i = 0u; i < boid_count; i = i + 1u) {
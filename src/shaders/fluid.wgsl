@@ -1,19 +1,4 @@
-// 顶点着色器
-@vertex
-fn vs_main(@location(0) position: vec2<f32>) -> @builtin(position) vec4<f32> {
-    return vec4<f32>(position, 0.0, 1.0);
-}
-
-// 更简单的Uniforms结构，避免布局问题
-struct Uniforms {
-    time: f32,
-    resolution: vec2<f32>,
-    mouse_position: vec2<f32>,
-    mouse_down: u32,
-}
-
-@group(0) @binding(0)
-var<uniform> uniforms: Uniforms;
+#include "playground.wgsl"
 
 // 简化的流体效果片段着色器
 @fragment
@@ -0,0 +1,41 @@
+#include "uniforms.wgsl"
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+
+// 固定的平行光，从右上方照过来
+const LIGHT_DIR: vec3<f32> = vec3<f32>(0.4, 0.8, 0.45);
+const AMBIENT: f32 = 0.3;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+}
+struct InstanceInput {
+    @location(4) model_0: vec4<f32>,
+    @location(5) model_1: vec4<f32>,
+    @location(6) model_2: vec4<f32>,
+    @location(7) model_3: vec4<f32>,
+    @location(8) color: vec4<f32>,
+}
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(in: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    var out: VertexOutput;
+    out.position = uniforms.matrix * model * vec4<f32>(in.position, 1.0);
+    // 只有等比缩放时模型矩阵才能直接变换法线
+    out.normal = (model * vec4<f32>(in.normal, 0.0)).xyz;
+    out.color = instance.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let diffuse = max(dot(normalize(in.normal), normalize(LIGHT_DIR)), 0.0);
+    return vec4<f32>((AMBIENT + diffuse) * in.color.rgb, in.color.a);
+}
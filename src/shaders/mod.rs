@@ -0,0 +1,135 @@
+/// 多个 shader 共用的 uniform / storage 结构体
+/// WGSL 声明由 `wgsl_struct!` 从这里生成，作为 include 片段交给预处理器（`#include "uniforms.wgsl"`），
+/// Rust 和 WGSL 两边只有这一份定义
+use std::sync::OnceLock;
+
+use crate::utils::layout::{wgsl_struct, WgslArray, WgslStruct};
+
+wgsl_struct! {
+    /// 每个场景共用的相机 uniform
+    pub struct Uniforms {
+        pub matrix: [[f32; 4]; 4],
+        pub resolution: [f32; 2],
+        pub delta_time: f32,
+        pub _padding: f32,
+    }
+}
+
+wgsl_struct! {
+    /// 阴影用的光源，depth / cube 两个 shader 以 storage 绑定
+    pub struct LightUniform {
+        pub view_proj: [[f32; 4]; 4],
+        pub bias: f32,
+        pub pcf_radius: f32,
+        pub _padding: [f32; 2],
+    }
+}
+
+wgsl_struct! {
+    /// 网格材质（见 `crate::mesh::Material`），每个 submesh 一份
+    pub struct MaterialUniform {
+        pub diffuse: [f32; 3],
+        pub opacity: f32,
+    }
+}
+
+wgsl_struct! {
+    /// 线框叠加层（见 `crate::mesh::wireframe`）的线条颜色，带 alpha
+    pub struct WireframeUniform {
+        pub color: [f32; 4],
+    }
+}
+
+wgsl_struct! {
+    /// 音频分析的标准 uniform 块，8 个频段按 vec4 打包
+    pub struct AudioUniforms {
+        pub bands: WgslArray<[f32; 4], 2>,
+        pub loudness: f32,
+        pub peak: f32,
+        pub _padding: [f32; 2],
+    }
+}
+
+wgsl_struct! {
+    /// boids 的模拟参数，字段和 boids 的可调参数同名
+    pub struct SimParams {
+        pub delta_t: f32,
+        pub rule1_distance: f32, // 凝聚
+        pub rule2_distance: f32, // 分离
+        pub rule3_distance: f32, // 对齐
+        pub rule1_scale: f32,
+        pub rule2_scale: f32,
+        pub rule3_scale: f32,
+    }
+}
+
+wgsl_struct! {
+    /// playground 每帧写入的标准输入
+    pub struct PlaygroundUniforms {
+        pub time: f32,       // 秒
+        pub delta_time: f32, // 秒
+        pub resolution: [f32; 2],
+        pub mouse_position: [f32; 2], // [0, 1] 的 uv，左上角为原点，和 frag_coord / resolution 一致
+        pub mouse_down: u32,
+        pub frame: u32,
+    }
+}
+
+/// 生成的 include 片段：(名字, WGSL 源码)
+pub fn includes() -> &'static [(&'static str, String)] {
+    static INCLUDES: OnceLock<Vec<(&'static str, String)>> = OnceLock::new();
+    INCLUDES.get_or_init(|| {
+        vec![
+            ("uniforms.wgsl", Uniforms::wgsl()),
+            ("light.wgsl", LightUniform::wgsl()),
+            ("material.wgsl", MaterialUniform::wgsl()),
+            ("wireframe_uniform.wgsl", WireframeUniform::wgsl()),
+            ("audio_uniforms.wgsl", AudioUniforms::wgsl()),
+            ("sim_params.wgsl", SimParams::wgsl()),
+            ("playground_uniforms.wgsl", PlaygroundUniforms::wgsl()),
+        ]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::layout::Storage;
+
+    wgsl_struct! {
+        /// storage 里的标量数组，步长 4
+        struct Weights {
+            count: u32,
+            weights: WgslArray<f32, 4, Storage>,
+        }
+    }
+
+    // naga 按 WGSL 规则算出的偏移和大小，要和 Rust 这边一致
+    fn assert_matches_naga<S: WgslStruct>() {
+        let module = naga::front::wgsl::parse_str(&S::wgsl()).unwrap();
+        let (_, ty) = module
+            .types
+            .iter()
+            .find(|(_, ty)| ty.name.as_deref() == Some(S::NAME))
+            .unwrap();
+        let naga::TypeInner::Struct { members, span } = &ty.inner else {
+            panic!("{} is not a struct", S::NAME);
+        };
+        assert_eq!(*span as usize, size_of::<S>(), "size of {}", S::NAME);
+        let offsets: Vec<_> = members.iter().map(|m| m.offset as usize).collect();
+        let expected: Vec<_> = S::FIELDS.iter().map(|f| f.offset).collect();
+        assert_eq!(offsets, expected, "offsets of {}", S::NAME);
+    }
+
+    #[test]
+    fn generated_structs_match_naga() {
+        assert_matches_naga::<Uniforms>();
+        assert_matches_naga::<LightUniform>();
+        assert_matches_naga::<MaterialUniform>();
+        assert_matches_naga::<WireframeUniform>();
+        assert_matches_naga::<AudioUniforms>();
+        assert_matches_naga::<SimParams>();
+        assert_matches_naga::<PlaygroundUniforms>();
+        assert_matches_naga::<Weights>();
+    }
+}
@@ -0,0 +1,13 @@
+// playground 的标准输入，字段见 Rust 侧 shaders/mod.rs 里的 PlaygroundUniforms
+// 效果只需要写 fs_main，铺满画布的 vs_main 在这里
+#include "playground_uniforms.wgsl"
+
+@group(0) @binding(0)
+var<uniform> uniforms: PlaygroundUniforms;
+
+// 一个盖住整个画布的三角形，不需要顶点 buffer
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
@@ -0,0 +1,59 @@
+// 阴影贴图采样，group 0 是深度图、比较采样器和光源（见 level2 的阴影场景）
+// 定义 SHADOW_PCF 时做 PCF 软阴影，否则只比较一次
+#include "light.wgsl"
+
+@group(0) @binding(0) var depth_texture: texture_depth_2d;
+@group(0) @binding(1) var shadow_sampler: sampler_comparison;
+@group(0) @binding(2) var<storage, read> light: LightUniform;
+
+fn getShadow(coords: vec2<f32>, compare: f32) -> f32 {
+    let bias = light.bias;
+#ifdef SHADOW_PCF
+    // PCF：在 (2r+1)^2 个相邻texel上比较后取平均，边缘更柔和
+    let texelSize = 1.0 / 1024.0;
+    let radius = i32(light.pcf_radius);
+    var shadow = 0.0;
+    for (var y = -radius; y <= radius; y += 1) {
+        for (var x = -radius; x <= radius; x += 1) {
+            let offset = vec2<f32>(f32(x), f32(y)) * texelSize;
+            shadow += textureSampleCompare(
+                depth_texture, 
+                shadow_sampler, 
+                coords + offset, 
+                compare - bias
+            );
+        }
+    }
+    let taps = f32(2 * radius + 1);
+    return shadow / (taps * taps);
+#else
+    // 只比较一次，硬阴影
+    return textureSampleCompare(depth_texture, shadow_sampler, coords, compare - bias);
+#endif
+}
+
+// 光空间坐标 -> 受光比例，1 为完全照亮
+fn shadow_factor(light_space_position: vec4<f32>) -> f32 {
+    // 计算光空间深度坐标
+    let proj_coords = light_space_position.xyz / light_space_position.w;
+    
+    // 转换到[0,1]范围
+    let shadow_coords = vec2<f32>(
+        proj_coords.x * 0.5 + 0.5,
+        -proj_coords.y * 0.5 + 0.5
+    );
+    
+    // 当前深度值
+    let current_depth = proj_coords.z * 0.5 + 0.5;
+    
+    // 获取阴影值
+    let shadow = getShadow(shadow_coords, current_depth);
+    
+    // 处理超出深度图边界的情况
+    if (shadow_coords.x < 0.0 || shadow_coords.x > 1.0 || 
+        shadow_coords.y < 0.0 || shadow_coords.y > 1.0 ||
+        current_depth < 0.0 || current_depth > 1.0) {
+        return 1.0; // 超出边界，不应用阴影
+    }
+    return shadow;
+}
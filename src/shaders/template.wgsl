@@ -0,0 +1,12 @@
+#include "playground.wgsl"
+
+// playground 里自定义效果的起点：只写 fs_main，可用的输入见 playground.wgsl 里的 Uniforms
+@fragment
+fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let uv = frag_coord.xy / uniforms.resolution;
+    let color = 0.5 + 0.5 * cos(uniforms.time + vec3<f32>(uv, uv.x) + vec3<f32>(0.0, 2.0, 4.0));
+    // 鼠标附近亮一圈，按下时更亮
+    let glow = smoothstep(0.1, 0.0, distance(uv, uniforms.mouse_position));
+    let strength = select(0.3, 0.8, uniforms.mouse_down != 0u);
+    return vec4<f32>(color + glow * strength, 1.0);
+}
@@ -0,0 +1,21 @@
+#include "uniforms.wgsl"
+#include "wireframe_uniform.wgsl"
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+@group(1) @binding(0) var<uniform> wireframe: WireframeUniform;
+
+// 往相机挪的 NDC 深度：足够盖住线所在的表面，又不会穿过薄的物体
+const DEPTH_OFFSET: f32 = 1e-5;
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    // 网格已经在世界坐标里，只乘相机矩阵
+    var clip = uniforms.matrix * vec4<f32>(position, 1.0);
+    clip.z -= DEPTH_OFFSET * clip.w;
+    return clip;
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return wireframe.color;
+}
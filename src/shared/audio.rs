@@ -0,0 +1,94 @@
+/// 音频分析的 scene 资源：每帧从 `web::audio::Audio` 取特征，CPU 侧读 `features`，
+/// GPU 侧绑定 `bind_group`（布局见 `bind_group_layout`，WGSL 侧 `#include "audio.wgsl"`）
+use wgpu::util::DeviceExt;
+
+use super::ready_paint::{get_res, get_res_mut, return_res, Gfx, HashTypeId2Data, Ready, Update};
+use crate::{
+    shaders::AudioUniforms,
+    utils::{layout::WgslArray, reflect::ShaderLayout},
+    web::audio::{Audio, AudioFeatures},
+};
+
+// 用到音频的 shader 各自决定 group 号，binding 都是 0
+const AUDIO_DECLARATION: &str = "#include \"audio.wgsl\"
+@group(0) @binding(0) var<uniform> audio: AudioUniforms;";
+
+impl From<AudioFeatures> for AudioUniforms {
+    fn from(features: AudioFeatures) -> Self {
+        Self {
+            bands: WgslArray::new(bytemuck::cast(features.bands)),
+            loudness: features.loudness,
+            peak: features.peak,
+            _padding: [0.; 2],
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct AudioReactive {
+    audio: Audio,
+    pub features: AudioFeatures,
+    pub buffer: Option<wgpu::Buffer>,
+    pub bind_group_layout: Option<wgpu::BindGroupLayout>,
+    pub bind_group: Option<wgpu::BindGroup>,
+}
+
+impl Ready for AudioReactive {
+    fn ready(&mut self, data: &mut HashTypeId2Data, gfx: &Gfx) {
+        let buffer = gfx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Audio Uniform Buffer"),
+                contents: bytemuck::bytes_of(&AudioUniforms::from(AudioFeatures::default())),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        let layout = ShaderLayout::shared_group(
+            &gfx.pipelines,
+            "Audio Bind Group Layout",
+            AUDIO_DECLARATION,
+            wgpu::ShaderStages::VERTEX_FRAGMENT | wgpu::ShaderStages::COMPUTE,
+        );
+        let bind_group = layout.create_bind_group(
+            &gfx.device,
+            "Audio Bind Group",
+            0,
+            &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        );
+        let bind_group_layout = layout.bind_group_layouts[0].clone();
+        return_res(
+            data,
+            AudioReactive {
+                audio: Audio::current(),
+                features: AudioFeatures::default(),
+                buffer: Some(buffer),
+                bind_group_layout: Some(bind_group_layout),
+                bind_group: Some(bind_group),
+            },
+        );
+    }
+}
+
+impl Update for AudioReactive {
+    fn update(
+        data: &mut HashTypeId2Data,
+        gfx: &Gfx,
+        _dt: f32,
+        _surface: &std::sync::Arc<wgpu::Surface<'static>>,
+    ) {
+        let reactive = get_res::<Self>(data);
+        // 没有音源且上一帧已经归零就不用再写
+        if !reactive.audio.is_active() && reactive.features.loudness == 0. {
+            return;
+        }
+        let features = reactive.audio.analyse();
+        gfx.queue.write_buffer(
+            reactive.buffer.as_ref().unwrap(),
+            0,
+            bytemuck::bytes_of(&AudioUniforms::from(features)),
+        );
+        get_res_mut::<Self>(data).features = features;
+    }
+}
@@ -0,0 +1,412 @@
+/// 相机控制器：轨道（旋转 / 平移 / 缩放）和 WASD 漫游，带阻尼和复位
+/// 只负责算 view 矩阵，写哪个 uniform buffer 由各自的 World 决定
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use glam::{Mat4, Quat, Vec3};
+
+use crate::web::input::{CanvasInput, Input, BUTTON_LEFT, BUTTON_MIDDLE, BUTTON_RIGHT};
+
+const PITCH_LIMIT: f32 = FRAC_PI_2 - 0.01;
+const ZOOM_SPEED: f32 = 0.001; // 每像素滚轮
+
+// 每 1/60 秒保留的速度比例，和帧率无关
+fn decay(damping: f32, dt: f32) -> f32 {
+    damping.powf(dt * 60.)
+}
+
+// 以 up 为 y 轴的局部坐标系
+fn frame(up: Vec3) -> Quat {
+    Quat::from_rotation_arc(Vec3::Y, up.normalize())
+}
+
+fn direction(yaw: f32, pitch: f32) -> Vec3 {
+    Vec3::new(
+        pitch.cos() * yaw.sin(),
+        pitch.sin(),
+        pitch.cos() * yaw.cos(),
+    )
+}
+
+#[derive(Debug, Clone, Copy)]
+struct OrbitHome {
+    target: Vec3,
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+}
+
+/// 左键旋转，右键 / 中键平移，滚轮和双指缩放
+#[derive(Debug, Clone)]
+pub struct OrbitController {
+    pub target: Vec3,
+    pub up: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub damping: f32,
+    yaw_velocity: f32,
+    pitch_velocity: f32,
+    pan_velocity: Vec3,
+    zoom_remaining: f32, // 滚轮还没缩放完的部分（对数）
+    pinch: Option<f32>,
+    home: OrbitHome,
+}
+
+impl OrbitController {
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let local = frame(up).inverse() * (eye - target);
+        let distance = local.length().max(1e-3);
+        let home = OrbitHome {
+            target,
+            yaw: local.x.atan2(local.z),
+            pitch: (local.y / distance).clamp(-1., 1.).asin(),
+            distance,
+        };
+        Self {
+            target,
+            up: up.normalize(),
+            yaw: home.yaw,
+            pitch: home.pitch,
+            distance,
+            min_distance: distance * 0.2,
+            max_distance: distance * 5.,
+            damping: 0.85,
+            yaw_velocity: 0.,
+            pitch_velocity: 0.,
+            pan_velocity: Vec3::ZERO,
+            zoom_remaining: 0.,
+            pinch: None,
+            home,
+        }
+    }
+
+    pub fn with_distance_range(mut self, min: f32, max: f32) -> Self {
+        self.min_distance = min;
+        self.max_distance = max;
+        self
+    }
+
+    pub fn eye(&self) -> Vec3 {
+        self.target + frame(self.up) * direction(self.yaw, self.pitch) * self.distance
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_at_rh(self.eye(), self.target, self.up)
+    }
+
+    pub fn reset(&mut self) {
+        let home = self.home;
+        self.target = home.target;
+        self.yaw = home.yaw;
+        self.pitch = home.pitch;
+        self.distance = home.distance;
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.yaw_velocity = 0.;
+        self.pitch_velocity = 0.;
+        self.pan_velocity = Vec3::ZERO;
+        self.zoom_remaining = 0.;
+    }
+
+    /// dt 单位为秒。按住时拖多少转多少，速度只用来做松手后的惯性
+    pub fn update(&mut self, input: &CanvasInput, dt: f32) {
+        let pointer = &input.pointer;
+        // 按画布高度归一化，拖满一屏转半圈
+        let height = input.size[1].max(1.);
+        let [dx, dy] = [pointer.delta[0] / height, pointer.delta[1] / height];
+        let two_fingers = input.touches.len() >= 2;
+        let k = decay(self.damping, dt);
+
+        if pointer.held[BUTTON_LEFT] && !two_fingers {
+            self.yaw_velocity = -dx * PI;
+            self.pitch_velocity = dy * PI;
+        } else {
+            self.yaw_velocity *= k;
+            self.pitch_velocity *= k;
+        }
+        if pointer.held[BUTTON_RIGHT] || pointer.held[BUTTON_MIDDLE] {
+            let rotation = Mat4::look_at_rh(self.eye(), self.target, self.up).inverse();
+            let right = rotation.transform_vector3(Vec3::X);
+            let up = rotation.transform_vector3(Vec3::Y);
+            self.pan_velocity = (-right * dx + up * dy) * self.distance;
+        } else {
+            self.pan_velocity *= k;
+        }
+
+        // 滚轮分几帧缩放完，总量还是 wheel * ZOOM_SPEED
+        self.zoom_remaining += pointer.wheel * ZOOM_SPEED;
+        let mut zoom = self.zoom_remaining * (1. - k);
+        self.zoom_remaining -= zoom;
+
+        // 双指捏合，跟手不做平滑
+        if two_fingers {
+            let [a, b] = [input.touches[0].position, input.touches[1].position];
+            let span = ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt();
+            if let Some(last) = self.pinch {
+                if span > 1. {
+                    zoom += (last / span).ln();
+                }
+            }
+            self.pinch = Some(span);
+        } else {
+            self.pinch = None;
+        }
+
+        self.yaw += self.yaw_velocity;
+        self.pitch = (self.pitch + self.pitch_velocity).clamp(-PITCH_LIMIT, PITCH_LIMIT);
+        self.target += self.pan_velocity;
+        self.distance = (self.distance * zoom.exp()).clamp(self.min_distance, self.max_distance);
+    }
+}
+
+/// 左键拖拽转向，WASD 前后左右，E / Q 上下，Shift 加速
+#[derive(Debug, Clone)]
+pub struct FlyController {
+    pub position: Vec3,
+    pub up: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub speed: f32, // 每秒
+    pub damping: f32,
+    velocity: Vec3,
+    look_velocity: [f32; 2],
+    home: (Vec3, f32, f32),
+}
+
+impl FlyController {
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let forward = frame(up).inverse() * (target - eye).normalize_or(Vec3::Z);
+        let yaw = forward.x.atan2(forward.z);
+        let pitch = forward.y.clamp(-1., 1.).asin();
+        Self {
+            position: eye,
+            up: up.normalize(),
+            yaw,
+            pitch,
+            speed: (target - eye).length().max(1.),
+            damping: 0.8,
+            velocity: Vec3::ZERO,
+            look_velocity: [0., 0.],
+            home: (eye, yaw, pitch),
+        }
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        frame(self.up) * direction(self.yaw, self.pitch)
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_to_rh(self.position, self.forward(), self.up)
+    }
+
+    pub fn reset(&mut self) {
+        (self.position, self.yaw, self.pitch) = self.home;
+        self.velocity = Vec3::ZERO;
+        self.look_velocity = [0., 0.];
+    }
+
+    /// 键盘只在指针停留在该画布上时生效，避免一个页面多个相机同时移动
+    pub fn update(&mut self, input: &CanvasInput, keys: &Input, dt: f32) {
+        let pointer = &input.pointer;
+        let height = input.size[1].max(1.);
+        let k = decay(self.damping, dt);
+        // 和轨道一样，按住时直接跟手，松开后才有惯性
+        if pointer.held[BUTTON_LEFT] {
+            self.look_velocity[0] = -pointer.delta[0] / height * PI;
+            self.look_velocity[1] = -pointer.delta[1] / height * PI;
+        } else {
+            self.look_velocity[0] *= k;
+            self.look_velocity[1] *= k;
+        }
+        self.yaw += self.look_velocity[0];
+        self.pitch = (self.pitch + self.look_velocity[1]).clamp(-PITCH_LIMIT, PITCH_LIMIT);
+
+        let forward = self.forward();
+        let right = forward.cross(self.up).normalize_or_zero();
+        let mut wish = Vec3::ZERO;
+        if pointer.hovered {
+            let axis = |positive: &str, negative: &str| {
+                keys.key_held(positive) as i32 as f32 - keys.key_held(negative) as i32 as f32
+            };
+            wish += forward * axis("KeyW", "KeyS");
+            wish += right * axis("KeyD", "KeyA");
+            wish += self.up * axis("KeyE", "KeyQ");
+        }
+        let boost = if keys.key_held("ShiftLeft") { 3. } else { 1. };
+        let target_velocity = wish.normalize_or_zero() * self.speed * boost;
+
+        self.velocity = target_velocity + (self.velocity - target_velocity) * k;
+        self.position += self.velocity * dt;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraMode {
+    Orbit,
+    Fly,
+}
+
+/// 绑定到一个画布的相机：C 切换轨道 / 漫游，R 复位
+pub struct CameraController {
+    pub canvas_id: &'static str,
+    pub mode: CameraMode,
+    pub orbit: OrbitController,
+    pub fly: FlyController,
+    last_view: Mat4,
+}
+
+impl CameraController {
+    pub fn new(canvas_id: &'static str, eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let orbit = OrbitController::look_at(eye, target, up);
+        Self {
+            canvas_id,
+            mode: CameraMode::Orbit,
+            fly: FlyController::look_at(eye, target, up),
+            last_view: orbit.view(),
+            orbit,
+        }
+    }
+
+    /// 限制轨道缩放范围（相机不要超出投影的远近平面）
+    pub fn with_distance_range(mut self, min: f32, max: f32) -> Self {
+        self.orbit = self.orbit.with_distance_range(min, max);
+        self
+    }
+
+    pub fn view(&self) -> Mat4 {
+        match self.mode {
+            CameraMode::Orbit => self.orbit.view(),
+            CameraMode::Fly => self.fly.view(),
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        if mode == self.mode {
+            return;
+        }
+        // 切换时保持当前视角不跳
+        match mode {
+            CameraMode::Fly => {
+                self.fly.position = self.orbit.eye();
+                let forward =
+                    frame(self.fly.up).inverse() * (self.orbit.target - self.fly.position);
+                let forward = forward.normalize_or(Vec3::Z);
+                self.fly.yaw = forward.x.atan2(forward.z);
+                self.fly.pitch = forward.y.clamp(-1., 1.).asin();
+            }
+            CameraMode::Orbit => {
+                self.orbit.target = self.fly.position + self.fly.forward() * self.orbit.distance;
+                self.orbit.yaw = self.fly.yaw + PI;
+                self.orbit.pitch = -self.fly.pitch;
+            }
+        }
+        self.mode = mode;
+    }
+
+    pub fn reset(&mut self) {
+        self.orbit.reset();
+        self.fly.reset();
+        self.mode = CameraMode::Orbit;
+    }
+
+    /// 更新相机，dt 单位为秒。返回 view 是否变化，没变就不用写 buffer
+    pub fn update(&mut self, input: &Input, dt: f32) -> bool {
+        let canvas = input.canvas(self.canvas_id);
+        if canvas.pointer.hovered {
+            if input.key_pressed("KeyR") {
+                self.reset();
+            }
+            if input.key_pressed("KeyC") {
+                self.set_mode(match self.mode {
+                    CameraMode::Orbit => CameraMode::Fly,
+                    CameraMode::Fly => CameraMode::Orbit,
+                });
+            }
+        }
+        match self.mode {
+            CameraMode::Orbit => self.orbit.update(&canvas, dt),
+            CameraMode::Fly => self.fly.update(&canvas, input, dt),
+        }
+        let view = self.view();
+        let changed = !view.abs_diff_eq(self.last_view, 1e-6);
+        self.last_view = view;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1. / 60.;
+
+    fn dragging(delta: [f32; 2]) -> CanvasInput {
+        let mut input = CanvasInput {
+            size: [800., 600.],
+            ..Default::default()
+        };
+        input.pointer.held[BUTTON_LEFT] = true;
+        input.pointer.delta = delta;
+        input
+    }
+
+    #[test]
+    fn orbit_drag_turns_half_a_revolution() {
+        let mut orbit = OrbitController::look_at(Vec3::Z * 5., Vec3::ZERO, Vec3::Y);
+        let yaw = orbit.yaw;
+        orbit.update(&dragging([600., 0.]), DT);
+        // 按住不动，再松开
+        for _ in 0..60 {
+            orbit.update(&dragging([0., 0.]), DT);
+        }
+        for _ in 0..120 {
+            orbit.update(&CanvasInput::default(), DT);
+        }
+        assert!((yaw - orbit.yaw - PI).abs() < 1e-5);
+    }
+
+    #[test]
+    fn orbit_released_drag_keeps_bounded_inertia() {
+        let mut orbit = OrbitController::look_at(Vec3::Z * 5., Vec3::ZERO, Vec3::Y);
+        let yaw = orbit.yaw;
+        orbit.update(&dragging([60., 0.]), DT);
+        for _ in 0..300 {
+            orbit.update(&CanvasInput::default(), DT);
+        }
+        // 松手后惯性最多是最后一帧的 k / (1 - k) 倍
+        let step = PI / 10.;
+        let inertia = step * orbit.damping / (1. - orbit.damping);
+        assert!((yaw - orbit.yaw - step - inertia).abs() < 1e-4);
+    }
+
+    #[test]
+    fn orbit_wheel_zooms_by_the_scrolled_amount() {
+        let mut orbit = OrbitController::look_at(Vec3::Z * 5., Vec3::ZERO, Vec3::Y);
+        let mut input = CanvasInput::default();
+        input.pointer.wheel = 200.;
+        orbit.update(&input, DT);
+        for _ in 0..300 {
+            orbit.update(&CanvasInput::default(), DT);
+        }
+        let expected = 5. * (200. * ZOOM_SPEED).exp();
+        assert!((orbit.distance - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn fly_look_follows_the_drag() {
+        let mut fly = FlyController::look_at(Vec3::Z * 5., Vec3::ZERO, Vec3::Y);
+        let (yaw, pitch) = (fly.yaw, fly.pitch);
+        let keys = Input::default();
+        fly.update(&dragging([300., 150.]), &keys, DT);
+        fly.update(&dragging([0., 0.]), &keys, DT);
+        for _ in 0..120 {
+            fly.update(&CanvasInput::default(), &keys, DT);
+        }
+        assert!((yaw - fly.yaw - PI / 2.).abs() < 1e-5);
+        assert!((pitch - fly.pitch - PI / 4.).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,190 @@
+/// WGSL 热替换
+/// 支持热替换的 system 在 ready 时用 `register` 登记自己的 shader（名字形如 "boids.draw"），
+/// JS 通过 `web::shaders::set_shader_source` 提交新源码，system 在 update 里 `take_request` 取走，
+/// 用 `rebuild` 先经 naga 校验，再在 error scope 里重建 shader module 和管线。编译信息和 scope 结果异步回来：
+/// 成功才把新管线交给 system 替换，失败保留旧管线，诊断信息回传给 JS
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::utils::shader_debug::{self, Diagnostic};
+
+/// Ok 时是警告等非错误消息，Err 时至少包含一条 error
+pub type ReloadResult = Result<Vec<Diagnostic>, Vec<Diagnostic>>;
+pub type Callback = Box<dyn FnOnce(ReloadResult)>;
+
+pub struct Request {
+    pub source: String,
+    callback: Callback,
+}
+
+struct Entry {
+    default: &'static str,
+    active: String,
+    pending: Option<Request>,
+}
+
+thread_local! {
+    static SHADERS: RefCell<HashMap<String, Entry>> = RefCell::new(HashMap::new());
+}
+
+/// 登记 shader，返回当前生效的源码（重新进入页面时保留之前替换成功的源码）
+pub fn register(name: &str, default: &'static str) -> String {
+    SHADERS.with(|shaders| {
+        shaders
+            .borrow_mut()
+            .entry(name.to_string())
+            .or_insert_with(|| Entry {
+                default,
+                active: default.to_string(),
+                pending: None,
+            })
+            .active
+            .clone()
+    })
+}
+
+pub fn names() -> Vec<String> {
+    SHADERS.with(|shaders| {
+        let mut names: Vec<_> = shaders.borrow().keys().cloned().collect();
+        names.sort();
+        names
+    })
+}
+
+pub fn active_source(name: &str) -> Option<String> {
+    SHADERS.with(|shaders| shaders.borrow().get(name).map(|e| e.active.clone()))
+}
+
+pub fn default_source(name: &str) -> Option<&'static str> {
+    SHADERS.with(|shaders| shaders.borrow().get(name).map(|e| e.default))
+}
+
+/// 提交新源码；同一个 shader 还没处理的旧请求被顶掉
+pub fn request(name: &str, source: String, callback: Callback) -> Result<(), String> {
+    let replaced = SHADERS.with(|shaders| {
+        let mut shaders = shaders.borrow_mut();
+        let entry = shaders
+            .get_mut(name)
+            .ok_or_else(|| format!("Unknown shader: {}", name))?;
+        Ok::<_, String>(entry.pending.replace(Request { source, callback }))
+    })?;
+    if let Some(old) = replaced {
+        (old.callback)(Err(vec![Diagnostic::error("superseded by a newer source")]));
+    }
+    Ok(())
+}
+
+pub fn take_request(name: &str) -> Option<Request> {
+    SHADERS.with(|shaders| {
+        shaders
+            .borrow_mut()
+            .get_mut(name)
+            .and_then(|entry| entry.pending.take())
+    })
+}
+
+/// 异步重建的结果，system 每帧 `take` 一次，拿到就替换自己的管线
+pub struct Reload<T> {
+    slot: Rc<RefCell<Option<T>>>,
+}
+
+// 手写 Clone / Debug，不要求 T 实现
+impl<T> Clone for Reload<T> {
+    fn clone(&self) -> Self {
+        Reload {
+            slot: self.slot.clone(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Reload<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Reload")
+            .field("ready", &self.slot.borrow().is_some())
+            .finish()
+    }
+}
+
+impl<T> Reload<T> {
+    pub fn take(&self) -> Option<T> {
+        self.slot.borrow_mut().take()
+    }
+}
+
+/// 用新源码创建 shader module，再用 `build` 建出依赖它的管线
+/// 源码和 ready 时一样先按 `defines` 预处理，诊断位置换算回提交的源码
+pub fn rebuild<T: 'static>(
+    device: &wgpu::Device,
+    name: &str,
+    request: Request,
+    defines: &[(&str, &str)],
+    build: impl FnOnce(&wgpu::ShaderModule) -> T,
+) -> Reload<T> {
+    let slot = Rc::new(RefCell::new(None));
+    // 先用 naga 校验，能给出准确的行列号，也免得把坏源码交给 GPU
+    let preprocessed = match shader_debug::preprocess_and_validate(&request.source, defines) {
+        Ok((preprocessed, _)) => preprocessed,
+        Err(diagnostics) => {
+            log::warn!("shader {} rejected, keep the old pipeline", name);
+            (request.callback)(Err(diagnostics));
+            return Reload { slot };
+        }
+    };
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some(name),
+        source: wgpu::ShaderSource::Wgsl(preprocessed.source.as_str().into()),
+    });
+    let built = build(&module);
+    let scope = device.pop_error_scope();
+    let info = module.get_compilation_info();
+
+    let slot_ = slot.clone();
+    let name = name.to_string();
+    wasm_bindgen_futures::spawn_local(async move {
+        let mut diagnostics: Vec<Diagnostic> = info
+            .await
+            .messages
+            .iter()
+            .map(diagnostic)
+            .map(|d| preprocessed.locate(d))
+            .collect();
+        // 编译通过但建管线失败（比如入口点或绑定对不上）也算失败
+        if let Some(error) = scope.await {
+            diagnostics.push(Diagnostic::error(&error.to_string()));
+        }
+        if diagnostics.iter().any(|d| d.kind == "error") {
+            log::warn!("shader {} rejected, keep the old pipeline", name);
+            (request.callback)(Err(diagnostics));
+            return;
+        }
+        log::info!("shader {} reloaded", name);
+        SHADERS.with(|shaders| {
+            if let Some(entry) = shaders.borrow_mut().get_mut(&name) {
+                entry.active = request.source;
+            }
+        });
+        *slot_.borrow_mut() = Some(built);
+        (request.callback)(Ok(diagnostics));
+    });
+    Reload { slot }
+}
+
+fn diagnostic(message: &wgpu::CompilationMessage) -> Diagnostic {
+    let kind = match message.message_type {
+        wgpu::CompilationMessageType::Error => "error",
+        wgpu::CompilationMessageType::Warning => "warning",
+        wgpu::CompilationMessageType::Info => "info",
+    };
+    let (line, column, offset, length) = match &message.location {
+        Some(l) => (l.line_number, l.line_position, l.offset, l.length),
+        None => (0, 0, 0, 0),
+    };
+    Diagnostic {
+        kind,
+        message: message.message.clone(),
+        line,
+        column,
+        offset,
+        length,
+    }
+}
@@ -1,6 +1,13 @@
-use crate::web::bridge_canvas::Canvas;
+use crate::{
+    utils::pipeline_cache::PipelineCache,
+    web::{bridge_canvas::Canvas, input::Input, visibility::Visibility},
+};
 use std::{cell::RefCell, rc::Rc, sync::Arc};
 use wgpu::{Adapter, Device, Queue};
+pub mod audio;
+pub mod camera;
+pub mod hot_reload;
+pub mod params;
 pub mod ready_paint;
 pub struct Shared {
     pub device: Arc<Device>,
@@ -8,15 +15,24 @@ pub struct Shared {
     pub adapter: Arc<Adapter>,
     pub canvas: Canvas,
     pub configs: Arc<RefCell<Vec<wgpu::SurfaceConfiguration>>>,
+    pub input: Input,
+    pub visibility: Visibility,
+    pub pipelines: PipelineCache, // shader / 管线按内容复用，见 `PipelineCache`
 }
 impl Shared {
     pub fn new(device: Device, queue: Queue, adapter: Adapter, canvas: Canvas) -> Self {
+        let input = Input::attach(&canvas).expect("Failed to attach input listeners");
+        let visibility = Visibility::observe(&canvas).expect("Failed to observe canvases");
+        let device = Arc::new(device);
         Self {
-            device: Arc::new(device),
+            pipelines: PipelineCache::new(device.clone()),
+            device,
             queue: Arc::new(queue),
             adapter: Arc::new(adapter),
             canvas,
             configs: Arc::new(RefCell::new(Vec::new())),
+            input,
+            visibility,
         }
     }
 }
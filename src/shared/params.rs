@@ -0,0 +1,173 @@
+/// 可调参数表
+/// 每个 system 在 ready 时声明自己的参数（名字、范围、默认值），
+/// JS 通过 `web::params` 读写，system 在 update 时取回变化写入 uniform buffer。
+use std::{cell::RefCell, collections::HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamKind {
+    Float,
+    Int,
+    Bool,
+}
+
+impl ParamKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParamKind::Float => "float",
+            ParamKind::Int => "int",
+            ParamKind::Bool => "bool",
+        }
+    }
+}
+
+/// 参数声明，通常写成 system 旁边的 const 数组
+#[derive(Debug, Clone, Copy)]
+pub struct ParamDesc {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub default: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ParamDesc {
+    pub const fn float(name: &'static str, default: f32, min: f32, max: f32) -> Self {
+        Self {
+            name,
+            kind: ParamKind::Float,
+            default,
+            min,
+            max,
+        }
+    }
+    pub const fn int(name: &'static str, default: i32, min: i32, max: i32) -> Self {
+        Self {
+            name,
+            kind: ParamKind::Int,
+            default: default as f32,
+            min: min as f32,
+            max: max as f32,
+        }
+    }
+    pub const fn bool(name: &'static str, default: bool) -> Self {
+        Self {
+            name,
+            kind: ParamKind::Bool,
+            default: default as u8 as f32,
+            min: 0.,
+            max: 1.,
+        }
+    }
+
+    // 按类型夹到合法范围
+    fn sanitize(&self, value: f32) -> f32 {
+        let value = value.clamp(self.min, self.max);
+        match self.kind {
+            ParamKind::Float => value,
+            ParamKind::Int => value.round(),
+            ParamKind::Bool => (value >= 0.5) as u8 as f32,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub system: &'static str,
+    pub desc: ParamDesc,
+    pub value: f32,
+}
+
+impl Param {
+    pub fn key(&self) -> String {
+        format!("{}.{}", self.system, self.desc.name)
+    }
+}
+
+#[derive(Default)]
+struct ParamRegistry {
+    params: Vec<Param>,
+    changed: HashSet<&'static str>,
+}
+
+thread_local! {
+    static PARAMS: RefCell<ParamRegistry> = RefCell::new(ParamRegistry::default());
+}
+
+/// 声明一组参数。已经声明过的保留当前值（切换页面重新 ready 时不丢掉 JS 的调整）
+pub fn declare(system: &'static str, descs: &[ParamDesc]) {
+    PARAMS.with(|params| {
+        let mut params = params.borrow_mut();
+        for desc in descs {
+            let exists = params
+                .params
+                .iter()
+                .any(|p| p.system == system && p.desc.name == desc.name);
+            if !exists {
+                params.params.push(Param {
+                    system,
+                    desc: *desc,
+                    value: desc.default,
+                });
+            }
+        }
+    });
+}
+
+/// 读取参数值，未声明时 panic（属于 system 自己的编码错误）
+pub fn get(system: &str, name: &str) -> f32 {
+    try_get(system, name).unwrap_or_else(|| panic!("Param not declared: {}.{}", system, name))
+}
+
+pub fn try_get(system: &str, name: &str) -> Option<f32> {
+    PARAMS.with(|params| {
+        params
+            .borrow()
+            .params
+            .iter()
+            .find(|p| p.system == system && p.desc.name == name)
+            .map(|p| p.value)
+    })
+}
+
+/// 写入参数，返回夹取后的实际值
+pub fn set(system: &str, name: &str, value: f32) -> Result<f32, String> {
+    if !value.is_finite() {
+        return Err(format!("Param {}.{} got non-finite value", system, name));
+    }
+    PARAMS.with(|params| {
+        let mut params = params.borrow_mut();
+        let param = params
+            .params
+            .iter_mut()
+            .find(|p| p.system == system && p.desc.name == name)
+            .ok_or_else(|| format!("Unknown param: {}.{}", system, name))?;
+        param.value = param.desc.sanitize(value);
+        let (system, value) = (param.system, param.value);
+        params.changed.insert(system);
+        Ok(value)
+    })
+}
+
+/// 恢复到默认值
+pub fn reset(system: &str) {
+    PARAMS.with(|params| {
+        let mut params = params.borrow_mut();
+        let mut hit = None;
+        for param in params.params.iter_mut().filter(|p| p.system == system) {
+            param.value = param.desc.default;
+            hit = Some(param.system);
+        }
+        if let Some(system) = hit {
+            params.changed.insert(system);
+        }
+    });
+}
+
+/// system 的参数自上次调用以来是否被改过（调用后清除标记）
+pub fn take_changed(system: &str) -> bool {
+    PARAMS.with(|params| params.borrow_mut().changed.remove(system))
+}
+
+pub fn list() -> Vec<Param> {
+    PARAMS.with(|params| params.borrow().params.clone())
+}
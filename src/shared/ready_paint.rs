@@ -1,6 +1,7 @@
 /// source from my crate https://crates.io/crates/ready-paint
 use std::{
     any::{Any, TypeId},
+    cell::RefCell,
     collections::HashMap,
     marker::PhantomData,
     sync::Arc,
@@ -8,7 +9,7 @@ use std::{
 
 use wgpu::Surface;
 
-use crate::console_log;
+use crate::web::gpu_errors::{self, Phase};
 
 use super::Shared;
 pub type Gfx = Arc<Shared>;
@@ -73,6 +74,24 @@ pub fn get_ref_and_mut<Ref: Any + 'static, Mut: Any + 'static>(
     }
 }
 
+thread_local! {
+    // 正在 paint 的 scene 名，`scoped` 报错时用
+    static PAINTING: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// 在 Paint 里调用某个系统（Update / Pass 或者它的其它方法）时包一层，错误记在 `T` 名下而不是整个 Paint。
+/// render pass 里的命令要到 `encoder.finish()` 才校验，那部分错误仍然记在外层 Paint 名下
+pub fn scoped<T: 'static, R>(gfx: &Gfx, f: impl FnOnce() -> R) -> R {
+    let scene = PAINTING.with(|scene| scene.borrow().clone());
+    gpu_errors::scoped(
+        &gfx.device,
+        &scene,
+        std::any::type_name::<T>(),
+        Phase::Paint,
+        f,
+    )
+}
+
 /// create a new box data of type in hashmap (directly cover)
 pub fn return_res<T: Any + 'static>(data: &mut HashMap<TypeId, Box<dyn Any>>, new_data: T) {
     data.insert(TypeId::of::<T>(), Box::new(new_data));
@@ -83,6 +102,8 @@ pub struct Scene {
     pub res: HashMap<TypeId, Box<dyn Any>>,
     readys: Vec<TypeId>,
     paints: Vec<TypeId>,
+    // 系统类型名，报错时用
+    names: HashMap<TypeId, &'static str>,
     readys_hashmap: HashMap<TypeId, Box<dyn FnMut(&mut HashMap<TypeId, Box<dyn Any>>, &Gfx)>>,
     paints_hashmap: HashMap<
         TypeId,
@@ -92,12 +113,13 @@ pub struct Scene {
 
 impl Scene {
     pub fn new(name: String) -> Self {
-        console_log!("Scene::new");
+        log::debug!("Scene::new");
         Scene {
             name,
             res: HashMap::new(),
             readys: Vec::new(),
             paints: Vec::new(),
+            names: HashMap::new(),
             readys_hashmap: HashMap::new(),
             paints_hashmap: HashMap::new(),
         }
@@ -110,6 +132,7 @@ impl Scene {
     pub fn add_ready<T: Ready + Default + 'static>(&mut self, mut ready_res: T) -> &mut Self {
         let type_id = TypeId::of::<T>();
         self.readys.push(type_id);
+        self.names.insert(type_id, std::any::type_name::<T>());
         self.res.insert(type_id, Box::new(T::default()));
         self.readys_hashmap.insert(
             type_id,
@@ -123,22 +146,30 @@ impl Scene {
     pub fn add_paint<T: Paint + 'static>(&mut self) {
         let type_id = TypeId::of::<T>();
         self.paints.push(type_id);
+        self.names.insert(type_id, std::any::type_name::<T>());
         self.paints_hashmap.insert(type_id, Box::new(T::paint));
     }
 
     pub fn ready(&mut self, gfx: &Gfx) {
-        println!("<Scene>::ready");
+        log::debug!("<Scene {}>::ready", self.name);
         for ready_type_id in self.readys.iter() {
             if let Some(ready_fn) = self.readys_hashmap.get_mut(ready_type_id) {
-                ready_fn(&mut self.res, gfx);
+                let system = self.names[ready_type_id];
+                gpu_errors::scoped(&gfx.device, &self.name, system, Phase::Ready, || {
+                    ready_fn(&mut self.res, gfx)
+                });
             }
         }
     }
 
     pub fn paint(&mut self, gfx: &Gfx, dt: f32, surface: &Arc<Surface<'static>>) {
+        PAINTING.with(|scene| scene.borrow_mut().clone_from(&self.name));
         for paint_type_id in self.paints.iter() {
             if let Some(paint_fn) = self.paints_hashmap.get_mut(paint_type_id) {
-                paint_fn(&mut self.res, gfx, dt, surface);
+                let system = self.names[paint_type_id];
+                gpu_errors::scoped(&gfx.device, &self.name, system, Phase::Paint, || {
+                    paint_fn(&mut self.res, gfx, dt, surface)
+                });
             }
         }
     }
@@ -0,0 +1,290 @@
+/// Rust 和 WGSL 共用的结构体布局
+/// `wgsl_struct!` 声明一个 `#[repr(C)]` 结构体，同时：
+/// - 编译期按 WGSL 的对齐规则检查每个字段的偏移和结构体大小，对不上就编译失败，提示在哪个字段前补 padding
+/// - `WgslStruct::wgsl` 生成对应的 WGSL 声明，shader 里 `#include` 使用（见 `crate::shaders`）
+/// - `write_field!` 只写一个字段，偏移和类型都由编译器检查，不用再手写偏移量
+///
+/// 数组的步长按 WGSL 规则由元素决定；放进 uniform 的数组（默认）还要求步长是 16 的倍数，不满足时编译失败，
+/// 只在 storage 里用的标量、vec2 数组写成 `WgslArray<_, _, Storage>`
+use std::marker::PhantomData;
+
+use bytemuck::{Pod, Zeroable};
+
+/// 能作为结构体成员的 WGSL 类型：对齐和大小按 WGSL 规范
+pub trait WgslType: Pod {
+    const ALIGN: usize;
+    const SIZE: usize;
+    fn wgsl_name() -> String;
+}
+
+macro_rules! wgsl_type {
+    ($($ty:ty => $name:literal, $align:literal, $size:literal;)*) => {
+        $(impl WgslType for $ty {
+            const ALIGN: usize = $align;
+            const SIZE: usize = $size;
+            fn wgsl_name() -> String {
+                $name.to_string()
+            }
+        })*
+    };
+}
+
+wgsl_type! {
+    f32 => "f32", 4, 4;
+    u32 => "u32", 4, 4;
+    i32 => "i32", 4, 4;
+    [f32; 2] => "vec2<f32>", 8, 8;
+    [f32; 3] => "vec3<f32>", 16, 12;
+    [f32; 4] => "vec4<f32>", 16, 16;
+    [u32; 2] => "vec2<u32>", 8, 8;
+    [u32; 4] => "vec4<u32>", 16, 16;
+    [i32; 2] => "vec2<i32>", 8, 8;
+    [i32; 4] => "vec4<i32>", 16, 16;
+    [[f32; 4]; 4] => "mat4x4<f32>", 16, 64;
+}
+
+/// 数组所在的地址空间，决定数组步长的额外限制
+pub trait AddressSpace: Copy + 'static {
+    const STRIDE_MULTIPLE: usize;
+}
+
+/// uniform buffer：数组步长必须是 16 的倍数，元素只能是 vec4、mat4 这类 16 字节的类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Uniform;
+
+/// storage buffer：数组按元素本身的对齐紧密排列
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Storage;
+
+impl AddressSpace for Uniform {
+    const STRIDE_MULTIPLE: usize = 16;
+}
+
+impl AddressSpace for Storage {
+    const STRIDE_MULTIPLE: usize = 1;
+}
+
+/// WGSL 的 `array<T, N>`；`[T; N]` 已经用来表示向量和矩阵，数组单独包一层
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WgslArray<T, const N: usize, S = Uniform>(pub [T; N], PhantomData<S>);
+
+impl<T, const N: usize, S> WgslArray<T, N, S> {
+    pub const fn new(items: [T; N]) -> Self {
+        WgslArray(items, PhantomData)
+    }
+}
+
+unsafe impl<T: Zeroable, const N: usize, S: AddressSpace> Zeroable for WgslArray<T, N, S> {}
+unsafe impl<T: Pod, const N: usize, S: AddressSpace> Pod for WgslArray<T, N, S> {}
+
+impl<T: Zeroable, const N: usize, S: AddressSpace> Default for WgslArray<T, N, S> {
+    fn default() -> Self {
+        Self::zeroed()
+    }
+}
+
+impl<T: WgslType, const N: usize, S: AddressSpace> WgslType for WgslArray<T, N, S> {
+    const ALIGN: usize = T::ALIGN;
+    const SIZE: usize = {
+        let stride = round_up(T::ALIGN, T::SIZE);
+        assert!(
+            stride.is_multiple_of(S::STRIDE_MULTIPLE),
+            "arrays in a uniform buffer need a 16-byte stride, \
+             use vec4 / mat4 elements or `WgslArray<_, _, Storage>`"
+        );
+        // vec3 的步长是 16，Rust 的 [f32; 3] 只有 12
+        assert!(
+            stride == size_of::<T>(),
+            "the WGSL array stride differs from the Rust element size"
+        );
+        N * stride
+    };
+    fn wgsl_name() -> String {
+        format!("array<{}, {}>", T::wgsl_name(), N)
+    }
+}
+
+/// 一个字段在两边的布局，由 `wgsl_struct!` 生成
+pub struct Field {
+    pub name: &'static str,
+    pub ty: fn() -> String,
+    pub offset: usize, // Rust 侧的 offset_of，检查通过后也就是 WGSL 侧的偏移
+    pub rust_size: usize,
+    pub align: usize,
+    pub size: usize,
+    pub error: &'static str,
+}
+
+pub trait WgslStruct: Pod {
+    const NAME: &'static str;
+    const FIELDS: &'static [Field];
+
+    /// WGSL 结构体声明，字段顺序、名字和类型与 Rust 一致
+    fn wgsl() -> String {
+        let mut wgsl = format!("struct {} {{\n", Self::NAME);
+        for field in Self::FIELDS {
+            wgsl.push_str(&format!("    {}: {},\n", field.name, (field.ty)()));
+        }
+        wgsl.push_str("}\n");
+        wgsl
+    }
+}
+
+const fn round_up(align: usize, n: usize) -> usize {
+    n.div_ceil(align) * align
+}
+
+/// 按 WGSL 规则算出每个字段应在的偏移和结构体大小，和 Rust 的 `#[repr(C)]` 布局比较；在 const 里调用，不一致即编译错误
+pub const fn check(fields: &[Field], size: usize, size_error: &'static str) {
+    let mut end = 0;
+    let mut align = 1;
+    let mut i = 0;
+    while i < fields.len() {
+        let field = &fields[i];
+        if field.offset != round_up(field.align, end) || field.rust_size != field.size {
+            panic!("{}", field.error);
+        }
+        end = field.offset + field.size;
+        if field.align > align {
+            align = field.align;
+        }
+        i += 1;
+    }
+    if size != round_up(align, end) {
+        panic!("{}", size_error);
+    }
+}
+
+/// 声明 Rust / WGSL 共用的结构体，见模块说明。padding 字段也会出现在 WGSL 声明里
+macro_rules! wgsl_struct {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident: $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(C)]
+        #[derive(Debug, Default, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $ty),*
+        }
+
+        impl $crate::utils::layout::WgslStruct for $name {
+            const NAME: &'static str = stringify!($name);
+            const FIELDS: &'static [$crate::utils::layout::Field] = &[$(
+                $crate::utils::layout::Field {
+                    name: stringify!($field),
+                    ty: <$ty as $crate::utils::layout::WgslType>::wgsl_name,
+                    offset: std::mem::offset_of!($name, $field),
+                    rust_size: std::mem::size_of::<$ty>(),
+                    align: <$ty as $crate::utils::layout::WgslType>::ALIGN,
+                    size: <$ty as $crate::utils::layout::WgslType>::SIZE,
+                    error: concat!(
+                        "`", stringify!($name), "::", stringify!($field),
+                        "` does not match the WGSL layout, add padding before it or change its type"
+                    ),
+                }
+            ),*];
+        }
+
+        const _: () = $crate::utils::layout::check(
+            <$name as $crate::utils::layout::WgslStruct>::FIELDS,
+            std::mem::size_of::<$name>(),
+            concat!(
+                "size of `", stringify!($name),
+                "` does not match the WGSL struct, add trailing padding"
+            ),
+        );
+    };
+}
+pub(crate) use wgsl_struct;
+
+/// 只写 buffer 里的一个字段：`write_field!(queue, buffer, Uniforms, delta_time, dt)`
+/// 字段不存在或值的类型不对都会编译失败
+// 只有 wasm 下的场景在用
+#[cfg_attr(not(target_arch = "wasm32"), allow(unused_macros))]
+macro_rules! write_field {
+    ($queue:expr, $buffer:expr, $struct:ty, $field:ident, $value:expr) => {
+        $crate::utils::layout::write_field_at::<$struct, _>(
+            $queue,
+            $buffer,
+            std::mem::offset_of!($struct, $field),
+            |s| &s.$field,
+            &$value,
+        )
+    };
+}
+#[cfg_attr(not(target_arch = "wasm32"), allow(unused_imports))]
+pub(crate) use write_field;
+
+// `field` 只用来让编译器核对值的类型
+pub fn write_field_at<S: WgslStruct, T: WgslType>(
+    queue: &wgpu::Queue,
+    buffer: &wgpu::Buffer,
+    offset: usize,
+    _field: fn(&S) -> &T,
+    value: &T,
+) {
+    queue.write_buffer(
+        buffer,
+        offset as wgpu::BufferAddress,
+        bytemuck::bytes_of(value),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    wgsl_struct! {
+        struct Packed {
+            position: [f32; 3],
+            radius: f32, // vec3 后面的 4 字节正好放一个标量
+            color: [f32; 4],
+            weights: WgslArray<[f32; 4], 2>,
+            flags: u32,
+            _pad0: u32,
+            _pad1: [u32; 2], // 结构体按 16 对齐，补到 80
+        }
+    }
+
+    #[test]
+    fn offsets_follow_wgsl() {
+        let offsets: Vec<_> = Packed::FIELDS.iter().map(|f| (f.name, f.offset)).collect();
+        assert_eq!(
+            offsets,
+            [
+                ("position", 0),
+                ("radius", 12),
+                ("color", 16),
+                ("weights", 32),
+                ("flags", 64),
+                ("_pad0", 68),
+                ("_pad1", 72),
+            ]
+        );
+        assert_eq!(size_of::<Packed>(), 80);
+    }
+
+    #[test]
+    fn array_stride_follows_element() {
+        assert_eq!(<WgslArray<[f32; 4], 2>>::SIZE, 32);
+        assert_eq!(<WgslArray<[[f32; 4]; 4], 2>>::ALIGN, 16);
+        // storage 里的标量、vec2 数组紧密排列
+        assert_eq!(<WgslArray<f32, 4, Storage>>::ALIGN, 4);
+        assert_eq!(<WgslArray<f32, 4, Storage>>::SIZE, 16);
+        assert_eq!(<WgslArray<[u32; 2], 3, Storage>>::SIZE, 24);
+    }
+
+    #[test]
+    fn wgsl_declaration() {
+        assert_eq!(
+            Packed::wgsl(),
+            "struct Packed {\n    position: vec3<f32>,\n    radius: f32,\n    \
+             color: vec4<f32>,\n    weights: array<vec4<f32>, 2>,\n    flags: u32,\n    \
+             _pad0: u32,\n    _pad1: vec2<u32>,\n}\n"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+/// 日志统一走 `log`：wasm 上交给 console_log 打到浏览器控制台，本地交给 env_logger。
+/// target 默认是模块路径，可以按前缀单独设级别，规则写法和 RUST_LOG 一样：
+/// `info,web::frame_loop=debug`（前缀里可以省掉 crate 名）。JS 通过 `set_log_level` 运行时切换
+use std::sync::RwLock;
+
+use log::{LevelFilter, Log, Metadata, Record};
+use wasm_bindgen::prelude::*;
+
+const CRATE_PREFIX: &str = "page_shader_tech::";
+const DEFAULT_SPEC: &str = "info";
+
+struct Filter {
+    default: LevelFilter,
+    // (target 前缀, 级别)，按前缀长度从长到短，先匹配到的生效
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    fn parse(spec: &str) -> Result<Self, String> {
+        let mut filter = Filter {
+            default: LevelFilter::Info,
+            targets: Vec::new(),
+        };
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    let target = target.trim().trim_start_matches(CRATE_PREFIX).to_string();
+                    filter.targets.push((target, parse_level(level)?));
+                }
+                None => filter.default = parse_level(directive)?,
+            }
+        }
+        filter
+            .targets
+            .sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        Ok(filter)
+    }
+
+    fn level_for(&self, target: &str) -> LevelFilter {
+        let target = short_target(target);
+        self.targets
+            .iter()
+            .find(|(prefix, _)| target.starts_with(prefix.as_str()))
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+
+    fn to_spec(&self) -> String {
+        std::iter::once(self.default.to_string().to_lowercase())
+            .chain(
+                self.targets.iter().map(|(target, level)| {
+                    format!("{}={}", target, level.to_string().to_lowercase())
+                }),
+            )
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    level
+        .trim()
+        .parse()
+        .map_err(|_| format!("Unknown log level: {}", level))
+}
+
+fn short_target(target: &str) -> &str {
+    target.strip_prefix(CRATE_PREFIX).unwrap_or(target)
+}
+
+static FILTER: RwLock<Option<Filter>> = RwLock::new(None);
+
+struct Logger {
+    #[cfg(not(target_arch = "wasm32"))]
+    native: env_logger::Logger,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let filter = FILTER.read().unwrap();
+        filter
+            .as_ref()
+            .is_some_and(|filter| metadata.level() <= filter.level_for(metadata.target()))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        #[cfg(target_arch = "wasm32")]
+        console_log::log(
+            &Record::builder()
+                .args(format_args!(
+                    "[{}] {}",
+                    short_target(record.target()),
+                    record.args()
+                ))
+                .level(record.level())
+                .target(record.target())
+                .build(),
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        self.native.log(record);
+    }
+
+    fn flush(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.native.flush();
+    }
+}
+
+/// 启动时调用一次；本地环境下读 RUST_LOG 作为初始规则
+pub fn init() {
+    #[cfg(target_arch = "wasm32")]
+    let (spec, logger) = (DEFAULT_SPEC.to_string(), Logger {});
+    #[cfg(not(target_arch = "wasm32"))]
+    let (spec, logger) = (
+        std::env::var("RUST_LOG").unwrap_or_else(|_| DEFAULT_SPEC.to_string()),
+        Logger {
+            // 过滤由上面的 Filter 负责，env_logger 只管输出格式
+            native: env_logger::Builder::new()
+                .filter_level(LevelFilter::Trace)
+                .build(),
+        },
+    );
+    if log::set_boxed_logger(Box::new(logger)).is_err() {
+        return;
+    }
+    let filter = Filter::parse(&spec).unwrap_or_else(|_| Filter::parse(DEFAULT_SPEC).unwrap());
+    apply(filter);
+}
+
+fn apply(filter: Filter) {
+    log::set_max_level(filter.max_level());
+    *FILTER.write().unwrap() = Some(filter);
+}
+
+/// 例如 `"debug"`、`"warn,web::frame_loop=debug,demos::level2=trace"`
+#[wasm_bindgen]
+pub fn set_log_level(spec: &str) -> Result<(), JsValue> {
+    let filter = Filter::parse(spec).map_err(|e| JsValue::from_str(&e))?;
+    apply(filter);
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn get_log_level() -> String {
+    FILTER
+        .read()
+        .unwrap()
+        .as_ref()
+        .map_or_else(String::new, Filter::to_spec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_default_and_targets() {
+        let filter =
+            Filter::parse(" warn , page_shader_tech::web=debug,web::frame_loop=trace,").unwrap();
+        assert_eq!(filter.default, LevelFilter::Warn);
+        // crate 名被去掉，长的前缀排在前面
+        assert_eq!(
+            filter.targets,
+            vec![
+                ("web::frame_loop".to_string(), LevelFilter::Trace),
+                ("web".to_string(), LevelFilter::Debug),
+            ]
+        );
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+        assert_eq!(filter.to_spec(), "warn,web::frame_loop=trace,web=debug");
+        assert_eq!(
+            Filter::parse(&filter.to_spec()).unwrap().targets,
+            filter.targets
+        );
+
+        let empty = Filter::parse("").unwrap();
+        assert_eq!(empty.default, LevelFilter::Info);
+        assert_eq!(empty.to_spec(), "info");
+    }
+
+    #[test]
+    fn rejects_unknown_levels() {
+        assert!(Filter::parse("loud").is_err());
+        assert!(Filter::parse("info,web=loud").is_err());
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let filter = Filter::parse("info,web=debug,web::frame_loop=off").unwrap();
+        // 带不带 crate 名都一样
+        assert_eq!(
+            filter.level_for("page_shader_tech::web::input"),
+            LevelFilter::Debug
+        );
+        assert_eq!(filter.level_for("web::input"), LevelFilter::Debug);
+        assert_eq!(
+            filter.level_for("page_shader_tech::web::frame_loop"),
+            LevelFilter::Off
+        );
+        assert_eq!(
+            filter.level_for("page_shader_tech::demos::level2"),
+            LevelFilter::Info
+        );
+        // 其它 crate 的 target 原样比较
+        assert_eq!(filter.level_for("wgpu_core::device"), LevelFilter::Info);
+    }
+}
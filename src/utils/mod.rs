@@ -5,13 +5,13 @@ use wgpu::SurfaceTexture;
 
 use crate::shared::Shared;
 
+pub mod layout;
+pub mod logging;
+pub mod pipeline_cache;
+pub mod preprocess;
+pub mod reflect;
+pub mod rng;
 pub mod shader_debug;
-#[macro_export]
-macro_rules! console_log {
-    ($($arg:tt)*) => {
-        web_sys::console::log_1(&format!($($arg)*).into());
-    };
-}
 
 pub fn split<'a>(
     canvas_id_name: &'a String,
@@ -55,7 +55,7 @@ pub fn split_for_update<'a>(
     Arc<wgpu::Queue>,
     Arc<wgpu::Surface<'static>>,
 ) {
-    console_log!("2 split_for_update");
+    log::debug!("2 split_for_update");
     let device = shared.device.clone();
     let queue = shared.queue.clone();
     let adapter = shared.adapter.clone();
@@ -70,86 +70,14 @@ pub fn split_for_update<'a>(
         .clone();
     let surface = arc_surface.clone();
 
-    let default_config = surface.get_default_config(&adapter, width, height).unwrap();
+    let mut default_config = surface.get_default_config(&adapter, width, height).unwrap();
+    // capture_png 要从 swapchain 纹理复制出像素
+    default_config.usage |= wgpu::TextureUsages::COPY_SRC;
     surface.configure(&device, &default_config);
 
     (adapter, queue, surface)
 }
 
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct Vertex {
-    pub position: [f32; 3],
-}
-pub struct CubeMesh {
-    pub vertices: Vec<Vertex>,
-    pub indices: Vec<u16>,
-}
-impl Default for CubeMesh {
-    fn default() -> Self {
-        create_cube(1.)
-    }
-}
-pub fn create_cube(size: f32) -> CubeMesh {
-    let half = size / 2.0;
-
-    //                    0 -------> 1
-    //                   |           |
-    //                7 -|--------4  |
-    //                |  |        |  |
-    //                |  |        |  |
-    //                |  3 --------> 2
-    //                |           |
-    //                6 ----------5
-
-    // Define the 8 vertices of the cube
-    let vertices = vec![
-        Vertex {
-            position: [-half, half, -half],
-        },
-        Vertex {
-            position: [half, half, -half],
-        },
-        Vertex {
-            position: [half, -half, -half],
-        },
-        Vertex {
-            position: [-half, -half, -half],
-        },
-        Vertex {
-            position: [half, half, half],
-        },
-        Vertex {
-            position: [half, -half, half],
-        },
-        Vertex {
-            position: [-half, -half, half],
-        },
-        Vertex {
-            position: [-half, half, half],
-        },
-    ];
-    //                    0 -------> 1
-    //                   |           |
-    //                7 -|--------4  |
-    //                |  |        |  |
-    //                |  |        |  |
-    //                |  3 --------> 2
-    //                |           |
-    //                6 ----------5
-    // Define the indices for the triangles (12 triangles, 6 faces)
-    let indices = vec![
-        7, 4, 5, 5, 6, 7, // front
-        5, 4, 2, 2, 4, 1, // right
-        0, 1, 4, 4, 7, 0, // top
-        2, 1, 0, 0, 3, 2, // back
-        3, 0, 7, 7, 6, 3, // left
-        3, 6, 5, 5, 2, 3, // bottom
-    ];
-
-    CubeMesh { vertices, indices }
-}
-
 pub struct Time {
     pub delta: f32,
     pub elapsed: f32,
@@ -0,0 +1,229 @@
+/// 设备级的 shader module / 管线缓存，挂在 `Shared::pipelines` 上
+/// 每次切换页面都会重新执行各 system 的 ready，重建出来的 shader 和管线大多和上次一模一样，这里按内容去重：
+/// - shader module：源码和 defines（预处理之前）
+/// - bind group layout：entries
+/// - pipeline layout：bind group layout 对象（已经去重过）和 push constant
+/// - 管线：layout、shader module 对象，加上描述符里除 label 以外的其它内容
+///
+/// 所以只要 shader 和 layout 也走这里创建，同样的描述符拿到的就是同一个管线对象。
+/// 缓存不淘汰，热替换重建的管线每次编辑都是新 module，不走缓存，直接在 `wgpu::Device` 上创建（见 `CreatePipeline`）
+/// 支持 `Features::PIPELINE_CACHE` 的后端（目前只有 Vulkan）另外把编译结果交给 `wgpu::PipelineCache`，浏览器里没有这个特性
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    rc::Rc,
+    sync::Arc,
+};
+
+use wasm_bindgen::JsValue;
+
+use super::shader_debug::{self, ValidatedShader};
+
+// 键里直接持有 layout 和 module，保证它们不会被释放后 id 被复用
+#[derive(PartialEq, Eq, Hash)]
+struct PipelineKey {
+    layout: Option<wgpu::PipelineLayout>,
+    modules: Vec<wgpu::ShaderModule>,
+    state: u64, // 描述符其余部分的哈希
+}
+
+// 预处理之前的源码和 defines
+type ShaderKey = (String, Vec<(String, String)>);
+
+type LayoutKey = (Vec<wgpu::BindGroupLayout>, Vec<wgpu::PushConstantRange>);
+
+pub struct PipelineCache {
+    device: Arc<wgpu::Device>,
+    wgpu_cache: Option<wgpu::PipelineCache>,
+    shaders: RefCell<HashMap<ShaderKey, (wgpu::ShaderModule, Rc<ValidatedShader>)>>,
+    bind_group_layouts: RefCell<HashMap<Vec<wgpu::BindGroupLayoutEntry>, wgpu::BindGroupLayout>>,
+    pipeline_layouts: RefCell<HashMap<LayoutKey, wgpu::PipelineLayout>>,
+    render_pipelines: RefCell<HashMap<PipelineKey, wgpu::RenderPipeline>>,
+    compute_pipelines: RefCell<HashMap<PipelineKey, wgpu::ComputePipeline>>,
+}
+
+impl PipelineCache {
+    pub fn new(device: Arc<wgpu::Device>) -> Self {
+        let wgpu_cache = device
+            .features()
+            .contains(wgpu::Features::PIPELINE_CACHE)
+            .then(|| unsafe {
+                // 没有之前保存的数据，fallback 为 true 时数据无效也不会出错
+                device.create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                    label: Some("pipeline cache"),
+                    data: None,
+                    fallback: true,
+                })
+            });
+        Self {
+            device,
+            wgpu_cache,
+            shaders: Default::default(),
+            bind_group_layouts: Default::default(),
+            pipeline_layouts: Default::default(),
+            render_pipelines: Default::default(),
+            compute_pipelines: Default::default(),
+        }
+    }
+
+    /// 预处理、naga 验证后创建 shader module（见 `shader_debug`），同样的源码和 defines 只编译一次
+    pub fn shader(
+        &self,
+        label: &str,
+        source: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<wgpu::ShaderModule, JsValue> {
+        self.validated_shader(label, source, defines)
+            .map(|(module, _)| module)
+    }
+
+    /// 同 `shader_debug::create_validated_shader`，验证结果也一起缓存
+    pub fn validated_shader(
+        &self,
+        label: &str,
+        source: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<(wgpu::ShaderModule, Rc<ValidatedShader>), JsValue> {
+        let key = (
+            source.to_string(),
+            defines
+                .iter()
+                .map(|&(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        );
+        if let Some((module, shader)) = self.shaders.borrow().get(&key) {
+            return Ok((module.clone(), shader.clone()));
+        }
+        // 验证失败的不缓存，错误每次都会打印
+        let (module, shader) =
+            shader_debug::create_validated_shader(&self.device, label, source, defines)?;
+        let shader = Rc::new(shader);
+        self.shaders
+            .borrow_mut()
+            .insert(key, (module.clone(), shader.clone()));
+        Ok((module, shader))
+    }
+
+    pub fn bind_group_layout(
+        &self,
+        desc: &wgpu::BindGroupLayoutDescriptor,
+    ) -> wgpu::BindGroupLayout {
+        self.bind_group_layouts
+            .borrow_mut()
+            .entry(desc.entries.to_vec())
+            .or_insert_with(|| self.device.create_bind_group_layout(desc))
+            .clone()
+    }
+
+    pub fn pipeline_layout(&self, desc: &wgpu::PipelineLayoutDescriptor) -> wgpu::PipelineLayout {
+        let key = (
+            desc.bind_group_layouts.iter().map(|&l| l.clone()).collect(),
+            desc.push_constant_ranges.to_vec(),
+        );
+        self.pipeline_layouts
+            .borrow_mut()
+            .entry(key)
+            .or_insert_with(|| self.device.create_pipeline_layout(desc))
+            .clone()
+    }
+
+    pub fn render_pipeline(&self, desc: &wgpu::RenderPipelineDescriptor) -> wgpu::RenderPipeline {
+        let mut hasher = DefaultHasher::new();
+        let mut modules = vec![desc.vertex.module.clone()];
+        desc.vertex.entry_point.hash(&mut hasher);
+        hash_options(&desc.vertex.compilation_options, &mut hasher);
+        desc.vertex.buffers.hash(&mut hasher);
+        desc.primitive.hash(&mut hasher);
+        desc.depth_stencil.hash(&mut hasher);
+        desc.multisample.hash(&mut hasher);
+        desc.multiview.hash(&mut hasher);
+        desc.fragment.is_some().hash(&mut hasher);
+        if let Some(fragment) = &desc.fragment {
+            modules.push(fragment.module.clone());
+            fragment.entry_point.hash(&mut hasher);
+            hash_options(&fragment.compilation_options, &mut hasher);
+            fragment.targets.hash(&mut hasher);
+        }
+        let key = PipelineKey {
+            layout: desc.layout.cloned(),
+            modules,
+            state: hasher.finish(),
+        };
+        self.render_pipelines
+            .borrow_mut()
+            .entry(key)
+            .or_insert_with(|| {
+                self.device
+                    .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                        cache: desc.cache.or(self.wgpu_cache.as_ref()),
+                        ..desc.clone()
+                    })
+            })
+            .clone()
+    }
+
+    pub fn compute_pipeline(
+        &self,
+        desc: &wgpu::ComputePipelineDescriptor,
+    ) -> wgpu::ComputePipeline {
+        let mut hasher = DefaultHasher::new();
+        desc.entry_point.hash(&mut hasher);
+        hash_options(&desc.compilation_options, &mut hasher);
+        let key = PipelineKey {
+            layout: desc.layout.cloned(),
+            modules: vec![desc.module.clone()],
+            state: hasher.finish(),
+        };
+        self.compute_pipelines
+            .borrow_mut()
+            .entry(key)
+            .or_insert_with(|| {
+                self.device
+                    .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                        cache: desc.cache.or(self.wgpu_cache.as_ref()),
+                        ..desc.clone()
+                    })
+            })
+            .clone()
+    }
+}
+
+/// 建管线的地方：`PipelineCache` 按内容复用，`wgpu::Device` 每次新建。
+/// 建管线的函数对两者通用，ready 时传缓存，热替换重建时传 device
+pub trait CreatePipeline {
+    fn render_pipeline(&self, desc: &wgpu::RenderPipelineDescriptor) -> wgpu::RenderPipeline;
+    fn compute_pipeline(&self, desc: &wgpu::ComputePipelineDescriptor) -> wgpu::ComputePipeline;
+}
+
+impl CreatePipeline for PipelineCache {
+    fn render_pipeline(&self, desc: &wgpu::RenderPipelineDescriptor) -> wgpu::RenderPipeline {
+        PipelineCache::render_pipeline(self, desc)
+    }
+
+    fn compute_pipeline(&self, desc: &wgpu::ComputePipelineDescriptor) -> wgpu::ComputePipeline {
+        PipelineCache::compute_pipeline(self, desc)
+    }
+}
+
+impl CreatePipeline for wgpu::Device {
+    fn render_pipeline(&self, desc: &wgpu::RenderPipelineDescriptor) -> wgpu::RenderPipeline {
+        self.create_render_pipeline(desc)
+    }
+
+    fn compute_pipeline(&self, desc: &wgpu::ComputePipelineDescriptor) -> wgpu::ComputePipeline {
+        self.create_compute_pipeline(desc)
+    }
+}
+
+// constants 是 HashMap，排序后再哈希
+fn hash_options(options: &wgpu::PipelineCompilationOptions, hasher: &mut DefaultHasher) {
+    let mut constants: Vec<_> = options
+        .constants
+        .iter()
+        .map(|(name, value)| (name, value.to_bits()))
+        .collect();
+    constants.sort();
+    constants.hash(hasher);
+    options.zero_initialize_workgroup_memory.hash(hasher);
+}
@@ -0,0 +1,438 @@
+/// WGSL 预处理，在创建 shader module 之前展开：
+/// - `#include "audio.wgsl"` 引入 src/shaders 下的公共片段，或者由 Rust 结构体生成的声明（见 `crate::shaders`），
+///   同一片段只引入一次
+/// - `#define NAME value` 按标识符整词替换，`#define NAME` 只用作开关，`#undef NAME` 取消
+/// - `#ifdef NAME` / `#ifndef NAME` / `#else` / `#endif` 按宏开关代码，可以嵌套
+///
+/// 调用方传入的 defines 和写在源码开头的 `#define` 等价
+use std::collections::HashMap;
+
+use super::shader_debug::Diagnostic;
+use crate::shaders;
+
+// 可以被 #include 的公共片段，生成的结构体声明见 `crate::shaders::includes`
+const INCLUDES: &[(&str, &str)] = &[
+    ("audio.wgsl", include_str!("../shaders/audio.wgsl")),
+    (
+        "playground.wgsl",
+        include_str!("../shaders/playground.wgsl"),
+    ),
+    ("shadow.wgsl", include_str!("../shaders/shadow.wgsl")),
+];
+
+/// 展开后的源码，并记录每一行的出处，用来把诊断位置换算回原始源码
+pub struct Preprocessed {
+    pub source: String,
+    origins: Vec<Origin>,
+    // 主源码每行起始的字节偏移
+    line_starts: Vec<u32>,
+}
+
+// 展开后的一行来自哪里
+struct Origin {
+    file: Option<&'static str>, // None 为主源码
+    line: u32,                  // 原始行号，从 1 开始
+    replaced: Vec<Replaced>,    // 这一行被 #define 替换掉的词，按位置排好
+}
+
+// 行内被替换的一个词，位置和长度都是字节
+struct Replaced {
+    expanded: u32,
+    expanded_len: u32,
+    original: u32,
+    original_len: u32,
+}
+
+impl Origin {
+    // 展开后行内的位置换回原始行；落在替换值里面的算到原来那个词的开头（`end` 时算到结尾）
+    fn original_position(&self, position: u32, end: bool) -> u32 {
+        let mut delta = 0i64;
+        for r in &self.replaced {
+            let (from, to) = (r.expanded, r.expanded + r.expanded_len);
+            if position < from || (end && position == from) {
+                break;
+            }
+            if position < to || (end && position == to) {
+                return if end {
+                    r.original + r.original_len
+                } else {
+                    r.original
+                };
+            }
+            delta = (r.original + r.original_len) as i64 - to as i64;
+        }
+        (position as i64 + delta) as u32
+    }
+}
+
+impl Preprocessed {
+    /// 把展开后源码上的诊断换算到原始源码，被 #define 替换过的行按替换前的列算；
+    /// 落在 include 片段里的诊断行列置 0，消息里带上片段名
+    pub fn locate(&self, mut d: Diagnostic) -> Diagnostic {
+        if d.line == 0 {
+            return d;
+        }
+        let Some(origin) = self.origins.get(d.line as usize - 1) else {
+            return d;
+        };
+        match origin.file {
+            None => {
+                let start = d.column.saturating_sub(1);
+                let column = origin.original_position(start, false);
+                if d.length > 0 {
+                    d.length = origin.original_position(start + d.length, true) - column;
+                }
+                if d.column > 0 {
+                    d.column = column + 1;
+                }
+                d.line = origin.line;
+                d.offset = self.line_starts[origin.line as usize - 1] + column;
+            }
+            Some(file) => {
+                d.message = format!("(in {}:{}) {}", file, origin.line, d.message);
+                d.line = 0;
+                d.column = 0;
+                d.offset = 0;
+                d.length = 0;
+            }
+        }
+        d
+    }
+}
+
+pub fn preprocess(source: &str, defines: &[(&str, &str)]) -> Result<Preprocessed, Diagnostic> {
+    let mut state = State {
+        defines: defines
+            .iter()
+            .map(|&(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        included: Vec::new(),
+        conds: Vec::new(),
+        source: String::new(),
+        origins: Vec::new(),
+    };
+    state.process(None, source)?;
+    let line_starts = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i as u32 + 1))
+        .collect();
+    Ok(Preprocessed {
+        source: state.source,
+        origins: state.origins,
+        line_starts,
+    })
+}
+
+struct Cond {
+    parent: bool, // 外层是否生效
+    taken: bool,  // #ifdef 的条件是否成立
+    has_else: bool,
+}
+
+struct State {
+    defines: HashMap<String, String>,
+    included: Vec<&'static str>,
+    conds: Vec<Cond>,
+    source: String,
+    origins: Vec<Origin>,
+}
+
+impl State {
+    fn active(&self) -> bool {
+        self.conds
+            .last()
+            .is_none_or(|c| c.parent && c.taken != c.has_else)
+    }
+
+    fn process(&mut self, file: Option<&'static str>, source: &str) -> Result<(), Diagnostic> {
+        let depth = self.conds.len();
+        let mut offset = 0;
+        for (i, line) in source.lines().enumerate() {
+            let line_no = i as u32 + 1;
+            let trimmed = line.trim_start();
+            if let Some(directive) = trimmed.strip_prefix('#') {
+                let error = |message: &str| {
+                    let indent = (line.len() - trimmed.len()) as u32;
+                    located(
+                        file,
+                        line_no,
+                        indent,
+                        offset + indent,
+                        trimmed.len(),
+                        message,
+                    )
+                };
+                self.directive(directive, depth).map_err(|e| error(&e))?;
+                if let Some(include) = self.take_include(directive).map_err(|e| error(&e))? {
+                    self.process(Some(include.0), include.1)?;
+                }
+            } else if self.active() {
+                let (expanded, replaced) = self.substitute(line);
+                self.source.push_str(&expanded);
+                self.source.push('\n');
+                self.origins.push(Origin {
+                    file,
+                    line: line_no,
+                    replaced,
+                });
+            }
+            offset += line.len() as u32 + 1;
+        }
+        if self.conds.len() > depth {
+            self.conds.truncate(depth);
+            return Err(located(file, 0, 0, 0, 0, "unterminated #ifdef"));
+        }
+        Ok(())
+    }
+
+    fn directive(&mut self, directive: &str, depth: usize) -> Result<(), String> {
+        let (name, rest) = directive
+            .split_once(char::is_whitespace)
+            .map_or((directive, ""), |(name, rest)| (name, rest.trim()));
+        match name {
+            "ifdef" | "ifndef" => {
+                let defined = self.defines.contains_key(identifier(rest)?);
+                let parent = self.active();
+                self.conds.push(Cond {
+                    parent,
+                    taken: defined == (name == "ifdef"),
+                    has_else: false,
+                });
+            }
+            "else" | "endif" if self.conds.len() <= depth => {
+                return Err(format!("#{} without #ifdef", name));
+            }
+            "else" => {
+                let cond = self.conds.last_mut().unwrap();
+                if cond.has_else {
+                    return Err("duplicate #else".to_string());
+                }
+                cond.has_else = true;
+            }
+            "endif" => {
+                self.conds.pop();
+            }
+            // 以下只在生效的分支里处理
+            _ if !self.active() => {}
+            "define" => {
+                let (name, value) = rest
+                    .split_once(char::is_whitespace)
+                    .map_or((rest, ""), |(name, value)| (name, value.trim()));
+                let (value, _) = self.substitute(value);
+                self.defines.insert(identifier(name)?.to_string(), value);
+            }
+            "undef" => {
+                self.defines.remove(identifier(rest)?);
+            }
+            "include" => {}
+            _ => return Err(format!("unknown directive #{}", name)),
+        }
+        Ok(())
+    }
+
+    // #include 单独处理，返回需要展开的片段；已经引入过的跳过
+    fn take_include(
+        &mut self,
+        directive: &str,
+    ) -> Result<Option<(&'static str, &'static str)>, String> {
+        let Some(rest) = directive.strip_prefix("include") else {
+            return Ok(None);
+        };
+        if !self.active() {
+            return Ok(None);
+        }
+        let name = rest
+            .trim()
+            .strip_prefix('"')
+            .and_then(|r| r.strip_suffix('"'))
+            .ok_or_else(|| format!("expected #include \"name\", found #include{}", rest))?;
+        let (name, source) = INCLUDES
+            .iter()
+            .copied()
+            .chain(shaders::includes().iter().map(|(n, s)| (*n, s.as_str())))
+            .find(|(n, _)| *n == name)
+            .ok_or_else(|| format!("unknown include \"{}\"", name))?;
+        if self.included.contains(&name) {
+            return Ok(None);
+        }
+        self.included.push(name);
+        Ok(Some((name, source)))
+    }
+
+    // 按标识符整词替换，数字字面量（如 1u、2.0f）不会被拆开；同时记下替换了哪些词，诊断要换算回去
+    fn substitute(&self, line: &str) -> (String, Vec<Replaced>) {
+        let mut replaced = Vec::new();
+        if self.defines.values().all(String::is_empty) {
+            return (line.to_string(), replaced);
+        }
+        let mut out = String::with_capacity(line.len());
+        let mut push_word = |out: &mut String, start: usize, word: &str| {
+            let value = self.expand(word);
+            if value != word {
+                replaced.push(Replaced {
+                    expanded: out.len() as u32,
+                    expanded_len: value.len() as u32,
+                    original: start as u32,
+                    original_len: word.len() as u32,
+                });
+            }
+            out.push_str(value);
+        };
+        let mut start = None;
+        for (i, c) in line.char_indices() {
+            let word = c.is_alphanumeric() || c == '_';
+            match (start, word) {
+                (None, true) => start = Some(i),
+                (Some(s), false) => {
+                    push_word(&mut out, s, &line[s..i]);
+                    start = None;
+                    out.push(c);
+                }
+                (None, false) => out.push(c),
+                (Some(_), true) => {}
+            }
+        }
+        if let Some(s) = start {
+            push_word(&mut out, s, &line[s..]);
+        }
+        (out, replaced)
+    }
+
+    fn expand<'a>(&'a self, word: &'a str) -> &'a str {
+        match self.defines.get(word) {
+            Some(value) if !value.is_empty() => value,
+            _ => word,
+        }
+    }
+}
+
+fn identifier(name: &str) -> Result<&str, String> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_');
+    if valid {
+        Ok(name)
+    } else {
+        Err(format!("expected an identifier, found \"{}\"", name))
+    }
+}
+
+// 主源码里的错误带行列号；片段里的错误行列置 0，消息里带上片段名和行号
+fn located(
+    file: Option<&str>,
+    line: u32,
+    indent: u32,
+    offset: u32,
+    length: usize,
+    message: &str,
+) -> Diagnostic {
+    let mut d = Diagnostic::error(message);
+    match file {
+        Some(file) => d.message = format!("(in {}:{}) {}", file, line, message),
+        None if line > 0 => {
+            d.line = line;
+            d.column = indent + 1;
+            d.offset = offset;
+            d.length = length as u32;
+        }
+        None => {}
+    }
+    d
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn include_once_and_locate() {
+        let source = "#include \"uniforms.wgsl\"\n#include \"uniforms.wgsl\"\nfn f() {}\n";
+        let out = preprocess(source, &[]).unwrap();
+        assert_eq!(out.source.matches("struct Uniforms").count(), 1);
+        assert!(out.source.ends_with("fn f() {}\n"));
+        // 最后一行对应主源码第 3 行
+        let last = out.source.lines().count() as u32;
+        let d = out.locate(Diagnostic {
+            line: last,
+            column: 4,
+            ..Diagnostic::error("x")
+        });
+        assert_eq!((d.line, d.column, d.offset), (3, 4, 53));
+        // 第 1 行来自片段
+        let d = out.locate(Diagnostic {
+            line: 1,
+            column: 1,
+            ..Diagnostic::error("x")
+        });
+        assert_eq!(d.line, 0);
+        assert!(
+            d.message.starts_with("(in uniforms.wgsl:1)"),
+            "{}",
+            d.message
+        );
+    }
+
+    #[test]
+    fn define_and_conditionals() {
+        let source = "#define TAPS 5\n\
+                      #ifdef PCF\n\
+                      let a = TAPS;\n\
+                      #ifndef FAST\n\
+                      let b = 1u;\n\
+                      #else\n\
+                      let b = 2u;\n\
+                      #endif\n\
+                      #else\n\
+                      let a = 1;\n\
+                      #endif\n\
+                      let TAPS_x = TAPS;\n";
+        let on = preprocess(source, &[("PCF", ""), ("FAST", "")]).unwrap();
+        assert_eq!(on.source, "let a = 5;\nlet b = 2u;\nlet TAPS_x = 5;\n");
+        let off = preprocess(source, &[]).unwrap();
+        assert_eq!(off.source, "let a = 1;\nlet TAPS_x = 5;\n");
+    }
+
+    #[test]
+    fn locate_on_substituted_lines() {
+        let source = "#define SCALE 2.0\nlet x = SCALE * foo;\n";
+        let out = preprocess(source, &[]).unwrap();
+        assert_eq!(out.source, "let x = 2.0 * foo;\n");
+        // 替换值后面的位置按原词的长度平移
+        let d = out.locate(Diagnostic {
+            line: 1,
+            column: 15,
+            length: 3,
+            ..Diagnostic::error("x")
+        });
+        assert_eq!((d.line, d.column, d.length), (2, 17, 3));
+        assert_eq!(
+            &source[d.offset as usize..(d.offset + d.length) as usize],
+            "foo"
+        );
+        // 落在替换值里面的指向原来的宏名
+        let d = out.locate(Diagnostic {
+            line: 1,
+            column: 10,
+            length: 1,
+            ..Diagnostic::error("x")
+        });
+        assert_eq!((d.column, d.length), (9, 5));
+        assert_eq!(
+            &source[d.offset as usize..(d.offset + d.length) as usize],
+            "SCALE"
+        );
+    }
+
+    #[test]
+    fn errors_have_location() {
+        let d = preprocess("fn f() {}\n  #include \"missing.wgsl\"\n", &[])
+            .err()
+            .unwrap();
+        assert_eq!((d.line, d.column, d.offset), (2, 3, 12));
+        assert!(d.message.contains("missing.wgsl"));
+
+        let d = preprocess("#ifdef A\nfn f() {}\n", &[]).err().unwrap();
+        assert!(d.message.contains("unterminated"));
+        let d = preprocess("#endif\n", &[]).err().unwrap();
+        assert_eq!(d.line, 1);
+    }
+}
@@ -0,0 +1,491 @@
+/// 从 naga 反射出 shader 的资源绑定，生成 bind group layout 和 pipeline layout，
+/// 并在创建 bind group 时检查 Rust 侧给的资源和 WGSL 声明是否一致（绑定号、资源种类、buffer 大小）
+use std::collections::BTreeMap;
+
+use naga::{AddressSpace, ImageClass, ImageDimension, ScalarKind, StorageAccess, TypeInner};
+
+use super::{pipeline_cache::PipelineCache, shader_debug::ValidatedShader};
+
+/// 一个 shader（或 vs/fs 分开的几个 shader）声明的全部绑定，按 group 分组
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bindings {
+    groups: BTreeMap<u32, Vec<Slot>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Slot {
+    name: String,
+    entry: wgpu::BindGroupLayoutEntry,
+}
+
+impl Bindings {
+    /// 合并几个 shader 的绑定，同一个位置在不同 shader 里类型不一致时报错
+    pub fn new(shaders: &[&ValidatedShader]) -> Result<Self, String> {
+        let mut bindings = Bindings {
+            groups: BTreeMap::new(),
+        };
+        for shader in shaders {
+            for (slot, group) in reflect(shader)? {
+                let slots = bindings.groups.entry(group).or_default();
+                match slots
+                    .iter_mut()
+                    .find(|s| s.entry.binding == slot.entry.binding)
+                {
+                    Some(s) if s.entry.ty != slot.entry.ty => {
+                        return Err(format!(
+                            "@group({}) @binding({}) is `{}` and `{}` with different types",
+                            group, slot.entry.binding, s.name, slot.name
+                        ));
+                    }
+                    Some(s) => s.entry.visibility |= slot.entry.visibility,
+                    None => slots.push(slot),
+                }
+            }
+        }
+        for slots in bindings.groups.values_mut() {
+            slots.sort_by_key(|s| s.entry.binding);
+        }
+        Ok(bindings)
+    }
+
+    /// 只有声明、没有入口点的 WGSL 反射不出可见性，由用它的管线决定，这里统一指定
+    pub fn with_visibility(mut self, visibility: wgpu::ShaderStages) -> Self {
+        for slot in self.groups.values_mut().flatten() {
+            slot.entry.visibility = visibility;
+        }
+        self
+    }
+
+    /// group 数量，按最大的 group 号算，中间空着的 group 也占位
+    pub fn group_count(&self) -> u32 {
+        self.groups.keys().next_back().map_or(0, |g| g + 1)
+    }
+
+    pub fn entries(&self, group: u32) -> Vec<wgpu::BindGroupLayoutEntry> {
+        self.groups
+            .get(&group)
+            .map(|slots| slots.iter().map(|s| s.entry).collect())
+            .unwrap_or_default()
+    }
+
+    /// 检查 bind group 的资源：每个绑定都给了，种类对得上，buffer 不小于 WGSL 类型的大小
+    pub fn check_resources(&self, group: u32, resources: &[(u32, Resource)]) -> Vec<String> {
+        let mut errors = Vec::new();
+        let slots = self.groups.get(&group).map_or(&[][..], Vec::as_slice);
+        for slot in slots {
+            let at = format!(
+                "@group({}) @binding({}) `{}`",
+                group, slot.entry.binding, slot.name
+            );
+            let Some(&(_, resource)) = resources.iter().find(|(b, _)| *b == slot.entry.binding)
+            else {
+                errors.push(format!("{} is not bound", at));
+                continue;
+            };
+            match (slot.entry.ty, resource) {
+                (
+                    wgpu::BindingType::Buffer {
+                        min_binding_size: Some(min),
+                        ..
+                    },
+                    Resource::Buffer { size },
+                ) if size < min.get() => errors.push(format!(
+                    "{} needs at least {} bytes but the buffer has {}",
+                    at,
+                    min.get(),
+                    size
+                )),
+                (wgpu::BindingType::Buffer { .. }, Resource::Buffer { .. })
+                | (wgpu::BindingType::Sampler(_), Resource::Sampler)
+                | (wgpu::BindingType::Texture { .. }, Resource::TextureView)
+                | (wgpu::BindingType::StorageTexture { .. }, Resource::TextureView) => {}
+                (ty, resource) => {
+                    errors.push(format!("{} expects {:?} but got a {:?}", at, ty, resource))
+                }
+            }
+        }
+        for (binding, _) in resources {
+            if !slots.iter().any(|s| s.entry.binding == *binding) {
+                errors.push(format!(
+                    "@group({}) @binding({}) is bound but not declared in WGSL",
+                    group, binding
+                ));
+            }
+        }
+        errors
+    }
+}
+
+/// bind group 里一个资源的种类，buffer 带上实际绑定的字节数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resource {
+    Buffer { size: u64 },
+    Sampler,
+    TextureView,
+    Other,
+}
+
+impl Resource {
+    pub fn of(resource: &wgpu::BindingResource) -> Self {
+        match resource {
+            wgpu::BindingResource::Buffer(b) => Resource::Buffer {
+                size: b
+                    .size
+                    .map_or(b.buffer.size().saturating_sub(b.offset), |s| s.get()),
+            },
+            wgpu::BindingResource::Sampler(_) => Resource::Sampler,
+            wgpu::BindingResource::TextureView(_) => Resource::TextureView,
+            _ => Resource::Other,
+        }
+    }
+}
+
+fn reflect(shader: &ValidatedShader) -> Result<Vec<(Slot, u32)>, String> {
+    let module = &shader.module;
+    let mut slots = Vec::new();
+    for (handle, var) in module.global_variables.iter() {
+        let Some(binding) = &var.binding else {
+            continue;
+        };
+        let name = var.name.clone().unwrap_or_default();
+        // 哪些入口点用到了它；都没用到时按模块里所有入口点的阶段算，保持和 WGSL 声明一致
+        let mut visibility = wgpu::ShaderStages::NONE;
+        let mut all = wgpu::ShaderStages::NONE;
+        for (i, ep) in module.entry_points.iter().enumerate() {
+            let stage = stage(ep.stage);
+            all |= stage;
+            if !shader.info.get_entry_point(i)[handle].is_empty() {
+                visibility |= stage;
+            }
+        }
+        if visibility.is_empty() {
+            visibility = all;
+        }
+        let ty = binding_type(module, var).map_err(|e| format!("`{}`: {}", name, e))?;
+        slots.push((
+            Slot {
+                name,
+                entry: wgpu::BindGroupLayoutEntry {
+                    binding: binding.binding,
+                    visibility,
+                    ty,
+                    count: None,
+                },
+            },
+            binding.group,
+        ));
+    }
+    Ok(slots)
+}
+
+fn stage(stage: naga::ShaderStage) -> wgpu::ShaderStages {
+    match stage {
+        naga::ShaderStage::Vertex => wgpu::ShaderStages::VERTEX,
+        naga::ShaderStage::Fragment => wgpu::ShaderStages::FRAGMENT,
+        naga::ShaderStage::Compute => wgpu::ShaderStages::COMPUTE,
+    }
+}
+
+fn binding_type(
+    module: &naga::Module,
+    var: &naga::GlobalVariable,
+) -> Result<wgpu::BindingType, String> {
+    let inner = &module.types[var.ty].inner;
+    let buffer = |ty| {
+        // 末尾是运行时数组时 size 只算一个元素，正好是 WebGPU 要求的最小绑定大小
+        let size = inner.size(module.to_ctx()) as u64;
+        wgpu::BindingType::Buffer {
+            ty,
+            has_dynamic_offset: false,
+            min_binding_size: wgpu::BufferSize::new(size),
+        }
+    };
+    Ok(match (var.space, inner) {
+        (AddressSpace::Uniform, _) => buffer(wgpu::BufferBindingType::Uniform),
+        (AddressSpace::Storage { access }, _) => buffer(wgpu::BufferBindingType::Storage {
+            read_only: !access.contains(StorageAccess::STORE),
+        }),
+        (_, TypeInner::Sampler { comparison }) => wgpu::BindingType::Sampler(if *comparison {
+            wgpu::SamplerBindingType::Comparison
+        } else {
+            wgpu::SamplerBindingType::Filtering
+        }),
+        (
+            _,
+            TypeInner::Image {
+                dim,
+                arrayed,
+                class,
+            },
+        ) => {
+            let view_dimension = view_dimension(*dim, *arrayed);
+            match *class {
+                ImageClass::Depth { multi } => wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension,
+                    multisampled: multi,
+                },
+                ImageClass::Sampled { kind, multi } => wgpu::BindingType::Texture {
+                    sample_type: match kind {
+                        ScalarKind::Sint => wgpu::TextureSampleType::Sint,
+                        ScalarKind::Uint => wgpu::TextureSampleType::Uint,
+                        _ => wgpu::TextureSampleType::Float { filterable: !multi },
+                    },
+                    view_dimension,
+                    multisampled: multi,
+                },
+                ImageClass::Storage { format, access } => wgpu::BindingType::StorageTexture {
+                    access: match (
+                        access.contains(StorageAccess::LOAD),
+                        access.contains(StorageAccess::STORE),
+                    ) {
+                        (true, true) => wgpu::StorageTextureAccess::ReadWrite,
+                        (true, false) => wgpu::StorageTextureAccess::ReadOnly,
+                        _ => wgpu::StorageTextureAccess::WriteOnly,
+                    },
+                    format: storage_format(format)?,
+                    view_dimension,
+                },
+            }
+        }
+        (space, inner) => return Err(format!("unsupported binding {:?} {:?}", space, inner)),
+    })
+}
+
+fn view_dimension(dim: ImageDimension, arrayed: bool) -> wgpu::TextureViewDimension {
+    match (dim, arrayed) {
+        (ImageDimension::D1, _) => wgpu::TextureViewDimension::D1,
+        (ImageDimension::D2, false) => wgpu::TextureViewDimension::D2,
+        (ImageDimension::D2, true) => wgpu::TextureViewDimension::D2Array,
+        (ImageDimension::D3, _) => wgpu::TextureViewDimension::D3,
+        (ImageDimension::Cube, false) => wgpu::TextureViewDimension::Cube,
+        (ImageDimension::Cube, true) => wgpu::TextureViewDimension::CubeArray,
+    }
+}
+
+// WebGPU 核心规范里允许做 storage texture 的格式
+fn storage_format(format: naga::StorageFormat) -> Result<wgpu::TextureFormat, String> {
+    use naga::StorageFormat as S;
+    use wgpu::TextureFormat as T;
+    Ok(match format {
+        S::Rgba8Unorm => T::Rgba8Unorm,
+        S::Rgba8Snorm => T::Rgba8Snorm,
+        S::Rgba8Uint => T::Rgba8Uint,
+        S::Rgba8Sint => T::Rgba8Sint,
+        S::Bgra8Unorm => T::Bgra8Unorm,
+        S::Rgba16Uint => T::Rgba16Uint,
+        S::Rgba16Sint => T::Rgba16Sint,
+        S::Rgba16Float => T::Rgba16Float,
+        S::R32Uint => T::R32Uint,
+        S::R32Sint => T::R32Sint,
+        S::R32Float => T::R32Float,
+        S::Rg32Uint => T::Rg32Uint,
+        S::Rg32Sint => T::Rg32Sint,
+        S::Rg32Float => T::Rg32Float,
+        S::Rgba32Uint => T::Rgba32Uint,
+        S::Rgba32Sint => T::Rgba32Sint,
+        S::Rgba32Float => T::Rgba32Float,
+        other => return Err(format!("unsupported storage texture format {:?}", other)),
+    })
+}
+
+/// 按反射结果建好的 layout。`shared` 里的 group 直接用外部的 layout（比如 World 的 uniforms、
+/// AudioReactive），这些 group 的 bind group 也由外部创建。
+/// layout 经 `PipelineCache` 去重，外部 layout 也从那里创建，重新 ready 时管线才能复用
+pub struct ShaderLayout {
+    pub bindings: Bindings,
+    pub bind_group_layouts: Vec<wgpu::BindGroupLayout>,
+    pub pipeline_layout: wgpu::PipelineLayout,
+}
+
+impl ShaderLayout {
+    pub fn new(
+        cache: &PipelineCache,
+        label: &str,
+        bindings: Bindings,
+        shared: &[(u32, &wgpu::BindGroupLayout)],
+    ) -> Self {
+        let group_count = shared
+            .iter()
+            .map(|(g, _)| g + 1)
+            .fold(bindings.group_count(), u32::max);
+        let bind_group_layouts: Vec<_> = (0..group_count)
+            .map(|group| match shared.iter().find(|(g, _)| *g == group) {
+                Some((_, layout)) => (*layout).clone(),
+                None => cache.bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some(&format!("{} group {}", label, group)),
+                    entries: &bindings.entries(group),
+                }),
+            })
+            .collect();
+        let pipeline_layout = cache.pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(label),
+            bind_group_layouts: &bind_group_layouts.iter().collect::<Vec<_>>(),
+            push_constant_ranges: &[],
+        });
+        ShaderLayout {
+            bindings,
+            bind_group_layouts,
+            pipeline_layout,
+        }
+    }
+
+    /// 给 `shared` 用的单个 group：`declaration` 只在 @group(0) 声明绑定，没有入口点，
+    /// 各个管线自己决定把它放在第几个 group
+    pub fn shared_group(
+        cache: &PipelineCache,
+        label: &str,
+        declaration: &str,
+        visibility: wgpu::ShaderStages,
+    ) -> Self {
+        let (_, reflect) = cache.validated_shader(label, declaration, &[]).unwrap();
+        let bindings = Bindings::new(&[&reflect])
+            .unwrap()
+            .with_visibility(visibility);
+        ShaderLayout::new(cache, label, bindings, &[])
+    }
+
+    /// 先检查资源和 WGSL 是否一致，不一致时打印出来（wgpu 之后也会报验证错误），再创建 bind group
+    pub fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        label: &str,
+        group: u32,
+        entries: &[wgpu::BindGroupEntry],
+    ) -> wgpu::BindGroup {
+        let resources: Vec<_> = entries
+            .iter()
+            .map(|e| (e.binding, Resource::of(&e.resource)))
+            .collect();
+        for error in self.bindings.check_resources(group, &resources) {
+            log::error!("bind group '{}': {}", label, error);
+        }
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(label),
+            layout: &self.bind_group_layouts[group as usize],
+            entries,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::shader_debug::preprocess_and_validate;
+
+    fn bindings(sources: &[&str]) -> Bindings {
+        let shaders: Vec<_> = sources
+            .iter()
+            .map(|s| preprocess_and_validate(s, &[("SHADOW_PCF", "")]).unwrap().1)
+            .collect();
+        Bindings::new(&shaders.iter().collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn boids_compute_layout() {
+        let b = bindings(&[include_str!("../demos/level2/spatial_hashing/compute.wgsl")]);
+        assert_eq!(b.group_count(), 1);
+        let entries = b.entries(0);
+        let ty = |e: &wgpu::BindGroupLayoutEntry| match e.ty {
+            wgpu::BindingType::Buffer {
+                ty,
+                min_binding_size,
+                ..
+            } => (ty, min_binding_size.map(|s| s.get())),
+            _ => panic!("{:?}", e.ty),
+        };
+        assert_eq!(
+            entries.iter().map(ty).collect::<Vec<_>>(),
+            [
+                (wgpu::BufferBindingType::Uniform, Some(28)),
+                (
+                    wgpu::BufferBindingType::Storage { read_only: true },
+                    Some(16)
+                ),
+                (
+                    wgpu::BufferBindingType::Storage { read_only: false },
+                    Some(16)
+                ),
+            ]
+        );
+        assert!(entries
+            .iter()
+            .all(|e| e.visibility == wgpu::ShaderStages::COMPUTE));
+    }
+
+    #[test]
+    fn shadow_cube_layout_matches_hand_written() {
+        let b = bindings(&[include_str!("../demos/level2/shadow/cube.wgsl")]);
+        let fragment = wgpu::ShaderStages::FRAGMENT;
+        let hand_written = [
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: fragment,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: fragment,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::VERTEX | fragment,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(80),
+                },
+                count: None,
+            },
+        ];
+        assert_eq!(b.entries(0), hand_written);
+    }
+
+    #[test]
+    fn shared_declaration_takes_given_visibility() {
+        let declaration = "#include \"uniforms.wgsl\"\n\
+                           @group(0) @binding(0) var<uniform> uniforms: Uniforms;";
+        let b = bindings(&[declaration]);
+        // 没有入口点，反射不出可见性
+        assert_eq!(b.entries(0)[0].visibility, wgpu::ShaderStages::NONE);
+        let visibility = wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT;
+        let hand_written = wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: wgpu::BufferSize::new(
+                    size_of::<crate::shaders::Uniforms>() as u64
+                ),
+            },
+            count: None,
+        };
+        assert_eq!(b.with_visibility(visibility).entries(0), [hand_written]);
+    }
+
+    #[test]
+    fn check_resources() {
+        let b = bindings(&[include_str!("../demos/level2/shadow/cube.wgsl")]);
+        let ok = [
+            (0, Resource::TextureView),
+            (1, Resource::Sampler),
+            (2, Resource::Buffer { size: 80 }),
+        ];
+        assert!(b.check_resources(0, &ok).is_empty());
+        let wrong = [
+            (0, Resource::Sampler),
+            (2, Resource::Buffer { size: 64 }),
+            (3, Resource::Sampler),
+        ];
+        // 种类不对、缺绑定、buffer 太小、多出来的绑定
+        assert_eq!(b.check_resources(0, &wrong).len(), 4);
+    }
+}
@@ -1,62 +1,320 @@
+/// WGSL 校验：用 naga 解析和验证，错误带行列号，不依赖 GPU，本地也能跑
+/// 本地测试：cargo test --target x86_64-unknown-linux-gnu（默认 target 是 wasm32）
+use std::fmt;
+
+use naga::valid::{Capabilities, ModuleInfo, ValidationFlags, Validator};
 use wasm_bindgen::JsValue;
+
+use super::preprocess::{preprocess, Preprocessed};
 use wgpu::*;
 
-// 打印着色器编译错误的辅助函数
-pub fn create_shader_with_debug(
+/// 一条编译诊断，行列从 1 开始；没有位置的消息行列为 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub kind: &'static str, // "error" / "warning" / "info"
+    pub message: String,
+    pub line: u32,
+    pub column: u32,
+    pub offset: u32,
+    pub length: u32,
+}
+
+impl Diagnostic {
+    pub fn error(message: &str) -> Self {
+        Diagnostic {
+            kind: "error",
+            message: message.to_string(),
+            line: 0,
+            column: 0,
+            offset: 0,
+            length: 0,
+        }
+    }
+
+    fn at(kind: &'static str, message: String, location: Option<naga::SourceLocation>) -> Self {
+        let l = location.unwrap_or(naga::SourceLocation {
+            line_number: 0,
+            line_position: 0,
+            offset: 0,
+            length: 0,
+        });
+        Diagnostic {
+            kind,
+            message,
+            line: l.line_number,
+            column: l.line_position,
+            offset: l.offset,
+            length: l.length,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line > 0 {
+            write!(
+                f,
+                "{} at {}:{}: {}",
+                self.kind, self.line, self.column, self.message
+            )
+        } else {
+            write!(f, "{}: {}", self.kind, self.message)
+        }
+    }
+}
+
+/// 验证通过的模块，后面做反射（入口点、绑定）时直接用
+pub struct ValidatedShader {
+    pub module: naga::Module,
+    pub info: ModuleInfo,
+}
+
+impl ValidatedShader {
+    pub fn entry_points(&self) -> impl Iterator<Item = (naga::ShaderStage, &str)> {
+        self.module
+            .entry_points
+            .iter()
+            .map(|ep| (ep.stage, ep.name.as_str()))
+    }
+}
+
+/// 解析并验证 WGSL；失败时第一条是错误本身，后面是相关位置的说明
+/// 只有 compute 入口的模块（比如 compute.wgsl）也是合法的，入口点检查交给调用方
+pub fn validate_wgsl(source: &str) -> Result<ValidatedShader, Vec<Diagnostic>> {
+    let module = naga::front::wgsl::parse_str(source).map_err(|e| {
+        let mut diagnostics = vec![Diagnostic::at(
+            "error",
+            e.message().to_string(),
+            e.location(source),
+        )];
+        diagnostics.extend(
+            e.labels()
+                .filter(|(span, label)| span.is_defined() && !label.is_empty())
+                .map(|(span, label)| {
+                    Diagnostic::at("info", label.to_string(), Some(span.location(source)))
+                }),
+        );
+        diagnostics
+    })?;
+    let info = Validator::new(ValidationFlags::all(), Capabilities::default())
+        .validate(&module)
+        .map_err(|e| {
+            // span 从外到内（函数、语句、表达式），最里面的最具体，作为错误位置
+            let mut spans: Vec<_> = e.spans().collect();
+            let primary = spans.pop().map(|(span, _)| span.location(source));
+            let mut diagnostics = vec![Diagnostic::at("error", error_chain(e.as_inner()), primary)];
+            diagnostics.extend(spans.into_iter().rev().map(|(span, label)| {
+                Diagnostic::at("info", label.clone(), Some(span.location(source)))
+            }));
+            diagnostics
+        })?;
+    Ok(ValidatedShader { module, info })
+}
+
+// 验证错误是嵌套的（函数 -> 表达式 -> 具体原因），拼成一行
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(e) = source {
+        message.push_str(": ");
+        message.push_str(&e.to_string());
+        source = e.source();
+    }
+    message
+}
+
+/// 预处理（见 `preprocess`）后用 naga 验证，出错时打印换算回原始源码的诊断并返回错误，不再交给 GPU；
+/// 另外返回验证结果，用来做反射（见 `reflect`）。各 system 一般经 `PipelineCache` 调用，同样的源码只编译一次
+pub fn create_validated_shader(
     device: &Device,
     label: &str,
     source: &str,
-) -> Result<ShaderModule, JsValue> {
-    let result = device.create_shader_module(ShaderModuleDescriptor {
+    defines: &[(&str, &str)],
+) -> Result<(ShaderModule, ValidatedShader), JsValue> {
+    let (source, shader) = preprocess_and_validate(source, defines).map_err(|diagnostics| {
+        let text = diagnostics
+            .iter()
+            .map(Diagnostic::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        log::error!("着色器 '{}' 验证失败:\n{}", label, text);
+        JsValue::from_str(&text)
+    })?;
+    log::debug!(
+        "着色器 '{}' 验证通过，入口点: {:?}",
+        label,
+        shader.entry_points().collect::<Vec<_>>()
+    );
+
+    let module = device.create_shader_module(ShaderModuleDescriptor {
         label: Some(label),
-        source: ShaderSource::Wgsl(source.into()),
+        source: ShaderSource::Wgsl(source.source.into()),
     });
+    Ok((module, shader))
+}
+
+/// 展开 include / define 后验证，诊断位置换算回原始源码
+pub fn preprocess_and_validate(
+    source: &str,
+    defines: &[(&str, &str)],
+) -> Result<(Preprocessed, ValidatedShader), Vec<Diagnostic>> {
+    let preprocessed = preprocess(source, defines).map_err(|d| vec![d])?;
+    match validate_wgsl(&preprocessed.source) {
+        Ok(shader) => Ok((preprocessed, shader)),
+        Err(diagnostics) => Err(diagnostics
+            .into_iter()
+            .map(|d| preprocessed.locate(d))
+            .collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use naga::ShaderStage::{Compute, Fragment, Vertex};
+
+    type EntryPoints = &'static [(naga::ShaderStage, &'static str)];
+    const RENDER: EntryPoints = &[(Vertex, "vs_main"), (Fragment, "fs_main")];
+
+    // 所有编进程序的 shader，以及各自必须有的入口点
+    fn embedded() -> Vec<(&'static str, &'static str, EntryPoints)> {
+        vec![
+            (
+                "boids.compute",
+                include_str!("../demos/level2/spatial_hashing/compute.wgsl"),
+                &[(Compute, "main")],
+            ),
+            (
+                "boids.draw",
+                include_str!("../demos/level2/spatial_hashing/draw.wgsl"),
+                RENDER,
+            ),
+            (
+                "shadow.cube",
+                include_str!("../demos/level2/shadow/cube.wgsl"),
+                RENDER,
+            ),
+            (
+                "shadow.model",
+                include_str!("../demos/level2/shadow/model.wgsl"),
+                RENDER,
+            ),
+            (
+                "shadow.depth",
+                include_str!("../demos/level2/shadow/depth.wgsl"),
+                &[(Vertex, "vs_main")],
+            ),
+            (
+                "object",
+                include_str!("../demos/update_scene/object.wgsl"),
+                RENDER,
+            ),
+            (
+                "triangle_list",
+                include_str!("../demos/update_scene/triangle_list.wgsl"),
+                RENDER,
+            ),
+            ("vr", include_str!("../demos/update_scene/vr.wgsl"), RENDER),
+            (
+                "vr_background",
+                include_str!("../demos/update_scene/vr_background.wgsl"),
+                RENDER,
+            ),
+            (
+                "shadow_depth",
+                include_str!("../demos/update_scene/shadow_depth.wgsl"),
+                &[(Vertex, "vs_main")],
+            ),
+            (
+                "second_depth",
+                include_str!("../demos/update_scene/second_depth.wgsl"),
+                RENDER,
+            ),
+            (
+                "wireframe",
+                include_str!("../shaders/wireframe.wgsl"),
+                RENDER,
+            ),
+            (
+                "instanced",
+                include_str!("../shaders/instanced.wgsl"),
+                RENDER,
+            ),
+            (
+                "playground.fluid",
+                include_str!("../shaders/fluid.wgsl"),
+                RENDER,
+            ),
+            (
+                "playground.boids",
+                include_str!("../shaders/boids.wgsl"),
+                RENDER,
+            ),
+            (
+                "playground.custom",
+                include_str!("../shaders/template.wgsl"),
+                RENDER,
+            ),
+        ]
+    }
 
-    // 在控制台记录着色器创建结果
-    web_sys::console::log_1(&JsValue::from_str(&format!(
-        "创建着色器模块 '{}': 成功",
-        label
-    )));
-
-    // 记录着色器源码摘要
-    let preview = if source.len() > 100 {
-        format!("{}...", &source[..100])
-    } else {
-        source.to_string()
-    };
-    web_sys::console::log_1(&JsValue::from_str(&format!("着色器源码预览:\n{}", preview)));
-
-    Ok(result)
-}
-
-// 验证WGSL语法
-pub fn validate_wgsl(source: &str) -> bool {
-    // 检查关键入口点
-    let has_vertex = source.contains("fn vs_main");
-    let has_fragment = source.contains("fn fs_main");
-
-    if !has_vertex || !has_fragment {
-        web_sys::console::error_1(&JsValue::from_str(&format!(
-            "着色器缺少入口点: vs_main={}, fs_main={}",
-            has_vertex, has_fragment
-        )));
-        return false;
-    }
-
-    // 基本语法检查
-    let basic_checks = [
-        ("@vertex", "缺少@vertex标记"),
-        ("@fragment", "缺少@fragment标记"),
-        ("@location", "缺少@location属性"),
-        ("@builtin", "缺少@builtin属性"),
-    ];
-
-    for (pattern, error) in basic_checks {
-        if !source.contains(pattern) {
-            web_sys::console::error_1(&JsValue::from_str(error));
-            return false;
+    #[test]
+    fn embedded_shaders_are_valid() {
+        // 带开关的 shader 每种组合都要能编译
+        const DEFINES: &[&[(&str, &str)]] = &[&[], &[("SHADOW_PCF", "")]];
+        for (name, source, entry_points) in embedded() {
+            for defines in DEFINES {
+                let (_, shader) =
+                    preprocess_and_validate(source, defines).unwrap_or_else(|diagnostics| {
+                        panic!("{} {:?} is invalid: {:?}", name, defines, diagnostics);
+                    });
+                for &(stage, entry) in entry_points {
+                    assert!(
+                        shader.entry_points().any(|ep| ep == (stage, entry)),
+                        "{} has no {:?} entry point {}",
+                        name,
+                        stage,
+                        entry
+                    );
+                }
+            }
         }
     }
 
-    true
+    #[test]
+    fn compute_only_module() {
+        let shader = validate_wgsl(
+            "@group(0) @binding(0) var<storage, read_write> data: array<f32>;\n\
+             @compute @workgroup_size(64)\n\
+             fn main(@builtin(global_invocation_id) id: vec3<u32>) { data[id.x] *= 2.0; }\n",
+        )
+        .unwrap();
+        assert_eq!(
+            shader.entry_points().collect::<Vec<_>>(),
+            [(Compute, "main")]
+        );
+    }
+
+    #[test]
+    fn parse_error_has_location() {
+        let source = "fn main() {\n    let x = 1.0\n}\n";
+        let diagnostics = validate_wgsl(source).err().unwrap();
+        let error = &diagnostics[0];
+        assert_eq!(error.kind, "error");
+        assert_eq!((error.line, error.column), (3, 1));
+        assert_eq!(
+            &source[error.offset as usize..(error.offset + error.length) as usize],
+            "}"
+        );
+    }
+
+    #[test]
+    fn validation_error_has_location() {
+        let source = "fn f() -> f32 {\n    return 1u;\n}\n";
+        let diagnostics = validate_wgsl(source).err().unwrap();
+        let error = &diagnostics[0];
+        assert_eq!(error.kind, "error");
+        assert!(error.message.contains("'f'"), "{}", error.message);
+        assert_eq!((error.line, error.column), (2, 12));
+    }
 }
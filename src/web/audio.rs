@@ -0,0 +1,206 @@
+/// Web Audio 分析：麦克风或音频文件接到 AnalyserNode 上，每帧算出频段能量和响度
+/// AudioContext 要在用户手势之后才能发声，所以等 JS 选了音源才创建
+use std::{cell::RefCell, rc::Rc};
+
+use js_sys::Promise;
+use wasm_bindgen::{prelude::*, JsCast, JsValue};
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::{
+    window, AnalyserNode, AudioContext, AudioNode, HtmlAudioElement, MediaStream,
+    MediaStreamConstraints, MediaStreamTrack,
+};
+
+pub const BAND_COUNT: usize = 8;
+const FFT_SIZE: u32 = 2048;
+// 频段按对数均分在这个范围内
+const MIN_FREQUENCY: f32 = 40.;
+const MAX_FREQUENCY: f32 = 16000.;
+
+/// 一帧的分析结果，都归一化到 0..1，没有音源时全是 0
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AudioFeatures {
+    pub bands: [f32; BAND_COUNT], // 低频到高频
+    pub loudness: f32,            // 平滑后的 RMS
+    pub peak: f32,                // 这一帧波形的最大振幅
+}
+
+enum Source {
+    Microphone(MediaStream),
+    File(HtmlAudioElement),
+}
+
+struct Connected {
+    node: AudioNode,
+    source: Source,
+}
+
+#[derive(Default)]
+struct State {
+    context: Option<AudioContext>,
+    analyser: Option<AnalyserNode>,
+    input: Option<Connected>,
+    spectrum: Vec<f32>,
+    waveform: Vec<f32>,
+    features: AudioFeatures,
+}
+
+/// 全局唯一的分析器，scene 里通过 `Audio::current()` 拿到
+#[derive(Clone, Default)]
+pub struct Audio {
+    state: Rc<RefCell<State>>,
+}
+
+thread_local! {
+    static AUDIO: Audio = Audio::default();
+}
+
+impl Audio {
+    pub fn current() -> Self {
+        AUDIO.with(Clone::clone)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.state.borrow().input.is_some()
+    }
+
+    /// 每帧调用一次
+    pub fn analyse(&self) -> AudioFeatures {
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        let (Some(context), Some(analyser), Some(_)) =
+            (&state.context, &state.analyser, &state.input)
+        else {
+            state.features = AudioFeatures::default();
+            return state.features;
+        };
+        analyser.get_float_frequency_data(&mut state.spectrum);
+        analyser.get_float_time_domain_data(&mut state.waveform);
+
+        let min_db = analyser.min_decibels() as f32;
+        let max_db = analyser.max_decibels() as f32;
+        let bin_width = context.sample_rate() / FFT_SIZE as f32;
+        let max_frequency = MAX_FREQUENCY.min(context.sample_rate() * 0.5);
+        let ratio = (max_frequency / MIN_FREQUENCY).powf(1. / BAND_COUNT as f32);
+        let last_bin = state.spectrum.len().saturating_sub(1);
+        for (i, band) in state.features.bands.iter_mut().enumerate() {
+            let low = MIN_FREQUENCY * ratio.powi(i as i32);
+            let high = low * ratio;
+            let start = ((low / bin_width) as usize).min(last_bin);
+            let end = ((high / bin_width) as usize).clamp(start + 1, last_bin + 1);
+            let bins = &state.spectrum[start..end];
+            let level = bins
+                .iter()
+                .map(|db| ((db - min_db) / (max_db - min_db)).clamp(0., 1.))
+                .sum::<f32>()
+                / bins.len() as f32;
+            *band = level;
+        }
+
+        let (sum, peak) = state.waveform.iter().fold((0., 0f32), |(sum, peak), s| {
+            (sum + s * s, peak.max(s.abs()))
+        });
+        let rms = (sum / state.waveform.len().max(1) as f32).sqrt().min(1.);
+        // 频谱有 AnalyserNode 自带的平滑，响度自己平滑一下
+        state.features.loudness += (rms - state.features.loudness) * 0.3;
+        state.features.peak = peak.min(1.);
+        state.features
+    }
+
+    fn context(&self) -> Result<(AudioContext, AnalyserNode), JsValue> {
+        let mut state = self.state.borrow_mut();
+        if let (Some(context), Some(analyser)) = (&state.context, &state.analyser) {
+            return Ok((context.clone(), analyser.clone()));
+        }
+        let context = AudioContext::new()?;
+        let analyser = context.create_analyser()?;
+        analyser.set_fft_size(FFT_SIZE);
+        analyser.set_smoothing_time_constant(0.8);
+        let bins = analyser.frequency_bin_count() as usize;
+        state.spectrum = vec![0.; bins];
+        state.waveform = vec![0.; FFT_SIZE as usize];
+        state.context = Some(context.clone());
+        state.analyser = Some(analyser.clone());
+        Ok((context, analyser))
+    }
+
+    fn set_input(&self, input: Connected) {
+        self.state.borrow_mut().input = Some(input);
+    }
+
+    /// 断开并停止当前音源，analyser 的输出（接扬声器的那条）也断开
+    fn stop(&self) {
+        let mut state = self.state.borrow_mut();
+        if let Some(analyser) = &state.analyser {
+            let _ = analyser.disconnect();
+        }
+        let Some(old) = state.input.take() else {
+            return;
+        };
+        let _ = old.node.disconnect();
+        match old.source {
+            Source::Microphone(stream) => {
+                for track in stream.get_tracks().iter() {
+                    track.unchecked_into::<MediaStreamTrack>().stop();
+                }
+            }
+            Source::File(element) => {
+                let _ = element.pause();
+            }
+        }
+    }
+}
+
+/// 用麦克风做音源（不接扬声器，避免回授）
+#[wasm_bindgen]
+pub fn audio_use_microphone() -> Promise {
+    future_to_promise(async {
+        let audio = Audio::current();
+        audio.stop();
+        let (context, analyser) = audio.context()?;
+        JsFuture::from(context.resume()?).await?;
+        let constraints = MediaStreamConstraints::new();
+        constraints.set_audio(&JsValue::TRUE);
+        let devices = window().unwrap().navigator().media_devices()?;
+        let stream: MediaStream =
+            JsFuture::from(devices.get_user_media_with_constraints(&constraints)?)
+                .await?
+                .unchecked_into();
+        let node: AudioNode = context.create_media_stream_source(&stream)?.into();
+        node.connect_with_audio_node(&analyser)?;
+        audio.set_input(Connected {
+            node,
+            source: Source::Microphone(stream),
+        });
+        log::info!("audio: microphone");
+        Ok(JsValue::UNDEFINED)
+    })
+}
+
+/// 循环播放一个音频文件并分析，跨域文件需要服务器允许 CORS
+#[wasm_bindgen]
+pub fn audio_use_url(url: String) -> Promise {
+    future_to_promise(async move {
+        let audio = Audio::current();
+        audio.stop();
+        let (context, analyser) = audio.context()?;
+        JsFuture::from(context.resume()?).await?;
+        let element = HtmlAudioElement::new_with_src(&url)?;
+        element.set_cross_origin(Some("anonymous"));
+        element.set_loop(true);
+        let node: AudioNode = context.create_media_element_source(&element)?.into();
+        node.connect_with_audio_node(&analyser)?;
+        analyser.connect_with_audio_node(&context.destination())?;
+        JsFuture::from(element.play()?).await?;
+        audio.set_input(Connected {
+            node,
+            source: Source::File(element),
+        });
+        log::info!("audio: {}", url);
+        Ok(JsValue::UNDEFINED)
+    })
+}
+
+#[wasm_bindgen]
+pub fn audio_stop() {
+    Audio::current().stop();
+}
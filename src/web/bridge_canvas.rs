@@ -12,13 +12,17 @@ pub struct CanvasContext {
     element: HtmlCanvasElement,
     pub surface: Option<Arc<Surface<'static>>>,
 }
-use crate::console_log;
+impl CanvasContext {
+    pub fn element(&self) -> &HtmlCanvasElement {
+        &self.element
+    }
+}
 use std::sync::Mutex;
 use wgpu::{Adapter, Backends, InstanceDescriptor, RequestAdapterOptions, SurfaceTarget};
 
 #[cfg(feature = "webgpu")]
 pub async fn connect_canvas_arr(canvas_arr: Vec<web_sys::HtmlCanvasElement>) -> (Canvas, Adapter) {
-    console_log!("准备canvas_arr");
+    log::debug!("准备canvas_arr");
 
     let mut canvas_contexts = HashMap::new();
 
@@ -33,22 +37,22 @@ pub async fn connect_canvas_arr(canvas_arr: Vec<web_sys::HtmlCanvasElement>) ->
         );
     }
     let instance = wgpu::Instance::new(&InstanceDescriptor::default());
-    console_log!("创建instance成功");
+    log::debug!("创建instance成功");
     let adapter = instance
         .request_adapter(&RequestAdapterOptions::default())
         .await
         .unwrap();
-    console_log!("创建adapter成功");
+    log::debug!("创建adapter成功");
     for canvas in canvas_arr.iter() {
         let surface = unsafe { instance.create_surface(SurfaceTarget::Canvas(canvas.clone())) };
         let id = canvas.id();
         canvas_contexts.get_mut(&id).unwrap().surface = Some(Arc::new(surface.unwrap()));
-        console_log!("创建surface成功 : {}", id);
+        log::debug!("创建surface成功 : {}", id);
     }
     let canvas = Canvas {
         canvases: canvas_contexts,
     };
-    console_log!("创建总canvas成功");
+    log::debug!("创建总canvas成功");
     (canvas, adapter)
 }
 
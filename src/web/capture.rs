@@ -0,0 +1,227 @@
+/// 截图导出：JS 调用 `capture_png(canvas_id)`（或 Rust 里 `request`）登记请求，
+/// 该 canvas 下一次被画时把 swapchain 纹理复制进 buffer，map_async 读回后编码成 PNG
+use std::{cell::RefCell, collections::HashMap};
+
+use image::{codecs::png::PngEncoder, ColorType, ImageEncoder};
+use js_sys::{Promise, Uint8Array};
+use wasm_bindgen::{prelude::*, JsValue};
+
+/// 拿到 PNG 字节或错误信息
+pub type Callback = Box<dyn FnOnce(Result<Vec<u8>, String>)>;
+
+// 等了这么多帧还没被画就放弃：有的 canvas 只在启动时画一次，单画布模式下各个视图也不单独画
+const MAX_WAIT_FRAMES: u32 = 120;
+
+struct Waiter {
+    callback: Callback,
+    frames: u32, // 已经等了几帧
+}
+
+thread_local! {
+    static PENDING: RefCell<HashMap<String, Vec<Waiter>>> = RefCell::new(HashMap::new());
+}
+
+pub fn request(canvas_id: &str, callback: Callback) {
+    PENDING.with(|pending| {
+        pending
+            .borrow_mut()
+            .entry(canvas_id.to_string())
+            .or_default()
+            .push(Waiter {
+                callback,
+                frames: 0,
+            });
+    });
+}
+
+/// 取消还没被处理的请求（canvas 这一帧没画），返回是否真的有请求被取消
+pub fn cancel(canvas_id: &str, reason: &str) -> bool {
+    match take(canvas_id) {
+        Some(waiters) => {
+            reject_all(waiters, reason);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 帧循环每次 rAF 调用一次（暂停时也调），等太久的请求直接 reject
+pub fn tick() {
+    let expired = PENDING.with(|pending| {
+        let mut expired = Vec::new();
+        pending.borrow_mut().retain(|canvas_id, waiters| {
+            for waiter in waiters.iter_mut() {
+                waiter.frames += 1;
+            }
+            let (stale, fresh) = std::mem::take(waiters)
+                .into_iter()
+                .partition::<Vec<_>, _>(|waiter| waiter.frames > MAX_WAIT_FRAMES);
+            expired.extend(stale.into_iter().map(|waiter| (canvas_id.clone(), waiter)));
+            *waiters = fresh;
+            !waiters.is_empty()
+        });
+        expired
+    });
+    // 借用结束后再回调，回调里可能会再发请求
+    for (canvas_id, waiter) in expired {
+        (waiter.callback)(Err(format!(
+            "{} was not drawn within {} frames",
+            canvas_id, MAX_WAIT_FRAMES
+        )));
+    }
+}
+
+fn take(canvas_id: &str) -> Option<Vec<Callback>> {
+    PENDING
+        .with(|pending| pending.borrow_mut().remove(canvas_id))
+        .map(|waiters| waiters.into_iter().map(|waiter| waiter.callback).collect())
+}
+
+/// 返回 Promise<Uint8Array>（PNG 文件字节）。canvas 在 `MAX_WAIT_FRAMES` 帧内没被画到时 reject
+#[wasm_bindgen]
+pub fn capture_png(canvas_id: String) -> Promise {
+    Promise::new(&mut |resolve, reject| {
+        request(
+            &canvas_id,
+            Box::new(move |result| {
+                let _ = match result {
+                    Ok(png) => resolve.call1(&JsValue::NULL, &Uint8Array::from(png.as_slice())),
+                    Err(e) => reject.call1(&JsValue::NULL, &JsValue::from_str(&e)),
+                };
+            }),
+        );
+    })
+}
+
+/// 一次进行中的截图，submit 之后调用 `finish`
+pub struct Capture {
+    buffer: wgpu::Buffer,
+    size: [u32; 2],
+    padded_bytes_per_row: u32,
+    bgra: bool,
+    waiters: Vec<Callback>,
+}
+
+/// 有请求时在 encoder 里记录纹理到 buffer 的复制（纹理需要 COPY_SRC）
+pub fn copy_frame(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    canvas_id: &str,
+    texture: &wgpu::Texture,
+) -> Option<Capture> {
+    let waiters = take(canvas_id)?;
+    let format = texture.format();
+    let bgra = match format {
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        _ => {
+            reject_all(
+                waiters,
+                &format!("Unsupported capture format: {:?}", format),
+            );
+            return None;
+        }
+    };
+    let size = [texture.width(), texture.height()];
+    // bytes_per_row 必须是 256 的倍数
+    let unpadded_bytes_per_row = size[0] * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(&format!("capture {}", canvas_id)),
+        size: (padded_bytes_per_row * size[1]) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(size[1]),
+            },
+        },
+        wgpu::Extent3d {
+            width: size[0],
+            height: size[1],
+            depth_or_array_layers: 1,
+        },
+    );
+    Some(Capture {
+        buffer,
+        size,
+        padded_bytes_per_row,
+        bgra,
+        waiters,
+    })
+}
+
+impl Capture {
+    pub fn finish(self) {
+        let buffer = self.buffer.clone();
+        self.buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                if let Err(e) = result {
+                    reject_all(self.waiters, &format!("map_async failed: {}", e));
+                    return;
+                }
+                let rgba = {
+                    let mapped = buffer.slice(..).get_mapped_range();
+                    unpad(
+                        &mapped,
+                        self.size,
+                        self.padded_bytes_per_row as usize,
+                        self.bgra,
+                    )
+                };
+                buffer.unmap();
+                match encode_png(&rgba, self.size) {
+                    Ok(png) => {
+                        log::info!(
+                            "captured {}x{} ({} bytes)",
+                            self.size[0],
+                            self.size[1],
+                            png.len()
+                        );
+                        for waiter in self.waiters {
+                            waiter(Ok(png.clone()));
+                        }
+                    }
+                    Err(e) => reject_all(self.waiters, &e),
+                }
+            });
+    }
+}
+
+// 去掉每行末尾的对齐填充，BGRA 转成 RGBA
+fn unpad(mapped: &[u8], size: [u32; 2], padded_bytes_per_row: usize, bgra: bool) -> Vec<u8> {
+    let row_bytes = size[0] as usize * 4;
+    let mut rgba = Vec::with_capacity(row_bytes * size[1] as usize);
+    for row in mapped.chunks(padded_bytes_per_row).take(size[1] as usize) {
+        rgba.extend_from_slice(&row[..row_bytes]);
+    }
+    if bgra {
+        for pixel in rgba.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    rgba
+}
+
+pub fn encode_png(rgba: &[u8], size: [u32; 2]) -> Result<Vec<u8>, String> {
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png)
+        .write_image(rgba, size[0], size[1], ColorType::Rgba8)
+        .map_err(|e| e.to_string())?;
+    Ok(png)
+}
+
+fn reject_all(waiters: Vec<Callback>, message: &str) {
+    for waiter in waiters {
+        waiter(Err(message.to_string()));
+    }
+}
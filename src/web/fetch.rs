@@ -0,0 +1,24 @@
+/// 按 URL 取回整个文件的字节，模型等运行时加载的资源用
+use js_sys::{ArrayBuffer, Uint8Array};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{window, Response};
+
+pub async fn fetch_bytes(url: &str) -> Result<Vec<u8>, String> {
+    let to_string = |e: JsValue| format!("fetch {}: {:?}", url, e);
+    let window = window().ok_or("no window")?;
+    let response: Response = JsFuture::from(window.fetch_with_str(url))
+        .await
+        .map_err(to_string)?
+        .dyn_into()
+        .map_err(to_string)?;
+    if !response.ok() {
+        return Err(format!("fetch {}: HTTP {}", url, response.status()));
+    }
+    let buffer: ArrayBuffer = JsFuture::from(response.array_buffer().map_err(to_string)?)
+        .await
+        .map_err(to_string)?
+        .dyn_into()
+        .map_err(to_string)?;
+    Ok(Uint8Array::new(&buffer).to_vec())
+}
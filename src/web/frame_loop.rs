@@ -0,0 +1,165 @@
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+use super::{
+    capture,
+    record::{self, Recording},
+};
+use crate::utils::Time;
+
+type FrameFn = Rc<RefCell<dyn FnMut(f32)>>;
+type VisibleFn = Rc<dyn Fn() -> bool>;
+
+const TARGET_FPS: f32 = 60.;
+const FRAME_TIME: f32 = 1000.0 / TARGET_FPS;
+
+/// 页面共用的 rAF 循环：按 60fps 节流，每秒打印一次渲染帧率
+/// `active` 变为 false 后不再请求下一帧，循环结束；
+/// `visible` 为 false 时跳过 frame 并冻结时钟，恢复后 dt 不会把暂停的时间算进去。
+/// 有等待中的录制请求时先跑离线模式，录完再转成实时循环
+pub fn run(
+    name: &'static str,
+    active: Rc<RefCell<bool>>,
+    visible: impl Fn() -> bool + 'static,
+    frame: impl FnMut(f32) + 'static,
+) {
+    let frame: FrameFn = Rc::new(RefCell::new(frame));
+    let visible: VisibleFn = Rc::new(visible);
+    match record::take_request() {
+        Some(recording) => {
+            log::info!(
+                "{} recording {} frames of {} at dt {}ms",
+                name,
+                recording.frames,
+                recording.canvas_id,
+                recording.dt
+            );
+            offline_step(Offline {
+                name,
+                active,
+                visible,
+                frame,
+                recording,
+                pngs: Vec::new(),
+            });
+        }
+        None => realtime(name, active, visible, frame),
+    }
+}
+
+fn realtime(name: &'static str, active: Rc<RefCell<bool>>, visible: VisibleFn, frame: FrameFn) {
+    let mut time = Time::new();
+    let last_time = time.performance.now() as f32;
+    time.last_frame_time = last_time;
+    let mut accumulated_time: f32 = 0.0;
+    // FPS 计算相关变量
+    let mut render_frame_count: u32 = 0;
+    let mut fps_update_time: f32 = last_time;
+    let mut paused = false;
+
+    let f = Rc::new(RefCell::new(None::<Closure<dyn FnMut()>>));
+    let g = f.clone();
+    *g.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+        if !*active.borrow() {
+            log::debug!("{} not active", name);
+            return;
+        }
+        capture::tick();
+        let now = time.performance.now() as f32;
+        if !visible() {
+            if !paused {
+                log::debug!("{} paused (nothing visible)", name);
+                paused = true;
+            }
+            time.last_frame_time = now;
+            accumulated_time = 0.;
+            fps_update_time = now;
+            render_frame_count = 0;
+            request_animation_frame(f.borrow().as_ref().unwrap());
+            return;
+        }
+        if paused {
+            log::debug!("{} resumed", name);
+            paused = false;
+        }
+        let dt = (now - time.last_frame_time).min(100.0);
+        time.last_frame_time = now;
+        accumulated_time += dt;
+        let mut updated = false;
+        while accumulated_time >= FRAME_TIME {
+            accumulated_time -= FRAME_TIME;
+            updated = true;
+        }
+        if updated {
+            (frame.borrow_mut())(dt);
+            render_frame_count += 1;
+        }
+        if now - fps_update_time > 1000.0 {
+            let time_diff = (now - fps_update_time) / 1000.0;
+            let render_fps = render_frame_count as f32 / time_diff;
+            render_frame_count = 0;
+            fps_update_time = now;
+            log::debug!("{} 渲染FPS: {}", name, render_fps);
+        }
+
+        request_animation_frame(f.borrow().as_ref().unwrap());
+    }) as Box<dyn FnMut()>));
+
+    request_animation_frame(g.borrow().as_ref().unwrap());
+}
+
+fn request_animation_frame(f: &Closure<dyn FnMut()>) {
+    web_sys::window()
+        .unwrap()
+        .request_animation_frame(f.as_ref().unchecked_ref())
+        .expect("Failed to request animation frame");
+}
+
+struct Offline {
+    name: &'static str,
+    active: Rc<RefCell<bool>>,
+    visible: VisibleFn,
+    frame: FrameFn,
+    recording: Recording,
+    pngs: Vec<Vec<u8>>,
+}
+
+// 离线模式：固定 dt 画一帧，等这一帧的 PNG 读回后再画下一帧
+fn offline_step(mut offline: Offline) {
+    if !*offline.active.borrow() {
+        offline.recording.fail("page left while recording");
+        return;
+    }
+    if offline.pngs.len() as u32 == offline.recording.frames {
+        let Offline {
+            name,
+            active,
+            visible,
+            frame,
+            recording,
+            pngs,
+        } = offline;
+        recording.finish(pngs);
+        realtime(name, active, visible, frame);
+        return;
+    }
+    // 录制期间实时循环不跑，别的截图请求在这里计时
+    capture::tick();
+    let canvas_id = offline.recording.canvas_id.clone();
+    let frame = offline.frame.clone();
+    let dt = offline.recording.dt;
+    capture::request(
+        &canvas_id,
+        Box::new(move |result| match result {
+            Ok(png) => {
+                offline.pngs.push(png);
+                offline_step(offline);
+            }
+            Err(e) => offline.recording.fail(&e),
+        }),
+    );
+    (frame.borrow_mut())(dt);
+    // 这一帧没画到目标 canvas，说明它不属于当前页面
+    capture::cancel(&canvas_id, "canvas is not drawn by the current page");
+}
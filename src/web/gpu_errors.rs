@@ -0,0 +1,251 @@
+/// wgpu 错误上报：每个系统的 ready / paint 包在 error scope 里，出错时带上 scene 名和系统类型名，
+/// scope 外漏掉的走 `on_uncaptured_error`。错误打到控制台、转给 JS 回调（`on_gpu_error`），
+/// 并显示在页面左下角的浮层里；同一个错误每帧重复只累加次数
+use std::cell::RefCell;
+
+use js_sys::{Function, Object, Reflect};
+use wasm_bindgen::{prelude::*, JsCast, JsValue};
+use web_sys::{window, HtmlElement};
+
+const OVERLAY_ID: &str = "gpu-error-overlay";
+const OVERLAY_STYLE: &str = "position:fixed;left:8px;bottom:8px;max-width:60vw;max-height:40vh;\
+overflow:auto;z-index:9999;padding:8px 28px 8px 8px;background:rgba(110,0,0,0.92);color:#fff;\
+font:12px/1.4 monospace;white-space:pre-wrap;border-radius:4px;";
+const CLOSE_STYLE: &str =
+    "position:absolute;top:2px;right:6px;cursor:pointer;background:none;border:none;color:#fff;";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Ready,
+    Paint,
+    Uncaptured,
+}
+
+impl Phase {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Ready => "ready",
+            Phase::Paint => "paint",
+            Phase::Uncaptured => "uncaptured",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct GpuErrorReport {
+    scene: String,
+    system: String,
+    phase: Phase,
+    kind: &'static str,
+    message: String,
+    count: u32,
+}
+
+impl GpuErrorReport {
+    fn same_as(&self, other: &Self) -> bool {
+        self.scene == other.scene
+            && self.system == other.system
+            && self.phase == other.phase
+            && self.message == other.message
+    }
+
+    fn to_js(&self) -> JsValue {
+        let object = Object::new();
+        let set = |key: &str, value: JsValue| {
+            let _ = Reflect::set(&object, &JsValue::from_str(key), &value);
+        };
+        set("scene", self.scene.as_str().into());
+        set("system", self.system.as_str().into());
+        set("phase", self.phase.as_str().into());
+        set("kind", self.kind.into());
+        set("message", self.message.as_str().into());
+        set("count", self.count.into());
+        object.into()
+    }
+}
+
+thread_local! {
+    static REPORTS: RefCell<Vec<GpuErrorReport>> = const { RefCell::new(Vec::new()) };
+    static LISTENERS: RefCell<Vec<Function>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 设备创建后立刻调用，兜住没被 scope 捕获的错误
+pub fn install(device: &wgpu::Device) {
+    device.on_uncaptured_error(Box::new(|error| {
+        report("-", "-", Phase::Uncaptured, error);
+    }));
+}
+
+/// 在 validation / out-of-memory / internal 三个 scope 里执行 `f`，pop 是异步的，结果稍后上报
+pub fn scoped<R>(
+    device: &wgpu::Device,
+    scene: &str,
+    system: &str,
+    phase: Phase,
+    f: impl FnOnce() -> R,
+) -> R {
+    const FILTERS: [wgpu::ErrorFilter; 3] = [
+        wgpu::ErrorFilter::Validation,
+        wgpu::ErrorFilter::OutOfMemory,
+        wgpu::ErrorFilter::Internal,
+    ];
+    for filter in FILTERS {
+        device.push_error_scope(filter);
+    }
+    let result = f();
+    // 后进先出，按相反顺序 pop
+    let pops: Vec<_> = FILTERS.iter().map(|_| device.pop_error_scope()).collect();
+    let scene = scene.to_string();
+    let system = short_type_name(system).to_string();
+    wasm_bindgen_futures::spawn_local(async move {
+        for pop in pops {
+            if let Some(error) = pop.await {
+                report(&scene, &system, phase, error);
+            }
+        }
+    });
+    result
+}
+
+// 去掉外层类型的模块路径，例如 page_shader_tech::demos::level2::union_paint::PaintLevel2 -> PaintLevel2
+fn short_type_name(name: &str) -> &str {
+    let base = name.split('<').next().unwrap_or(name);
+    let start = base.rfind("::").map_or(0, |i| i + 2);
+    &name[start..]
+}
+
+fn report(scene: &str, system: &str, phase: Phase, error: wgpu::Error) {
+    let (kind, message) = match &error {
+        wgpu::Error::Validation { description, .. } => ("validation", description.clone()),
+        wgpu::Error::Internal { description, .. } => ("internal", description.clone()),
+        wgpu::Error::OutOfMemory { .. } => ("out-of-memory", error.to_string()),
+    };
+    let new = GpuErrorReport {
+        scene: scene.to_string(),
+        system: system.to_string(),
+        phase,
+        kind,
+        message,
+        count: 1,
+    };
+    let (entry, is_new) = REPORTS.with(|reports| {
+        let mut reports = reports.borrow_mut();
+        match reports.iter_mut().find(|report| report.same_as(&new)) {
+            Some(existing) => {
+                existing.count += 1;
+                (existing.clone(), false)
+            }
+            None => {
+                reports.push(new.clone());
+                (new, true)
+            }
+        }
+    });
+    // 重复的错误不再刷屏，只更新浮层上的次数
+    if is_new {
+        log::error!(
+            "[{}] {} ({}, {}): {}",
+            entry.scene,
+            entry.system,
+            entry.phase.as_str(),
+            entry.kind,
+            entry.message
+        );
+        let payload = entry.to_js();
+        let listeners = LISTENERS.with(|listeners| listeners.borrow().clone());
+        for listener in listeners {
+            let _ = listener.call1(&JsValue::NULL, &payload);
+        }
+    }
+    render_overlay();
+}
+
+fn render_overlay() {
+    let Some(document) = window().and_then(|w| w.document()) else {
+        return;
+    };
+    let text_id = format!("{}-text", OVERLAY_ID);
+    let text_node = match document.get_element_by_id(&text_id) {
+        Some(text_node) => text_node,
+        None => match create_overlay(&document, &text_id) {
+            Ok(text_node) => text_node,
+            Err(_) => return,
+        },
+    };
+    let text = REPORTS.with(|reports| {
+        reports
+            .borrow()
+            .iter()
+            .map(|report| {
+                let count = if report.count > 1 {
+                    format!(" ×{}", report.count)
+                } else {
+                    String::new()
+                };
+                format!(
+                    "[{}] {} · {} · {}{}\n{}",
+                    report.scene,
+                    report.system,
+                    report.phase.as_str(),
+                    report.kind,
+                    count,
+                    report.message
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    });
+    text_node.set_text_content(Some(&text));
+}
+
+// 浮层只建一次：文字节点每次刷新，关闭按钮的回调常驻
+fn create_overlay(
+    document: &web_sys::Document,
+    text_id: &str,
+) -> Result<web_sys::Element, JsValue> {
+    let overlay = document.create_element("div")?;
+    overlay.set_id(OVERLAY_ID);
+    overlay.set_attribute("style", OVERLAY_STYLE)?;
+    let text_node = document.create_element("div")?;
+    text_node.set_id(text_id);
+    overlay.append_child(&text_node)?;
+
+    let close = document.create_element("button")?;
+    close.set_text_content(Some("×"));
+    close.set_attribute("style", CLOSE_STYLE)?;
+    let on_click = Closure::wrap(Box::new(clear_gpu_errors) as Box<dyn FnMut()>);
+    close
+        .unchecked_ref::<HtmlElement>()
+        .set_onclick(Some(on_click.as_ref().unchecked_ref()));
+    on_click.forget();
+    overlay.append_child(&close)?;
+
+    if let Some(body) = document.body() {
+        body.append_child(&overlay)?;
+    }
+    Ok(text_node)
+}
+
+/// 注册错误回调，参数是 `{ scene, system, phase, kind, message, count }`
+#[wasm_bindgen]
+pub fn on_gpu_error(callback: Function) {
+    LISTENERS.with(|listeners| listeners.borrow_mut().push(callback));
+}
+
+/// 清空已记录的错误并移除浮层
+#[wasm_bindgen]
+pub fn clear_gpu_errors() {
+    REPORTS.with(|reports| reports.borrow_mut().clear());
+    if let Some(overlay) = window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(OVERLAY_ID))
+    {
+        overlay.remove();
+    }
+}
+
+/// JS 侧也可以查询当前记录的错误
+#[wasm_bindgen]
+pub fn gpu_errors() -> js_sys::Array {
+    REPORTS.with(|reports| reports.borrow().iter().map(GpuErrorReport::to_js).collect())
+}
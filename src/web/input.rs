@@ -0,0 +1,341 @@
+/// 每个 canvas 的指针 / 触摸输入，加上全局键盘状态
+/// 事件回调只写入共享状态，system 在 update 里通过 `Input` 资源读取，帧末 `end_frame` 清掉单帧事件
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+// 本地测试只用到状态部分，挂监听的部分只在 wasm 下编译
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+#[cfg(target_arch = "wasm32")]
+use web_sys::{
+    window, AddEventListenerOptions, EventTarget, HtmlCanvasElement, KeyboardEvent, MouseEvent,
+    TouchEvent, WheelEvent,
+};
+
+#[cfg(target_arch = "wasm32")]
+use super::bridge_canvas::Canvas;
+
+pub const BUTTON_LEFT: usize = 0;
+pub const BUTTON_MIDDLE: usize = 1;
+pub const BUTTON_RIGHT: usize = 2;
+
+#[derive(Debug, Clone, Default)]
+pub struct PointerState {
+    pub position: [f32; 2], // canvas 像素坐标，左上角为原点
+    pub ndc: [f32; 2],      // [-1, 1]，y 向上
+    pub delta: [f32; 2],    // 本帧累计移动（像素）
+    pub held: [bool; 3],
+    pub pressed: [bool; 3],
+    pub released: [bool; 3],
+    pub wheel: f32, // 本帧滚轮，单位约为像素，向下为正
+    pub hovered: bool,
+}
+
+impl PointerState {
+    /// [0, 1] 的 uv 坐标，和 shader 里 frag_coord / resolution 一致
+    pub fn uv(&self) -> [f32; 2] {
+        [self.ndc[0] * 0.5 + 0.5, 0.5 - self.ndc[1] * 0.5]
+    }
+    pub fn any_held(&self) -> bool {
+        self.held.iter().any(|b| *b)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TouchPoint {
+    pub id: i32,
+    pub position: [f32; 2],
+    pub ndc: [f32; 2],
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CanvasInput {
+    pub size: [f32; 2],
+    pub pointer: PointerState,
+    pub touches: Vec<TouchPoint>,
+    pub touch_started: bool,
+    pub touch_ended: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct KeyboardState {
+    pub held: HashSet<String>,
+    pub pressed: HashSet<String>,
+    pub released: HashSet<String>,
+}
+
+/// 作为 scene 资源放进 `scene.res`，clone 只是多一个引用
+#[derive(Clone, Default)]
+pub struct Input {
+    canvases: Rc<RefCell<HashMap<String, CanvasInput>>>,
+    keyboard: Rc<RefCell<KeyboardState>>,
+}
+
+impl Input {
+    /// 某个 canvas 本帧的输入快照
+    pub fn canvas(&self, id: &str) -> CanvasInput {
+        self.canvases.borrow().get(id).cloned().unwrap_or_default()
+    }
+
+    pub fn key_held(&self, code: &str) -> bool {
+        self.keyboard.borrow().held.contains(code)
+    }
+    pub fn key_pressed(&self, code: &str) -> bool {
+        self.keyboard.borrow().pressed.contains(code)
+    }
+    pub fn key_released(&self, code: &str) -> bool {
+        self.keyboard.borrow().released.contains(code)
+    }
+
+    /// 每次 paint 之后调用，清掉单帧的 pressed / released / delta / wheel
+    pub fn end_frame(&self) {
+        for state in self.canvases.borrow_mut().values_mut() {
+            let pointer = &mut state.pointer;
+            pointer.pressed = [false; 3];
+            pointer.released = [false; 3];
+            pointer.delta = [0., 0.];
+            pointer.wheel = 0.;
+            state.touch_started = false;
+            state.touch_ended = false;
+        }
+        let mut keyboard = self.keyboard.borrow_mut();
+        keyboard.pressed.clear();
+        keyboard.released.clear();
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Input {
+    /// 给所有 canvas 挂上监听，键盘监听挂在 window 上
+    pub fn attach(canvas: &Canvas) -> Result<Self, JsValue> {
+        let input = Input::default();
+        for (id, context) in canvas.canvases.iter() {
+            input.attach_canvas(id, context.element())?;
+        }
+        input.attach_keyboard()?;
+        Ok(input)
+    }
+
+    fn attach_canvas(&self, id: &str, element: &HtmlCanvasElement) -> Result<(), JsValue> {
+        self.canvases
+            .borrow_mut()
+            .insert(id.to_string(), CanvasInput::default());
+        let target: &EventTarget = element.as_ref();
+
+        let (canvases, id_, el) = (self.canvases.clone(), id.to_string(), element.clone());
+        listen(target, "mousemove", true, move |e: MouseEvent| {
+            let (position, ndc, size) = to_canvas_space(&el, e.client_x(), e.client_y());
+            with_canvas(&canvases, &id_, |state| {
+                let pointer = &mut state.pointer;
+                if pointer.hovered {
+                    pointer.delta[0] += position[0] - pointer.position[0];
+                    pointer.delta[1] += position[1] - pointer.position[1];
+                }
+                pointer.position = position;
+                pointer.ndc = ndc;
+                pointer.hovered = true;
+                state.size = size;
+            });
+        })?;
+
+        let (canvases, id_, el) = (self.canvases.clone(), id.to_string(), element.clone());
+        listen(target, "mousedown", true, move |e: MouseEvent| {
+            let (position, ndc, size) = to_canvas_space(&el, e.client_x(), e.client_y());
+            let button = e.button() as usize;
+            with_canvas(&canvases, &id_, |state| {
+                let pointer = &mut state.pointer;
+                pointer.position = position;
+                pointer.ndc = ndc;
+                pointer.hovered = true;
+                state.size = size;
+                if button < 3 {
+                    pointer.held[button] = true;
+                    pointer.pressed[button] = true;
+                }
+            });
+        })?;
+
+        // mouseup 挂在 window 上，拖出 canvas 后松开也能收到
+        let (canvases, id_) = (self.canvases.clone(), id.to_string());
+        let window_target: EventTarget = window().unwrap().into();
+        listen(&window_target, "mouseup", true, move |e: MouseEvent| {
+            let button = e.button() as usize;
+            with_canvas(&canvases, &id_, |state| {
+                let pointer = &mut state.pointer;
+                if button < 3 && pointer.held[button] {
+                    pointer.held[button] = false;
+                    pointer.released[button] = true;
+                }
+            });
+        })?;
+
+        let (canvases, id_) = (self.canvases.clone(), id.to_string());
+        listen(target, "mouseleave", true, move |_: MouseEvent| {
+            with_canvas(&canvases, &id_, |state| state.pointer.hovered = false);
+        })?;
+
+        let (canvases, id_) = (self.canvases.clone(), id.to_string());
+        listen(target, "wheel", false, move |e: WheelEvent| {
+            // 不让页面跟着滚动
+            e.prevent_default();
+            // delta_mode: 0 像素, 1 行, 2 页
+            let scale = match e.delta_mode() {
+                1 => 16.,
+                2 => 400.,
+                _ => 1.,
+            };
+            with_canvas(&canvases, &id_, |state| {
+                state.pointer.wheel += (e.delta_y() * scale) as f32
+            });
+        })?;
+
+        // 右键拖拽留给相机平移
+        listen(target, "contextmenu", false, |e: MouseEvent| {
+            e.prevent_default()
+        })?;
+
+        for event in ["touchstart", "touchmove", "touchend", "touchcancel"] {
+            let (canvases, id_, el) = (self.canvases.clone(), id.to_string(), element.clone());
+            listen(target, event, false, move |e: TouchEvent| {
+                e.prevent_default();
+                let list = e.touches();
+                let touches = (0..list.length())
+                    .filter_map(|i| list.get(i))
+                    .map(|touch| {
+                        let (position, ndc, _) =
+                            to_canvas_space(&el, touch.client_x(), touch.client_y());
+                        TouchPoint {
+                            id: touch.identifier(),
+                            position,
+                            ndc,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let size = canvas_size(&el);
+                with_canvas(&canvases, &id_, |state| {
+                    update_touches(state, touches, event, size)
+                });
+            })?;
+        }
+        Ok(())
+    }
+
+    fn attach_keyboard(&self) -> Result<(), JsValue> {
+        let window_target: EventTarget = window().unwrap().into();
+
+        let keyboard = self.keyboard.clone();
+        listen(&window_target, "keydown", true, move |e: KeyboardEvent| {
+            if e.repeat() {
+                return;
+            }
+            let mut keyboard = keyboard.borrow_mut();
+            keyboard.held.insert(e.code());
+            keyboard.pressed.insert(e.code());
+        })?;
+
+        let keyboard = self.keyboard.clone();
+        listen(&window_target, "keyup", true, move |e: KeyboardEvent| {
+            let mut keyboard = keyboard.borrow_mut();
+            keyboard.held.remove(&e.code());
+            keyboard.released.insert(e.code());
+        })?;
+
+        // 切走窗口时 keyup 收不到，直接清空
+        let keyboard = self.keyboard.clone();
+        listen(&window_target, "blur", true, move |_: web_sys::Event| {
+            let mut keyboard = keyboard.borrow_mut();
+            let held = std::mem::take(&mut keyboard.held);
+            keyboard.released.extend(held);
+        })?;
+        Ok(())
+    }
+}
+
+// 单指触摸同时驱动 pointer 左键，多指只记录在 touches 里
+fn update_touches(state: &mut CanvasInput, touches: Vec<TouchPoint>, event: &str, size: [f32; 2]) {
+    let pointer = &mut state.pointer;
+    state.size = size;
+    if let Some(first) = touches.first() {
+        if pointer.held[BUTTON_LEFT] && event == "touchmove" {
+            pointer.delta[0] += first.position[0] - pointer.position[0];
+            pointer.delta[1] += first.position[1] - pointer.position[1];
+        }
+        pointer.position = first.position;
+        pointer.ndc = first.ndc;
+    }
+    match event {
+        "touchstart" => {
+            state.touch_started = true;
+            if !pointer.held[BUTTON_LEFT] {
+                pointer.held[BUTTON_LEFT] = true;
+                pointer.pressed[BUTTON_LEFT] = true;
+            }
+        }
+        "touchend" | "touchcancel" => {
+            state.touch_ended = true;
+            if touches.is_empty() && pointer.held[BUTTON_LEFT] {
+                pointer.held[BUTTON_LEFT] = false;
+                pointer.released[BUTTON_LEFT] = true;
+            }
+        }
+        _ => {}
+    }
+    state.touches = touches;
+}
+
+fn with_canvas(
+    canvases: &Rc<RefCell<HashMap<String, CanvasInput>>>,
+    id: &str,
+    f: impl FnOnce(&mut CanvasInput),
+) {
+    if let Some(state) = canvases.borrow_mut().get_mut(id) {
+        f(state);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn canvas_size(canvas: &HtmlCanvasElement) -> [f32; 2] {
+    [canvas.width() as f32, canvas.height() as f32]
+}
+
+// client 坐标 -> canvas 像素坐标和 NDC（css 尺寸和 canvas 分辨率可能不同）
+#[cfg(target_arch = "wasm32")]
+fn to_canvas_space(
+    canvas: &HtmlCanvasElement,
+    client_x: i32,
+    client_y: i32,
+) -> ([f32; 2], [f32; 2], [f32; 2]) {
+    let rect = canvas.get_bounding_client_rect();
+    let size = canvas_size(canvas);
+    let css_w = rect.width().max(1.) as f32;
+    let css_h = rect.height().max(1.) as f32;
+    let x = (client_x as f32 - rect.left() as f32) * size[0] / css_w;
+    let y = (client_y as f32 - rect.top() as f32) * size[1] / css_h;
+    let ndc = [x / size[0].max(1.) * 2. - 1., 1. - y / size[1].max(1.) * 2.];
+    ([x, y], ndc, size)
+}
+
+// passive 为 false 时才能 prevent_default
+#[cfg(target_arch = "wasm32")]
+fn listen<E: JsCast + 'static>(
+    target: &EventTarget,
+    event: &str,
+    passive: bool,
+    mut f: impl FnMut(E) + 'static,
+) -> Result<(), JsValue> {
+    let closure = Closure::wrap(Box::new(move |e: web_sys::Event| {
+        f(e.unchecked_into::<E>());
+    }) as Box<dyn FnMut(_)>);
+    let options = AddEventListenerOptions::new();
+    options.set_passive(passive);
+    target.add_event_listener_with_callback_and_add_event_listener_options(
+        event,
+        closure.as_ref().unchecked_ref(),
+        &options,
+    )?;
+    closure.forget();
+    Ok(())
+}
@@ -1,2 +1,13 @@
+pub(crate) mod audio;
 pub(crate) mod bridge_canvas;
+pub(crate) mod capture;
+pub(crate) mod fetch;
+pub(crate) mod frame_loop;
+pub(crate) mod gpu_errors;
+pub(crate) mod input;
 pub(crate) mod interactions;
+pub(crate) mod params;
+pub(crate) mod record;
+pub(crate) mod router;
+pub(crate) mod shaders;
+pub(crate) mod visibility;
@@ -0,0 +1,54 @@
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::shared::params;
+
+// key 形如 "boids.rule1_distance"
+fn split_key(key: &str) -> Result<(&str, &str), JsValue> {
+    key.split_once('.')
+        .ok_or_else(|| JsValue::from_str(&format!("Param key should be `system.name`: {}", key)))
+}
+
+/// 返回所有已声明参数：[{ key, system, name, kind, min, max, default, value }]
+#[wasm_bindgen]
+pub fn list_params() -> Array {
+    let list = Array::new();
+    for param in params::list() {
+        let obj = Object::new();
+        let fields: [(&str, JsValue); 8] = [
+            ("key", param.key().into()),
+            ("system", param.system.into()),
+            ("name", param.desc.name.into()),
+            ("kind", param.desc.kind.as_str().into()),
+            ("min", param.desc.min.into()),
+            ("max", param.desc.max.into()),
+            ("default", param.desc.default.into()),
+            ("value", param.value.into()),
+        ];
+        for (field, value) in fields.iter() {
+            Reflect::set(&obj, &JsValue::from_str(field), value).unwrap();
+        }
+        list.push(&obj);
+    }
+    list
+}
+
+/// 设置参数，返回按范围和类型夹取后的值
+#[wasm_bindgen]
+pub fn set_param(key: &str, value: f32) -> Result<f32, JsValue> {
+    let (system, name) = split_key(key)?;
+    params::set(system, name, value).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn get_param(key: &str) -> Result<f32, JsValue> {
+    let (system, name) = split_key(key)?;
+    params::try_get(system, name)
+        .ok_or_else(|| JsValue::from_str(&format!("Unknown param: {}", key)))
+}
+
+/// 把某个 system 的参数恢复默认值
+#[wasm_bindgen]
+pub fn reset_params(system: &str) {
+    params::reset(system);
+}
@@ -0,0 +1,90 @@
+/// 确定性录制：JS 调用 `record_frames(canvas_id, frames, dt_ms, seed)`，
+/// 当前页面用固定种子重新进入，帧循环切到离线模式按固定 dt 逐帧推进，
+/// 每帧等 canvas 的 PNG 读回后才画下一帧，和 requestAnimationFrame 无关。
+/// Promise 解析为 `Uint8Array[]`（PNG 序列），录完回到实时循环
+use std::cell::{Cell, RefCell};
+
+use js_sys::{Array, Function, Promise, Uint8Array};
+use wasm_bindgen::{prelude::*, JsValue};
+
+use super::{router, visibility};
+
+pub struct Recording {
+    pub canvas_id: String,
+    pub frames: u32,
+    pub dt: f32,
+    resolve: Function,
+    reject: Function,
+}
+
+thread_local! {
+    static PENDING: RefCell<Option<Recording>> = const { RefCell::new(None) };
+    static SEED: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// 录制期间的随机种子，场景 ready 时用它代替系统随机数
+pub fn seed() -> Option<u64> {
+    SEED.with(Cell::get)
+}
+
+/// 帧循环启动时取走等待中的录制请求
+pub fn take_request() -> Option<Recording> {
+    PENDING.with(|pending| pending.borrow_mut().take())
+}
+
+impl Recording {
+    pub fn finish(self, pngs: Vec<Vec<u8>>) {
+        log::info!("recorded {} frames of {}", pngs.len(), self.canvas_id);
+        let frames: Array = pngs
+            .iter()
+            .map(|png| JsValue::from(Uint8Array::from(png.as_slice())))
+            .collect();
+        end();
+        let _ = self.resolve.call1(&JsValue::NULL, &frames);
+    }
+
+    pub fn fail(self, message: &str) {
+        log::warn!("recording {} failed: {}", self.canvas_id, message);
+        end();
+        let _ = self
+            .reject
+            .call1(&JsValue::NULL, &JsValue::from_str(message));
+    }
+}
+
+fn end() {
+    SEED.with(|s| s.set(None));
+    visibility::force_visible(false);
+}
+
+/// dt 单位毫秒，和帧循环传给 paint 的一致
+#[wasm_bindgen]
+pub fn record_frames(canvas_id: String, frames: u32, dt_ms: f32, seed: u32) -> Promise {
+    // 种子在录制开始时设置、结束时清掉，同时当作“正在录制”的标记
+    if SEED.with(Cell::get).is_some() {
+        return Promise::reject(&JsValue::from_str("Already recording"));
+    }
+    if frames == 0 || dt_ms <= 0. {
+        return Promise::reject(&JsValue::from_str("frames and dt_ms must be positive"));
+    }
+    let promise = Promise::new(&mut |resolve, reject| {
+        PENDING.with(|pending| {
+            *pending.borrow_mut() = Some(Recording {
+                canvas_id: canvas_id.clone(),
+                frames,
+                dt: dt_ms,
+                resolve,
+                reject,
+            })
+        });
+    });
+    SEED.with(|s| s.set(Some(seed as u64)));
+    visibility::force_visible(true);
+    // 重新进入页面：scene 按种子重建，帧循环取走请求后进入离线模式
+    if let Err(e) = router::reload() {
+        if let Some(recording) = take_request() {
+            recording.fail(&e);
+        }
+    }
+    promise
+}
@@ -0,0 +1,188 @@
+/// 页面路由：按名字注册页面，和 `location.hash` 双向同步（浏览器前进 / 后退即切页）
+/// 切页时先把旧页面的 active 置 false 并调用 exit，再给新页面一个新的 active 标记调用 enter
+/// 每次切页后在 window 上派发 `pagechange` 事件（detail 为页面名），JS 据此切换布局
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use js_sys::Array;
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{window, CustomEvent, CustomEventInit};
+
+use crate::shared::Shared;
+
+pub const PAGE_CHANGE_EVENT: &str = "pagechange";
+
+pub trait Page {
+    /// 进入页面：准备 scene 并启动自己的帧循环，循环在 `active` 为 false 时停止
+    fn enter(&mut self, shared: Arc<Shared>, active: Rc<RefCell<bool>>);
+    /// 离开页面，active 已经置为 false
+    fn exit(&mut self, _shared: &Arc<Shared>) {}
+}
+
+struct Route {
+    name: &'static str,
+    page: Box<dyn Page>,
+}
+
+pub struct Router {
+    shared: Arc<Shared>,
+    routes: Vec<Route>,
+    current: Option<(usize, Rc<RefCell<bool>>)>,
+}
+
+impl Router {
+    pub fn new(shared: Arc<Shared>) -> Self {
+        Self {
+            shared,
+            routes: Vec::new(),
+            current: None,
+        }
+    }
+
+    /// 第一个注册的页面是默认页
+    pub fn add_page(mut self, name: &'static str, page: impl Page + 'static) -> Self {
+        self.routes.push(Route {
+            name,
+            page: Box::new(page),
+        });
+        self
+    }
+
+    pub fn current(&self) -> Option<&'static str> {
+        self.current
+            .as_ref()
+            .map(|(index, _)| self.routes[*index].name)
+    }
+
+    /// 返回是否真的切换了页面
+    pub fn navigate(&mut self, name: &str) -> Result<bool, String> {
+        let index = self
+            .routes
+            .iter()
+            .position(|route| route.name == name)
+            .ok_or_else(|| format!("Unknown page: {}", name))?;
+        if matches!(self.current, Some((current, _)) if current == index) {
+            return Ok(false);
+        }
+        if let Some((current, active)) = self.current.take() {
+            *active.borrow_mut() = false;
+            log::info!("exit page: {}", self.routes[current].name);
+            self.routes[current].page.exit(&self.shared);
+        }
+        let active = Rc::new(RefCell::new(true));
+        log::info!("enter page: {}", name);
+        self.routes[index]
+            .page
+            .enter(self.shared.clone(), active.clone());
+        self.current = Some((index, active));
+        Ok(true)
+    }
+
+    /// 退出并重新进入当前页面，scene 从头 ready
+    pub fn reload(&mut self) -> Result<(), String> {
+        let (index, active) = self.current.take().ok_or("No current page")?;
+        *active.borrow_mut() = false;
+        let name = self.routes[index].name;
+        log::info!("reload page: {}", name);
+        self.routes[index].page.exit(&self.shared);
+        let active = Rc::new(RefCell::new(true));
+        self.routes[index]
+            .page
+            .enter(self.shared.clone(), active.clone());
+        self.current = Some((index, active));
+        Ok(())
+    }
+}
+
+thread_local! {
+    static ROUTER: RefCell<Option<Router>> = const { RefCell::new(None) };
+}
+
+/// 安装路由并进入 hash 指定的页面（没有或不认识就进默认页）
+pub fn install(router: Router) -> Result<(), JsValue> {
+    let initial = hash_page()
+        .filter(|name| router.routes.iter().any(|route| route.name == name))
+        .or_else(|| router.routes.first().map(|route| route.name.to_string()))
+        .ok_or_else(|| JsValue::from_str("Router has no pages"))?;
+    ROUTER.with(|r| *r.borrow_mut() = Some(router));
+
+    // 前进 / 后退 / 手动改 hash
+    let closure = Closure::wrap(Box::new(move |_: web_sys::Event| {
+        if let Some(name) = hash_page() {
+            if let Err(e) = go(&name) {
+                log::warn!("hashchange: {}", e);
+            }
+        }
+    }) as Box<dyn FnMut(_)>);
+    window()
+        .unwrap()
+        .add_event_listener_with_callback("hashchange", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+
+    go(&initial).map_err(|e| JsValue::from_str(&e))
+}
+
+// pagechange 在释放 ROUTER 之后派发，JS 回调里可以再调 current_page / navigate
+fn go(name: &str) -> Result<(), String> {
+    if with_router(|router| router.navigate(name))? {
+        set_hash(name);
+        notify(name);
+    }
+    Ok(())
+}
+
+pub fn reload() -> Result<(), String> {
+    with_router(Router::reload)
+}
+
+fn with_router<R>(f: impl FnOnce(&mut Router) -> Result<R, String>) -> Result<R, String> {
+    ROUTER.with(|r| {
+        let mut r = r.borrow_mut();
+        let router = r.as_mut().ok_or("Router not installed")?;
+        f(router)
+    })
+}
+
+fn hash_page() -> Option<String> {
+    let hash = window()?.location().hash().ok()?;
+    let name = hash.trim_start_matches('#');
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+// 只在不同的时候写，避免多一条历史记录
+fn set_hash(name: &str) {
+    if hash_page().as_deref() != Some(name) {
+        let _ = window().unwrap().location().set_hash(name);
+    }
+}
+
+fn notify(name: &str) {
+    let init = CustomEventInit::new();
+    init.set_detail(&JsValue::from_str(name));
+    if let Ok(event) = CustomEvent::new_with_event_init_dict(PAGE_CHANGE_EVENT, &init) {
+        let _ = window().unwrap().dispatch_event(&event);
+    }
+}
+
+#[wasm_bindgen]
+pub fn navigate(name: &str) -> Result<(), JsValue> {
+    go(name).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn current_page() -> Option<String> {
+    with_router(|router| Ok(router.current().map(str::to_string)))
+        .ok()
+        .flatten()
+}
+
+#[wasm_bindgen]
+pub fn list_pages() -> Array {
+    with_router(|router| {
+        Ok(router
+            .routes
+            .iter()
+            .map(|route| JsValue::from_str(route.name))
+            .collect::<Array>())
+    })
+    .unwrap_or_default()
+}
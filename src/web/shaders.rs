@@ -0,0 +1,69 @@
+use js_sys::{Array, Object, Promise, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::{shared::hot_reload, utils::shader_debug::Diagnostic};
+
+// { kind, message, line, column, offset, length }
+fn diagnostics_to_js(diagnostics: &[Diagnostic]) -> Array {
+    diagnostics
+        .iter()
+        .map(|d| {
+            let obj = Object::new();
+            let fields: [(&str, JsValue); 6] = [
+                ("kind", d.kind.into()),
+                ("message", d.message.as_str().into()),
+                ("line", d.line.into()),
+                ("column", d.column.into()),
+                ("offset", d.offset.into()),
+                ("length", d.length.into()),
+            ];
+            for (field, value) in fields.iter() {
+                Reflect::set(&obj, &JsValue::from_str(field), value).unwrap();
+            }
+            JsValue::from(obj)
+        })
+        .collect()
+}
+
+/// 可以热替换的 shader 名字，例如 "boids.draw"
+#[wasm_bindgen]
+pub fn list_shaders() -> Array {
+    hot_reload::names().into_iter().map(JsValue::from).collect()
+}
+
+/// 当前生效的源码，`default_source` 为 true 时返回内置的原始源码
+#[wasm_bindgen]
+pub fn get_shader_source(name: &str, default_source: bool) -> Option<String> {
+    if default_source {
+        hot_reload::default_source(name).map(str::to_string)
+    } else {
+        hot_reload::active_source(name)
+    }
+}
+
+/// 提交新源码，对应 system 下一帧重建管线
+/// 成功时 resolve 为诊断数组（警告等），失败时 reject 为诊断数组，旧管线继续使用
+#[wasm_bindgen]
+pub fn set_shader_source(name: &str, source: String) -> Promise {
+    let mut result = Ok(());
+    let promise = Promise::new(&mut |resolve, reject| {
+        result = hot_reload::request(
+            name,
+            source.clone(),
+            Box::new(move |result| {
+                let _ = match result {
+                    Ok(diagnostics) => {
+                        resolve.call1(&JsValue::NULL, &diagnostics_to_js(&diagnostics))
+                    }
+                    Err(diagnostics) => {
+                        reject.call1(&JsValue::NULL, &diagnostics_to_js(&diagnostics))
+                    }
+                };
+            }),
+        );
+    });
+    match result {
+        Ok(()) => promise,
+        Err(e) => Promise::reject(&JsValue::from_str(&e)),
+    }
+}
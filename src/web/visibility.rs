@@ -0,0 +1,90 @@
+/// 画布可见性：IntersectionObserver 跟踪每个 canvas 是否在视口内，
+/// `document.visibilityState` 跟踪标签页是否在前台。两者都满足才算可见
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use js_sys::Array;
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+use web_sys::{window, IntersectionObserver, IntersectionObserverEntry, VisibilityState};
+
+use super::bridge_canvas::Canvas;
+
+struct State {
+    canvases: HashMap<String, bool>,
+    document_visible: bool,
+}
+
+thread_local! {
+    static FORCE_VISIBLE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 离线录制时画布不在视口内也要画
+pub fn force_visible(force: bool) {
+    FORCE_VISIBLE.with(|f| f.set(force));
+}
+
+/// 作为 scene 资源放进 `scene.res`，paint 里按 canvas 决定画不画
+#[derive(Clone)]
+pub struct Visibility {
+    state: Rc<RefCell<State>>,
+}
+
+impl Visibility {
+    pub fn observe(canvas: &Canvas) -> Result<Self, JsValue> {
+        let document = window().unwrap().document().unwrap();
+        // observer 第一次回调之前先当作可见
+        let state = Rc::new(RefCell::new(State {
+            canvases: canvas
+                .canvases
+                .keys()
+                .map(|id| (id.clone(), true))
+                .collect(),
+            document_visible: document.visibility_state() == VisibilityState::Visible,
+        }));
+
+        let state_ = state.clone();
+        let on_intersect = Closure::wrap(Box::new(move |entries: Array| {
+            let mut state = state_.borrow_mut();
+            for entry in entries.iter() {
+                let entry: IntersectionObserverEntry = entry.unchecked_into();
+                let id = entry.target().id();
+                state.canvases.insert(id, entry.is_intersecting());
+            }
+        }) as Box<dyn FnMut(Array)>);
+        let observer = IntersectionObserver::new(on_intersect.as_ref().unchecked_ref())?;
+        on_intersect.forget();
+        for context in canvas.canvases.values() {
+            observer.observe(context.element());
+        }
+
+        let state_ = state.clone();
+        let document_ = document.clone();
+        let on_visibility_change = Closure::wrap(Box::new(move |_: web_sys::Event| {
+            let visible = document_.visibility_state() == VisibilityState::Visible;
+            log::debug!("document visible: {}", visible);
+            state_.borrow_mut().document_visible = visible;
+        }) as Box<dyn FnMut(_)>);
+        document.add_event_listener_with_callback(
+            "visibilitychange",
+            on_visibility_change.as_ref().unchecked_ref(),
+        )?;
+        on_visibility_change.forget();
+
+        Ok(Self { state })
+    }
+
+    pub fn is_visible(&self, id: &str) -> bool {
+        if FORCE_VISIBLE.with(Cell::get) {
+            return true;
+        }
+        let state = self.state.borrow();
+        state.document_visible && state.canvases.get(id).copied().unwrap_or(true)
+    }
+
+    pub fn any_visible(&self, ids: &[&str]) -> bool {
+        ids.iter().any(|id| self.is_visible(id))
+    }
+}